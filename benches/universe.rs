@@ -0,0 +1,102 @@
+//! Throughput of the per-asset pipeline on a synthetic universe (500 assets x 3000 days by
+//! default; override with `BENCH_ASSETS` and `BENCH_DAYS`).
+//!
+//! ```text
+//! cargo bench --bench universe
+//! BENCH_ASSETS=50 BENCH_DAYS=1000 cargo bench --bench universe
+//! ```
+
+use std::fmt::Write as _;
+use std::fs;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::{StrategyArgs, analyzer, strategy};
+
+fn env_or(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Random-walk OHLCV CSV with a per-asset drift; assets list on staggered dates.
+fn write_series(path: &Path, rng: &mut StdRng, days: usize, listed: usize) {
+    let start = NaiveDate::from_ymd_opt(2015, 1, 1).unwrap();
+    let drift = rng.random_range(-0.0005..0.001);
+    let mut px = rng.random_range(1.0..1000.0);
+    let mut csv = String::from("date,open,high,low,close,volume\n");
+    for d in listed..days {
+        let open: f64 = px;
+        px *= 1.0 + drift + rng.random_range(-0.04..0.04);
+        let high = open.max(px) * (1.0 + rng.random_range(0.0..0.02));
+        let low = open.min(px) * (1.0 - rng.random_range(0.0..0.02));
+        let volume = rng.random_range(1e5..1e8);
+        let date = start + chrono::Duration::days(i64::try_from(d).unwrap());
+        writeln!(csv, "{date},{open:.6},{high:.6},{low:.6},{px:.6},{volume:.2}").unwrap();
+    }
+    fs::write(path, csv).unwrap();
+}
+
+fn universe(dir: &Path, assets: usize, days: usize) -> (PathBuf, Vec<PathBuf>) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let btc = dir.join("BTC.csv");
+    write_series(&btc, &mut rng, days, 0);
+    let paths = (0..assets)
+        .map(|i| {
+            let path = dir.join(format!("A{i:03}_asset{i}.csv"));
+            let listed = rng.random_range(0..days / 3);
+            write_series(&path, &mut rng, days, listed);
+            path
+        })
+        .collect();
+    (btc, paths)
+}
+
+fn bench_universe(c: &mut Criterion) {
+    let (assets, days) = (env_or("BENCH_ASSETS", 500), env_or("BENCH_DAYS", 3000));
+    let tmp = tempfile::tempdir().unwrap();
+    let (btc, asset_paths) = universe(tmp.path(), assets, days);
+    let out = tmp.path().join("out");
+    let cli = StrategyArgs {
+        btc: Some(btc),
+        assets: Some(asset_paths),
+        out: Some(out.clone()),
+        record: Some(false),
+        ..StrategyArgs::default()
+    };
+    let args: StrategyArgs = Resolved::from_sources(None, None, &[])
+        .unwrap()
+        .with_cli("strategy", &cli)
+        .unwrap();
+
+    let mut group = c.benchmark_group(format!("universe_{assets}x{days}"));
+    group.sample_size(10);
+    group.throughput(Throughput::Elements((assets * days) as u64));
+
+    group.bench_function("load_market_data", |b| {
+        b.iter(|| strategy::load_market_data(black_box(&args)).unwrap());
+    });
+    let data = strategy::load_market_data(&args).unwrap();
+    group.bench_function("run_backtest", |b| {
+        b.iter(|| strategy::run_backtest(black_box(&data), &args).unwrap());
+    });
+    let result = strategy::backtest(&args).unwrap();
+    group.bench_function("write_outputs", |b| {
+        b.iter(|| strategy::write_outputs(black_box(&result), &out).unwrap());
+    });
+    let signals_dir = out.to_str().unwrap();
+    group.bench_function("analyze_signals_directory", |b| {
+        b.iter(|| analyzer::analyze_signals_directory(black_box(signals_dir)).unwrap());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_universe);
+criterion_main!(benches);
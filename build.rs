@@ -0,0 +1,25 @@
+//! Records the git commit the binary is built from, for run manifests (`runs.rs`).
+
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).output().ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn main() {
+    // Commits, checkouts and staging move these; edits to the sources change the dirty flag
+    for path in [".git/HEAD", ".git/index", ".git/refs", "src", "Cargo.toml"] {
+        println!("cargo:rerun-if-changed={path}");
+    }
+    // Builds outside a git checkout (e.g. a packaged crate or a Docker context without
+    // `.git`) leave both unset
+    if let Some(commit) = git(&["rev-parse", "HEAD"]) {
+        println!("cargo:rustc-env=GIT_COMMIT={commit}");
+        let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+            .is_some_and(|s| !s.is_empty());
+        println!("cargo:rustc-env=GIT_DIRTY={dirty}");
+    }
+}
@@ -0,0 +1,143 @@
+//! The trading account playbooks size against: free cash, open positions and the currency
+//! both are quoted in. Read from a JSON file (`--account`), or all cash at
+//! `--portfolio-value` when no file is given.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::TradeArgs;
+use crate::error::{Context, Error, Required, Result};
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+/// An open position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub asset: String,
+    pub quantity: f64,
+    /// Average entry price; values the position until it has a mark
+    pub avg_price: f64,
+    /// Latest price, set by [`Account::mark`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mark: Option<f64>,
+}
+
+impl Holding {
+    #[must_use]
+    pub fn value(&self) -> f64 {
+        self.quantity * self.mark.unwrap_or(self.avg_price)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Free cash
+    pub cash: f64,
+    #[serde(default)]
+    pub positions: Vec<Holding>,
+}
+
+impl Account {
+    /// An account holding only `cash`.
+    #[must_use]
+    pub fn cash_only(cash: f64, currency: &str) -> Self {
+        Self {
+            currency: currency.to_string(),
+            cash,
+            positions: Vec::new(),
+        }
+    }
+
+    /// Read an account file, e.g.
+    /// `{"currency": "USD", "cash": 42000, "positions": [{"asset": "ETH_ethereum", "quantity": 3.5, "avg_price": 2400}]}`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, or holds a negative or
+    /// non-finite amount.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let account: Self = serde_json::from_str(&text)
+            .map_err(|e| Error::config(format!("account file {}: {e}", path.display())))?;
+        account.validate()?;
+        Ok(account)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !self.cash.is_finite() {
+            return Err(Error::config("account cash must be a finite number"));
+        }
+        for h in &self.positions {
+            if !(h.quantity.is_finite() && h.quantity >= 0.0) {
+                return Err(Error::config(format!(
+                    "account position {}: quantity must be a non-negative number",
+                    h.asset
+                )));
+            }
+            if !(h.avg_price.is_finite() && h.avg_price > 0.0) {
+                return Err(Error::config(format!(
+                    "account position {}: avg_price must be positive",
+                    h.asset
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The `--account` file, or all cash at `--portfolio-value` in `--currency`.
+    ///
+    /// # Errors
+    /// Returns an error if the account file is invalid, or there is none and `portfolio_value`
+    /// or `currency` is not set.
+    pub fn from_args(args: &TradeArgs) -> Result<Self> {
+        match &args.account {
+            Some(path) => Self::load(path),
+            None => Ok(Self::cash_only(
+                args.portfolio_value.required("portfolio_value")?,
+                args.currency.as_deref().required("currency")?,
+            )),
+        }
+    }
+
+    /// Value positions at `prices` (latest closes by asset); others keep their last mark.
+    pub fn mark(&mut self, prices: &BTreeMap<String, f64>) {
+        for h in &mut self.positions {
+            if let Some(&px) = prices.get(&h.asset) {
+                h.mark = Some(px);
+            }
+        }
+    }
+
+    /// Cash plus marked positions.
+    #[must_use]
+    pub fn equity(&self) -> f64 {
+        self.cash + self.positions.iter().map(Holding::value).sum::<f64>()
+    }
+
+    /// Units of `asset` held.
+    #[must_use]
+    pub fn quantity(&self, asset: &str) -> f64 {
+        self.positions
+            .iter()
+            .filter(|h| h.asset == asset)
+            .fold(0.0, |total, h| total + h.quantity)
+    }
+
+    /// One-line description for console output.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "equity {:.2} {cur} (cash {:.2} {cur}, {} open positions)",
+            self.equity(),
+            self.cash,
+            self.positions.len(),
+            cur = self.currency
+        )
+    }
+}
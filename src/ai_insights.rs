@@ -1,6 +1,46 @@
-use anyhow::Result;
+use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::time::Duration;
+
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+pub const DEFAULT_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// `OpenAI` request settings; every field falls back to the built-in value when unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AiSettings {
+    /// Use the API at all; `false` always produces the rule-based fallback
+    pub enabled: Option<bool>,
+    /// Chat completion model
+    pub model: Option<String>,
+    /// Chat completions endpoint
+    pub api_url: Option<String>,
+    /// Request timeout (seconds)
+    pub timeout_secs: Option<u64>,
+}
+
+impl AiSettings {
+    fn model(&self) -> &str {
+        self.model.as_deref().unwrap_or(DEFAULT_MODEL)
+    }
+
+    fn api_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or(DEFAULT_API_URL)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+    /// API key, or `None` when AI is disabled or `OPENAI_API_KEY` is not set
+    fn api_key(&self) -> Option<String> {
+        if self.enabled == Some(false) {
+            return None;
+        }
+        env::var("OPENAI_API_KEY").ok()
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetInsights {
@@ -35,16 +75,20 @@ pub struct AssetMetrics {
     pub rs_ma30: f64,
     pub atr_14: f64,
     pub volatility: f64,
+    pub market_regime: Option<String>,
 }
 
 /// Generate AI-powered insights for a trading asset based on its performance data
-pub async fn generate_asset_insights(metrics: &AssetMetrics) -> Result<AssetInsights> {
+pub async fn generate_asset_insights(
+    metrics: &AssetMetrics,
+    ai: &AiSettings,
+) -> Result<AssetInsights> {
     // Check if OpenAI API key is available
-    let api_key = match env::var("OPENAI_API_KEY") {
-        Ok(key) => key,
-        Err(_) => {
-            println!(
-                "⚠️  OPENAI_API_KEY not set, using fallback analysis for {}",
+    let api_key = match ai.api_key() {
+        Some(key) => key,
+        None => {
+            eprintln!(
+                "⚠️  AI disabled or OPENAI_API_KEY not set, using fallback analysis for {}",
                 metrics.asset
             );
             return Ok(generate_fallback_insights(
@@ -58,8 +102,9 @@ pub async fn generate_asset_insights(metrics: &AssetMetrics) -> Result<AssetInsi
     };
 
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+        .timeout(ai.timeout())
+        .build()
+        .map_err(Error::ai)?;
 
     let prompt = format!(
         r#"You are a quantitative trading analyst specializing in cryptocurrency momentum strategies. Analyze this trading strategy performance and provide actionable insights.
@@ -82,9 +127,11 @@ CURRENT MARKET DATA:
 - ATR(14): ${:.2}
 - Volatility: {:.2}%
 
+MARKET REGIME: {}
+
 Please provide:
 1. 3-5 specific trading notes (execution tips, market conditions, risk factors)
-2. Risk assessment (1-2 sentences on risk level and key concerns)
+2. Risk assessment (1-2 sentences on risk level and key concerns, taking the market regime into account)
 3. 2-3 execution recommendations (entry/exit strategies, position sizing)
 4. Market context (1-2 sentences on current market conditions and outlook)
 
@@ -108,11 +155,12 @@ IMPORTANT: Respond with ONLY valid JSON in this exact format (no markdown, no ex
         metrics.rs_ma7,
         metrics.rs_ma30,
         metrics.atr_14,
-        metrics.volatility
+        metrics.volatility,
+        metrics.market_regime.as_deref().unwrap_or("unknown")
     );
 
     let request_body = serde_json::json!({
-        "model": "gpt-4o-mini",
+        "model": ai.model(),
         "messages": [
             {
                 "role": "user",
@@ -124,16 +172,17 @@ IMPORTANT: Respond with ONLY valid JSON in this exact format (no markdown, no ex
     });
 
     let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+        .post(ai.api_url())
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await?;
+        .await
+        .map_err(Error::ai)?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await?;
-        println!(
+        let error_text = response.text().await.map_err(Error::ai)?;
+        eprintln!(
             "⚠️  OpenAI API error for {}: {}. Using fallback analysis.",
             metrics.asset, error_text
         );
@@ -146,23 +195,23 @@ IMPORTANT: Respond with ONLY valid JSON in this exact format (no markdown, no ex
         ));
     }
 
-    let response_json: serde_json::Value = response.json().await?;
+    let response_json: serde_json::Value = response.json().await.map_err(Error::ai)?;
 
     // Check if the response has the expected structure
     let choices = response_json["choices"].as_array()
-        .ok_or_else(|| anyhow::anyhow!("Invalid response structure: no choices array"))?;
+        .ok_or_else(|| Error::ai("Invalid response structure: no choices array"))?;
 
     if choices.is_empty() {
-        return Err(anyhow::anyhow!("No choices in OpenAI response"));
+        return Err(Error::ai("No choices in OpenAI response"));
     }
 
     let content = choices[0]["message"]["content"]
         .as_str()
-        .ok_or_else(|| anyhow::anyhow!("No content in OpenAI response"))?;
+        .ok_or_else(|| Error::ai("No content in OpenAI response"))?;
 
     // Debug: Print the raw content to understand what we're getting
     if content.trim().is_empty() {
-        println!(
+        eprintln!(
             "⚠️  Empty OpenAI response for {}. Using fallback analysis.",
             metrics.asset
         );
@@ -200,7 +249,7 @@ IMPORTANT: Respond with ONLY valid JSON in this exact format (no markdown, no ex
             market_context: response.market_context,
         }),
         Err(e) => {
-            println!(
+            eprintln!(
                 "⚠️  Failed to parse OpenAI response for {}: {}. Raw content: '{}'. Using fallback analysis.",
                 metrics.asset, e, json_content
             );
@@ -216,6 +265,7 @@ IMPORTANT: Respond with ONLY valid JSON in this exact format (no markdown, no ex
 }
 
 /// Generate portfolio-level insights based on overall strategy performance
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_portfolio_insights(
     total_strategies: usize,
     profitable_strategies: usize,
@@ -224,11 +274,12 @@ pub async fn generate_portfolio_insights(
     avg_win_rate: f64,
     top_performers: Vec<(String, f64)>,
     market_conditions: &str,
+    ai: &AiSettings,
 ) -> Result<String> {
     // Check if OpenAI API key is available
-    let api_key = match env::var("OPENAI_API_KEY") {
-        Ok(key) => key,
-        Err(_) => {
+    let api_key = match ai.api_key() {
+        Some(key) => key,
+        None => {
             let success_rate = (profitable_strategies as f64 / total_strategies as f64) * 100.0;
             return Ok(format!(
                 "Portfolio Analysis: {} profitable strategies out of {} total ({:.1}% success rate). \
@@ -246,8 +297,9 @@ pub async fn generate_portfolio_insights(
     };
 
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+        .timeout(ai.timeout())
+        .build()
+        .map_err(Error::ai)?;
 
     let top_performers_str = top_performers
         .iter()
@@ -288,7 +340,7 @@ Be specific and actionable for a quantitative trader."#,
     );
 
     let request_body = serde_json::json!({
-        "model": "gpt-4o-mini",
+        "model": ai.model(),
         "messages": [
             {
                 "role": "user",
@@ -300,16 +352,17 @@ Be specific and actionable for a quantitative trader."#,
     });
 
     let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+        .post(ai.api_url())
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await?;
+        .await
+        .map_err(Error::ai)?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await?;
-        println!(
+        let error_text = response.text().await.map_err(Error::ai)?;
+        eprintln!(
             "⚠️  OpenAI API error for portfolio analysis: {}. Using fallback analysis.",
             error_text
         );
@@ -328,10 +381,10 @@ Be specific and actionable for a quantitative trader."#,
         ));
     }
 
-    let response_json: serde_json::Value = response.json().await?;
+    let response_json: serde_json::Value = response.json().await.map_err(Error::ai)?;
     let content = response_json["choices"][0]["message"]["content"]
         .as_str()
-        .ok_or_else(|| anyhow::anyhow!("No content in OpenAI response"))?;
+        .ok_or_else(|| Error::ai("No content in OpenAI response"))?;
 
     Ok(content.to_string())
 }
@@ -342,11 +395,12 @@ pub async fn generate_market_context(
     eth_price: f64,
     market_cap_change: f64,
     fear_greed_index: Option<i32>,
+    ai: &AiSettings,
 ) -> Result<String> {
     // Check if OpenAI API key is available
-    let api_key = match env::var("OPENAI_API_KEY") {
-        Ok(key) => key,
-        Err(_) => {
+    let api_key = match ai.api_key() {
+        Some(key) => key,
+        None => {
             let sentiment = if market_cap_change > 5.0 {
                 "bullish"
             } else if market_cap_change < -5.0 {
@@ -369,8 +423,9 @@ pub async fn generate_market_context(
     };
 
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+        .timeout(ai.timeout())
+        .build()
+        .map_err(Error::ai)?;
 
     let fear_greed_str = match fear_greed_index {
         Some(index) => format!("Fear & Greed Index: {}", index),
@@ -397,7 +452,7 @@ Be concise and actionable for traders."#,
     );
 
     let request_body = serde_json::json!({
-        "model": "gpt-4o-mini",
+        "model": ai.model(),
         "messages": [
             {
                 "role": "user",
@@ -409,16 +464,17 @@ Be concise and actionable for traders."#,
     });
 
     let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+        .post(ai.api_url())
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
-        .await?;
+        .await
+        .map_err(Error::ai)?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await?;
-        println!(
+        let error_text = response.text().await.map_err(Error::ai)?;
+        eprintln!(
             "⚠️  OpenAI API error for market context: {}. Using fallback analysis.",
             error_text
         );
@@ -442,10 +498,10 @@ Be concise and actionable for traders."#,
         ));
     }
 
-    let response_json: serde_json::Value = response.json().await?;
+    let response_json: serde_json::Value = response.json().await.map_err(Error::ai)?;
     let content = response_json["choices"][0]["message"]["content"]
         .as_str()
-        .ok_or_else(|| anyhow::anyhow!("No content in OpenAI response"))?;
+        .ok_or_else(|| Error::ai("No content in OpenAI response"))?;
 
     Ok(content.to_string())
 }
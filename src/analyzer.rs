@@ -1,10 +1,17 @@
-use anyhow::Result;
+use crate::error::Result;
 use chrono::NaiveDate;
 use csv::ReaderBuilder;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::benchmark::{self, RelativeStats};
+use crate::metrics::{self, Metrics};
+use crate::overfit::{self, Pbo};
+use crate::strategy::{Backtest, DailySignal};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalRow {
     date: NaiveDate,
@@ -20,10 +27,15 @@ pub struct SignalRow {
     score: f64,
     raw_weight: f64,
     stop_level: Option<f64>,
+    #[serde(default)]
+    adv: Option<f64>,
 }
 
 impl SignalRow {
     // Getter methods for trade module
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
     pub fn close(&self) -> f64 {
         self.close
     }
@@ -39,66 +51,171 @@ impl SignalRow {
     pub fn rs_ma_long(&self) -> Option<f64> {
         self.rs_ma_long
     }
+    pub fn adv(&self) -> Option<f64> {
+        self.adv
+    }
+    pub fn stop_level(&self) -> Option<f64> {
+        self.stop_level
+    }
+}
+
+impl From<&DailySignal> for SignalRow {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(s: &DailySignal) -> Self {
+        Self {
+            date: s.date(),
+            close: s.price(),
+            ma_short: s.ma_short(),
+            ma_long: s.ma_long(),
+            rs: s.rs(),
+            rs_ma_short: s.rs_ma_short(),
+            rs_ma_long: s.rs_ma_long(),
+            trend_bull: s.trend_bull(),
+            mom_bull: s.mom_bull(),
+            rs_bull: s.rs_bull(),
+            score: s.score() as f64,
+            raw_weight: s.raw_weight(),
+            stop_level: s.stop_level(),
+            adv: s.adv(),
+        }
+    }
+}
+
+/// Round-trip statistics: a trade is a run of consecutive days held on the same side.
+#[derive(Debug, Clone, Default)]
+pub struct TradeStats {
+    pub trades: usize,
+    pub win_rate: f64,
+    /// Mean compounded return per trade
+    pub avg_return: f64,
+    pub avg_win: f64,
+    pub avg_loss: f64,
+    pub best: f64,
+    pub worst: f64,
+    pub avg_holding_days: f64,
+}
+
+impl TradeStats {
+    fn from_trades(trades: &[(f64, usize)]) -> Self {
+        if trades.is_empty() {
+            return Self::default();
+        }
+        let n = trades.len() as f64;
+        let mean = |x: &[f64]| {
+            if x.is_empty() {
+                0.0
+            } else {
+                x.iter().sum::<f64>() / x.len() as f64
+            }
+        };
+        let wins: Vec<f64> = trades.iter().map(|t| t.0).filter(|r| *r > 0.0).collect();
+        let losses: Vec<f64> = trades.iter().map(|t| t.0).filter(|r| *r < 0.0).collect();
+        Self {
+            trades: trades.len(),
+            win_rate: wins.len() as f64 / n,
+            avg_return: trades.iter().map(|t| t.0).sum::<f64>() / n,
+            avg_win: mean(&wins),
+            avg_loss: mean(&losses),
+            best: trades.iter().map(|t| t.0).fold(f64::NEG_INFINITY, f64::max),
+            worst: trades.iter().map(|t| t.0).fold(f64::INFINITY, f64::min),
+            avg_holding_days: trades.iter().map(|t| t.1 as f64).sum::<f64>() / n,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct StrategyAnalysis {
     asset: String,
     total_days: usize,
+    /// Days on which a position was held (previous day's weight non-zero)
     trading_days: usize,
-    total_return: f64,
     max_return: f64,
     min_return: f64,
-    win_rate: f64,
     avg_win: f64,
     avg_loss: f64,
     profit_factor: f64,
-    max_drawdown: f64,
-    sharpe_ratio: f64,
+    /// Shared metric suite over every bar after the first (flat days earn zero)
+    metrics: Metrics,
+    /// Relative to BTC buy-and-hold (BTC close recovered as close / rs)
+    vs_btc: Option<RelativeStats>,
+    /// Relative to the equal-weight universe of every analyzed asset
+    vs_universe: Option<RelativeStats>,
+    /// Per-bar strategy returns aligned with `signals[1..]`
+    bar_returns: Vec<f64>,
+    /// Probabilistic Sharpe ratio: P(true Sharpe > 0)
+    psr: f64,
+    /// Deflated Sharpe ratio across every asset screened alongside this one
+    dsr: f64,
+    trade_stats: TradeStats,
+    returns: Vec<f64>,
+    return_dates: Vec<NaiveDate>,
     signals: Vec<SignalRow>,
 }
 
 impl StrategyAnalysis {
+    /// Analyze one asset's signals. The weight decided at day t-1's close earns the
+    /// close-to-close return of day t.
     pub fn new(asset: String, signals: Vec<SignalRow>) -> Self {
         let total_days = signals.len();
-        let trading_days = signals.iter().filter(|s| s.raw_weight.abs() > 1e-6).count();
 
-        // Calculate returns
         let mut returns = Vec::new();
-        let mut cumulative_return = 1.0;
-        let mut max_cumulative = 1.0;
-        let mut max_drawdown = 0.0;
-
-        for signal in &signals {
-            if signal.raw_weight.abs() > 1e-6 {
-                let daily_return =
-                    signal.raw_weight * (signal.close - signals[0].close) / signals[0].close;
-                cumulative_return *= 1.0 + daily_return;
-                returns.push(daily_return);
-
-                if cumulative_return > max_cumulative {
-                    max_cumulative = cumulative_return;
-                }
-                let drawdown = (max_cumulative - cumulative_return) / max_cumulative;
-                if drawdown > max_drawdown {
-                    max_drawdown = drawdown;
-                }
+        let mut return_dates = Vec::new();
+        let mut bar_returns = Vec::with_capacity(total_days);
+        let mut bar_exposure = Vec::with_capacity(total_days);
+        let mut trades: Vec<(f64, usize)> = Vec::new();
+        let mut open: Option<(f64, f64, usize)> = None; // (side, growth, days)
+
+        for pair in signals.windows(2) {
+            let (prev, today) = (&pair[0], &pair[1]);
+            let weight = prev.raw_weight;
+            let side = weight.signum();
+
+            // A trade ends when the position goes flat or flips side
+            if let Some((open_side, growth, days)) = open
+                && (weight.abs() <= 1e-6 || side != open_side)
+            {
+                trades.push((growth - 1.0, days));
+                open = None;
             }
+            if weight.abs() <= 1e-6 || prev.close <= 0.0 {
+                bar_returns.push(0.0);
+                bar_exposure.push(0.0);
+                continue;
+            }
+
+            let daily_return = weight * (today.close / prev.close - 1.0);
+            bar_returns.push(daily_return);
+            bar_exposure.push(weight.abs());
+            returns.push(daily_return);
+            return_dates.push(today.date);
+
+            let (_, growth, days) = open.get_or_insert((side, 1.0, 0));
+            *growth *= 1.0 + daily_return;
+            *days += 1;
+        }
+        if let Some((_, growth, days)) = open {
+            trades.push((growth - 1.0, days));
         }
 
-        let total_return = cumulative_return - 1.0;
+        let trading_days = returns.len();
+        let dates: Vec<NaiveDate> = signals.iter().skip(1).map(|s| s.date).collect();
+        let periods_per_year = metrics::periods_per_year(&dates);
+        let metrics = metrics::compute(&bar_returns, &dates, periods_per_year, &[], &bar_exposure);
+        let btc_closes: Vec<Option<f64>> = signals
+            .iter()
+            .map(|s| s.rs.filter(|rs| *rs > 0.0).map(|rs| s.close / rs))
+            .collect();
+        let vs_btc = btc_closes.iter().any(Option::is_some).then(|| {
+            let btc_returns = benchmark::buy_and_hold(&btc_closes);
+            benchmark::relative(&bar_returns, &btc_returns[1..], periods_per_year)
+        });
         let max_return = returns.iter().fold(0.0f64, |acc, &x| acc.max(x));
         let min_return = returns.iter().fold(0.0f64, |acc, &x| acc.min(x));
 
-        // Calculate win rate and profit factor
+        // Calculate average win/loss and profit factor
         let wins: Vec<f64> = returns.iter().filter(|&&x| x > 0.0).copied().collect();
         let losses: Vec<f64> = returns.iter().filter(|&&x| x < 0.0).copied().collect();
 
-        let win_rate = if returns.is_empty() {
-            0.0
-        } else {
-            wins.len() as f64 / returns.len() as f64
-        };
         let avg_win = if wins.is_empty() {
             0.0
         } else {
@@ -118,43 +235,33 @@ impl StrategyAnalysis {
             total_wins / total_losses
         };
 
-        // Calculate Sharpe ratio (simplified)
-        let mean_return = if returns.is_empty() {
-            0.0
-        } else {
-            returns.iter().sum::<f64>() / returns.len() as f64
-        };
-        let variance = if returns.len() <= 1 {
-            0.0
-        } else {
-            let mean = mean_return;
-            returns.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64
-        };
-        let sharpe_ratio = if variance == 0.0 {
-            0.0
-        } else {
-            mean_return / variance.sqrt()
-        };
+        // Same series as every other metric: flat days count as zero returns
+        let psr = overfit::probabilistic_sharpe(&bar_returns, 0.0);
 
         Self {
             asset,
             total_days,
             trading_days,
-            total_return,
             max_return,
             min_return,
-            win_rate,
             avg_win,
             avg_loss,
             profit_factor,
-            max_drawdown,
-            sharpe_ratio,
+            metrics,
+            vs_btc,
+            vs_universe: None,
+            bar_returns,
+            psr,
+            dsr: psr,
+            trade_stats: TradeStats::from_trades(&trades),
+            returns,
+            return_dates,
             signals,
         }
     }
 
     pub fn is_profitable(&self) -> bool {
-        self.total_return > 0.0 && self.win_rate > 0.5 && self.profit_factor > 1.0
+        self.metrics.total_return > 0.0 && self.metrics.win_rate > 0.5 && self.profit_factor > 1.0
     }
 
     // Getter methods for trade module
@@ -162,16 +269,16 @@ impl StrategyAnalysis {
         &self.asset
     }
     pub fn total_return(&self) -> f64 {
-        self.total_return
+        self.metrics.total_return
     }
     pub fn sharpe_ratio(&self) -> f64 {
-        self.sharpe_ratio
+        self.metrics.sharpe
     }
     pub fn win_rate(&self) -> f64 {
-        self.win_rate
+        self.metrics.win_rate
     }
     pub fn max_drawdown(&self) -> f64 {
-        self.max_drawdown
+        self.metrics.max_drawdown
     }
     pub fn trading_days(&self) -> usize {
         self.trading_days
@@ -182,20 +289,116 @@ impl StrategyAnalysis {
     pub fn profit_factor(&self) -> f64 {
         self.profit_factor
     }
+    pub fn total_days(&self) -> usize {
+        self.total_days
+    }
+    pub fn avg_win(&self) -> f64 {
+        self.avg_win
+    }
+    pub fn avg_loss(&self) -> f64 {
+        self.avg_loss
+    }
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+    pub fn vs_btc(&self) -> Option<&RelativeStats> {
+        self.vs_btc.as_ref()
+    }
+    pub fn vs_universe(&self) -> Option<&RelativeStats> {
+        self.vs_universe.as_ref()
+    }
+    pub fn trade_stats(&self) -> &TradeStats {
+        &self.trade_stats
+    }
+    pub fn returns(&self) -> &[f64] {
+        &self.returns
+    }
+    /// Strategy return of every bar, flat days included as zero
+    pub fn bar_returns(&self) -> &[f64] {
+        &self.bar_returns
+    }
+    pub fn psr(&self) -> f64 {
+        self.psr
+    }
+    pub fn dsr(&self) -> f64 {
+        self.dsr
+    }
 
     pub fn print_summary(&self) {
         println!("📊 {} Analysis", self.asset);
         println!("   Total Days: {}", self.total_days);
         println!("   Trading Days: {}", self.trading_days);
-        println!("   Total Return: {:.2}%", self.total_return * 100.0);
+        println!("   Total Return: {:.2}%", self.metrics.total_return * 100.0);
         println!("   Max Return: {:.2}%", self.max_return * 100.0);
         println!("   Min Return: {:.2}%", self.min_return * 100.0);
-        println!("   Win Rate: {:.1}%", self.win_rate * 100.0);
+        println!("   Win Rate: {:.1}%", self.metrics.win_rate * 100.0);
         println!("   Avg Win: {:.2}%", self.avg_win * 100.0);
         println!("   Avg Loss: {:.2}%", self.avg_loss * 100.0);
         println!("   Profit Factor: {:.2}", self.profit_factor);
-        println!("   Max Drawdown: {:.2}%", self.max_drawdown * 100.0);
-        println!("   Sharpe Ratio: {:.2}", self.sharpe_ratio);
+        println!("   Max Drawdown: {:.2}%", self.metrics.max_drawdown * 100.0);
+        println!(
+            "   Sharpe Ratio: {:.2} (PSR {:.1}%, DSR {:.1}%)",
+            self.metrics.sharpe,
+            self.psr * 100.0,
+            self.dsr * 100.0
+        );
+        let m = &self.metrics;
+        println!(
+            "   Sortino / Calmar / Omega: {:.2} / {:.2} / {:.2}",
+            m.sortino, m.calmar, m.omega
+        );
+        println!(
+            "   Ulcer Index: {:.2}, Max DD Duration: {} days, Time in Market: {:.1}%",
+            m.ulcer_index * 100.0,
+            m.max_drawdown_duration,
+            m.time_in_market * 100.0
+        );
+        println!(
+            "   VaR/CVaR 95%: {:.2}% / {:.2}%, 99%: {:.2}% / {:.2}%",
+            m.var_95 * 100.0,
+            m.cvar_95 * 100.0,
+            m.var_99 * 100.0,
+            m.cvar_99 * 100.0
+        );
+        println!(
+            "   Best / Worst Month: {:.2}% / {:.2}%",
+            m.best_month * 100.0,
+            m.worst_month * 100.0
+        );
+        for (label, rel) in [("BTC", &self.vs_btc), ("Universe (EW)", &self.vs_universe)] {
+            if let Some(r) = rel {
+                println!(
+                    "   vs {}: alpha {:.2}%, beta {:.2}, corr {:.2}, IR {:.2}, capture {:.2}/{:.2}",
+                    label,
+                    r.alpha * 100.0,
+                    r.beta,
+                    r.correlation,
+                    r.information_ratio,
+                    r.up_capture,
+                    r.down_capture
+                );
+            }
+        }
+        let t = &self.trade_stats;
+        println!(
+            "   Trades: {} (win rate {:.1}%, avg {:.2}%, avg hold {:.1} days)",
+            t.trades,
+            t.win_rate * 100.0,
+            t.avg_return * 100.0,
+            t.avg_holding_days
+        );
+        println!(
+            "   Avg Winning / Losing Trade: {:.2}% / {:.2}%",
+            t.avg_win * 100.0,
+            t.avg_loss * 100.0
+        );
+        if t.trades > 0 {
+            println!(
+                "   Best / Worst Trade: {:.2}% / {:.2}%",
+                t.best * 100.0,
+                t.worst * 100.0
+            );
+        }
         println!();
     }
 
@@ -255,38 +458,130 @@ pub fn read_signals_file(path: &PathBuf) -> Result<Vec<SignalRow>> {
     Ok(signals)
 }
 
-pub fn analyze_signals_directory(signals_dir: &str) -> Result<Vec<StrategyAnalysis>> {
-    let mut analyses = Vec::new();
-    let entries = fs::read_dir(signals_dir)?;
-
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() && path.extension().unwrap_or_default() == "csv" {
-            let filename = path.file_name().unwrap().to_string_lossy();
-            if filename.starts_with("signals_") {
-                let asset = filename
-                    .strip_prefix("signals_")
-                    .unwrap()
-                    .strip_suffix(".csv")
-                    .unwrap()
-                    .to_string();
-
-                match read_signals_file(&path) {
-                    Ok(signals) => {
-                        let analysis = StrategyAnalysis::new(asset, signals);
-                        analyses.push(analysis);
-                    }
-                    Err(e) => {
-                        eprintln!("Warning: Failed to read {}: {}", filename, e);
-                    }
-                }
+/// Deflate every analysis' Sharpe by the number of assets screened together.
+pub fn apply_overfitting_diagnostics(analyses: &mut [StrategyAnalysis]) {
+    let trials: Vec<&[f64]> = analyses.iter().map(|a| a.bar_returns.as_slice()).collect();
+    let diagnostics = overfit::sharpe_diagnostics(&trials);
+    for (analysis, d) in analyses.iter_mut().zip(diagnostics) {
+        analysis.psr = d.psr;
+        analysis.dsr = d.dsr;
+    }
+}
+
+/// Compare every analysis with the equal-weight universe of all analyzed assets: each date
+/// averages the close-to-close returns of the assets with signals on both days.
+#[allow(clippy::cast_precision_loss)]
+pub fn apply_universe_benchmark(analyses: &mut [StrategyAnalysis]) {
+    let mut universe: BTreeMap<NaiveDate, (f64, usize)> = BTreeMap::new();
+    for a in analyses.iter() {
+        for pair in a.signals.windows(2) {
+            if pair[0].close > 0.0 {
+                let e = universe.entry(pair[1].date).or_insert((0.0, 0));
+                e.0 += pair[1].close / pair[0].close - 1.0;
+                e.1 += 1;
             }
         }
     }
+    for a in analyses.iter_mut() {
+        let bench: Vec<f64> = a
+            .signals
+            .iter()
+            .skip(1)
+            .map(|s| universe.get(&s.date).map_or(0.0, |(sum, n)| sum / *n as f64))
+            .collect();
+        a.vs_universe = Some(benchmark::relative(
+            &a.bar_returns,
+            &bench,
+            a.metrics.periods_per_year,
+        ));
+    }
+}
+
+/// Probability that picking the best asset by Sharpe is overfit (CSCV over date-aligned
+/// strategy returns, flat days counted as zero).
+pub fn screening_pbo(analyses: &[StrategyAnalysis]) -> Option<Pbo> {
+    let dates: BTreeSet<NaiveDate> = analyses
+        .iter()
+        .flat_map(|a| a.return_dates.iter().copied())
+        .collect();
+    let matrix: Vec<Vec<f64>> = analyses
+        .iter()
+        .map(|a| {
+            let by_date: BTreeMap<NaiveDate, f64> = a
+                .return_dates
+                .iter()
+                .copied()
+                .zip(a.returns.iter().copied())
+                .collect();
+            dates
+                .iter()
+                .map(|d| by_date.get(d).copied().unwrap_or(0.0))
+                .collect()
+        })
+        .collect();
+    overfit::pbo_cscv(&matrix, 16)
+}
 
-    Ok(analyses)
+/// Analyze every asset's signals together, applying the cross-asset diagnostics.
+pub fn analyze(signals: impl IntoIterator<Item = (String, Vec<SignalRow>)>) -> Vec<StrategyAnalysis> {
+    let mut analyses: Vec<StrategyAnalysis> = signals
+        .into_iter()
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(asset, rows)| StrategyAnalysis::new(asset, rows))
+        .collect();
+    apply_overfitting_diagnostics(&mut analyses);
+    apply_universe_benchmark(&mut analyses);
+    analyses
+}
+
+/// Analyze the signals of an in-memory backtest, skipping days an asset was not tradeable
+/// just as the signal CSVs do.
+pub fn analyze_backtest(bt: &Backtest) -> Vec<StrategyAnalysis> {
+    analyze(bt.signals.iter().map(|(asset, signals)| {
+        let rows = signals
+            .iter()
+            .filter(|s| s.available())
+            .map(SignalRow::from)
+            .collect();
+        (asset.clone(), rows)
+    }))
+}
+
+/// Analyze every `signals_*.csv` in `signals_dir`.
+///
+/// # Errors
+/// Returns an error if the directory cannot be read.
+pub fn analyze_signals_directory(signals_dir: &str) -> Result<Vec<StrategyAnalysis>> {
+    // Sorted by asset so results do not depend on directory order
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(signals_dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        if let Some(asset) = filename
+            .strip_prefix("signals_")
+            .and_then(|f| f.strip_suffix(".csv"))
+        {
+            files.push((asset.to_string(), path.clone()));
+        }
+    }
+    files.sort();
+
+    let signals: Vec<(String, Vec<SignalRow>)> = files
+        .into_par_iter()
+        .filter_map(|(asset, path)| match read_signals_file(&path) {
+            Ok(rows) => Some((asset, rows)),
+            Err(e) => {
+                eprintln!("Warning: Failed to read {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(analyze(signals))
 }
 
 pub fn print_profitable_strategies(analyses: &[StrategyAnalysis]) {
@@ -308,62 +603,96 @@ pub fn print_profitable_strategies(analyses: &[StrategyAnalysis]) {
 
     // Sort by total return (descending)
     let mut sorted = profitable.clone();
-    sorted.sort_by(|a, b| b.total_return.partial_cmp(&a.total_return).unwrap());
+    sorted.sort_by(|a, b| b.total_return().total_cmp(&a.total_return()));
 
     println!("📈 TOP PERFORMING STRATEGIES (by Total Return)");
     println!(
-        "{:<25} {:<12} {:<10} {:<10} {:<10} {:<10} {:<10}",
-        "Asset", "Total Ret%", "Win Rate%", "Profit Factor", "Sharpe", "Max DD%", "Trading Days"
+        "{:<25} {:<12} {:<10} {:<10} {:<10} {:<8} {:<8} {:<10} {:<10}",
+        "Asset", "Total Ret%", "Win Rate%", "Profit Factor", "Sharpe", "PSR%", "DSR%", "Max DD%", "Trading Days"
     );
-    println!("{}", "-".repeat(90));
+    println!("{}", "-".repeat(106));
 
     for analysis in &sorted {
         println!(
-            "{:<25} {:<12.2} {:<10.1} {:<10.2} {:<10.2} {:<10.2} {:<10}",
+            "{:<25} {:<12.2} {:<10.1} {:<10.2} {:<10.2} {:<8.1} {:<8.1} {:<10.2} {:<10}",
             analysis.asset,
-            analysis.total_return * 100.0,
-            analysis.win_rate * 100.0,
+            analysis.total_return() * 100.0,
+            analysis.win_rate() * 100.0,
             analysis.profit_factor,
-            analysis.sharpe_ratio,
-            analysis.max_drawdown * 100.0,
+            analysis.sharpe_ratio(),
+            analysis.psr * 100.0,
+            analysis.dsr * 100.0,
+            analysis.max_drawdown() * 100.0,
             analysis.trading_days
         );
     }
     println!();
 
     // Sort by Sharpe ratio (descending)
-    sorted.sort_by(|a, b| b.sharpe_ratio.partial_cmp(&a.sharpe_ratio).unwrap());
+    sorted.sort_by(|a, b| b.sharpe_ratio().total_cmp(&a.sharpe_ratio()));
 
     println!("⚡ TOP RISK-ADJUSTED STRATEGIES (by Sharpe Ratio)");
     println!(
-        "{:<25} {:<12} {:<10} {:<10} {:<10} {:<10} {:<10}",
-        "Asset", "Sharpe", "Total Ret%", "Win Rate%", "Profit Factor", "Max DD%", "Trading Days"
+        "{:<25} {:<12} {:<8} {:<8} {:<10} {:<10} {:<10} {:<10} {:<10}",
+        "Asset", "Sharpe", "PSR%", "DSR%", "Total Ret%", "Win Rate%", "Profit Factor", "Max DD%", "Trading Days"
     );
-    println!("{}", "-".repeat(90));
+    println!("{}", "-".repeat(106));
 
     for analysis in &sorted {
         println!(
-            "{:<25} {:<12.2} {:<10.2} {:<10.1} {:<10.2} {:<10.2} {:<10}",
+            "{:<25} {:<12.2} {:<8.1} {:<8.1} {:<10.2} {:<10.1} {:<10.2} {:<10.2} {:<10}",
             analysis.asset,
-            analysis.sharpe_ratio,
-            analysis.total_return * 100.0,
-            analysis.win_rate * 100.0,
+            analysis.sharpe_ratio(),
+            analysis.psr * 100.0,
+            analysis.dsr * 100.0,
+            analysis.total_return() * 100.0,
+            analysis.win_rate() * 100.0,
             analysis.profit_factor,
-            analysis.max_drawdown * 100.0,
+            analysis.max_drawdown() * 100.0,
             analysis.trading_days
         );
     }
     println!();
 
+    // Benchmark-relative view, best alpha vs BTC first
+    let btc_alpha = |a: &StrategyAnalysis| a.vs_btc.map_or(f64::NEG_INFINITY, |r| r.alpha);
+    sorted.sort_by(|a, b| btc_alpha(b).total_cmp(&btc_alpha(a)));
+
+    println!("🏁 BENCHMARK-RELATIVE (vs BTC buy-and-hold / equal-weight universe)");
+    println!(
+        "{:<25} {:<12} {:<8} {:<8} {:<8} {:<12} {:<8} {:<8} {:<10}",
+        "Asset", "α BTC%", "β BTC", "IR BTC", "ρ BTC", "α EW%", "β EW", "IR EW", "Up/Down"
+    );
+    println!("{}", "-".repeat(106));
+
+    for analysis in &sorted {
+        let btc = analysis.vs_btc.unwrap_or_default();
+        let ew = analysis.vs_universe.unwrap_or_default();
+        println!(
+            "{:<25} {:<12.2} {:<8.2} {:<8.2} {:<8.2} {:<12.2} {:<8.2} {:<8.2} {:.2}/{:.2}",
+            analysis.asset,
+            btc.alpha * 100.0,
+            btc.beta,
+            btc.information_ratio,
+            btc.correlation,
+            ew.alpha * 100.0,
+            ew.beta,
+            ew.information_ratio,
+            btc.up_capture,
+            btc.down_capture
+        );
+    }
+    println!();
+
     // Overall statistics
     let total_strategies = analyses.len();
     let profitable_count = profitable.len();
     let avg_return: f64 =
-        profitable.iter().map(|a| a.total_return).sum::<f64>() / profitable_count as f64;
+        profitable.iter().map(|a| a.total_return()).sum::<f64>() / profitable_count as f64;
     let avg_win_rate: f64 =
-        profitable.iter().map(|a| a.win_rate).sum::<f64>() / profitable_count as f64;
+        profitable.iter().map(|a| a.win_rate()).sum::<f64>() / profitable_count as f64;
     let avg_sharpe: f64 =
-        profitable.iter().map(|a| a.sharpe_ratio).sum::<f64>() / profitable_count as f64;
+        profitable.iter().map(|a| a.sharpe_ratio()).sum::<f64>() / profitable_count as f64;
 
     println!("📊 OVERALL STATISTICS");
     println!("   Total Strategies Analyzed: {}", total_strategies);
@@ -378,6 +707,35 @@ pub fn print_profitable_strategies(analyses: &[StrategyAnalysis]) {
         avg_win_rate * 100.0
     );
     println!("   Average Sharpe (Profitable): {avg_sharpe:.2}");
+    let mean_of = |f: &dyn Fn(&StrategyAnalysis) -> Option<f64>| {
+        let v: Vec<f64> = profitable.iter().filter_map(|a| f(a)).collect();
+        v.iter().sum::<f64>() / v.len().max(1) as f64
+    };
+    println!(
+        "   Average Alpha vs BTC / EW Universe (Profitable): {:.2}% / {:.2}%",
+        mean_of(&|a| a.vs_btc.map(|r| r.alpha)) * 100.0,
+        mean_of(&|a| a.vs_universe.map(|r| r.alpha)) * 100.0
+    );
+    let beat_btc = analyses
+        .iter()
+        .filter(|a| a.vs_btc.is_some_and(|r| r.information_ratio > 0.0))
+        .count();
+    println!(
+        "   Outperforming BTC (IR > 0): {} of {}",
+        beat_btc, total_strategies
+    );
+    let deflated = profitable.iter().filter(|a| a.dsr >= 0.95).count();
+    println!(
+        "   Significant After Deflation (DSR ≥ 95%, {} trials): {}",
+        total_strategies, deflated
+    );
+    if let Some(pbo) = screening_pbo(analyses) {
+        println!(
+            "   Probability of Backtest Overfitting (CSCV, {} splits): {:.1}%",
+            pbo.splits,
+            pbo.pbo * 100.0
+        );
+    }
     println!();
 }
 
@@ -0,0 +1,307 @@
+use crate::error::Result;
+use chrono::{Datelike, NaiveDate};
+use csv::WriterBuilder;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Column name of the BTC hedge leg
+pub const HEDGE: &str = "BTC_HEDGE";
+
+/// Signal state that put a position on, taken from the prior day's signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignalState {
+    /// Threshold mode, 3/3 signals: full weight
+    Full,
+    /// Threshold mode, `min_signals` with RS bullish: half weight
+    Half,
+    CrossSectionalLong,
+    CrossSectionalShort,
+    Hedge,
+}
+
+impl SignalState {
+    /// State of a position entered on a signal with `score` and `raw_weight`.
+    #[must_use]
+    pub fn of(cross_sectional: bool, score: usize, raw_weight: f64) -> Self {
+        match (cross_sectional, raw_weight > 0.0) {
+            (true, true) => Self::CrossSectionalLong,
+            (true, false) => Self::CrossSectionalShort,
+            (false, _) if score >= 3 => Self::Full,
+            (false, _) => Self::Half,
+        }
+    }
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Full => "full_3of3",
+            Self::Half => "half_2of3_rs",
+            Self::CrossSectionalLong => "xs_long",
+            Self::CrossSectionalShort => "xs_short",
+            Self::Hedge => "btc_hedge",
+        }
+    }
+}
+
+/// One position's P&L on one day: `weight x asset_return`.
+#[derive(Debug, Clone)]
+pub struct Contribution {
+    pub asset: String,
+    pub state: SignalState,
+    pub weight: f64,
+    pub asset_return: f64,
+}
+
+impl Contribution {
+    #[must_use]
+    pub fn value(&self) -> f64 {
+        self.weight * self.asset_return
+    }
+}
+
+/// Aggregate attribution of one group (asset, signal state or month).
+#[derive(Debug, Clone, Default)]
+pub struct GroupStats {
+    pub group: String,
+    /// Position-days in the group
+    pub days: usize,
+    /// Sum of daily contributions
+    pub contribution: f64,
+    /// Share of the summed contribution of all positions
+    pub share: f64,
+    pub avg_weight: f64,
+    /// Mean signed return of the underlying while held (weight-independent)
+    pub avg_asset_return: f64,
+    /// Share of position-days with a positive contribution
+    pub hit_rate: f64,
+    /// Contribution from the max-drawdown peak to its trough
+    pub drawdown_contribution: f64,
+}
+
+/// Attribution summary of a backtest.
+#[derive(Debug, Clone, Default)]
+pub struct Attribution {
+    pub by_asset: Vec<GroupStats>,
+    pub by_state: Vec<GroupStats>,
+    /// (`YYYY-MM`, asset -> contribution)
+    pub by_month: Vec<(String, BTreeMap<String, f64>)>,
+    /// Dates bounding the max drawdown (peak, trough), if there was one
+    pub drawdown: Option<(NaiveDate, NaiveDate)>,
+}
+
+/// Index range of days (peak, trough] of the deepest drawdown of `equity`.
+fn max_drawdown_window(equity: &[f64]) -> Option<(usize, usize)> {
+    let mut peak = 0;
+    let mut worst = 0.0;
+    let mut window = None;
+    for (i, e) in equity.iter().enumerate() {
+        if *e > equity[peak] {
+            peak = i;
+        }
+        let dd = 1.0 - e / equity[peak];
+        if dd > worst {
+            worst = dd;
+            window = Some((peak, i));
+        }
+    }
+    window
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn group_stats<'a>(
+    group: String,
+    items: impl Iterator<Item = (usize, &'a Contribution)>,
+    drawdown: Option<(usize, usize)>,
+    total: f64,
+) -> GroupStats {
+    let mut g = GroupStats {
+        group,
+        ..GroupStats::default()
+    };
+    let (mut weight, mut ret, mut hits) = (0.0, 0.0, 0usize);
+    for (day, c) in items {
+        g.days += 1;
+        g.contribution += c.value();
+        weight += c.weight.abs();
+        ret += c.asset_return * c.weight.signum();
+        hits += usize::from(c.value() > 0.0);
+        if drawdown.is_some_and(|(p, t)| day > p && day <= t) {
+            g.drawdown_contribution += c.value();
+        }
+    }
+    if g.days > 0 {
+        let n = g.days as f64;
+        g.avg_weight = weight / n;
+        g.avg_asset_return = ret / n;
+        g.hit_rate = hits as f64 / n;
+    }
+    g.share = if total.abs() > 0.0 {
+        g.contribution / total
+    } else {
+        0.0
+    };
+    g
+}
+
+/// Summarize daily `contributions` (indexed like `dates`) by asset, signal state and month.
+#[must_use]
+pub fn summarize(
+    dates: &[NaiveDate],
+    equity: &[f64],
+    contributions: &[Vec<Contribution>],
+) -> Attribution {
+    let all: Vec<(usize, &Contribution)> = contributions
+        .iter()
+        .enumerate()
+        .flat_map(|(i, day)| day.iter().map(move |c| (i, c)))
+        .collect();
+    let total: f64 = all.iter().map(|(_, c)| c.value()).sum();
+    let window = max_drawdown_window(equity);
+
+    let assets: BTreeSet<&str> = all.iter().map(|(_, c)| c.asset.as_str()).collect();
+    let mut by_asset: Vec<GroupStats> = assets
+        .into_iter()
+        .map(|a| {
+            let items = all.iter().filter(|(_, c)| c.asset == a).copied();
+            group_stats(a.to_string(), items, window, total)
+        })
+        .collect();
+    by_asset.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+
+    let states: BTreeSet<SignalState> = all.iter().map(|(_, c)| c.state).collect();
+    let by_state = states
+        .into_iter()
+        .map(|s| {
+            let items = all.iter().filter(|(_, c)| c.state == s).copied();
+            group_stats(s.name().to_string(), items, window, total)
+        })
+        .collect();
+
+    let mut months: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+    for (i, c) in &all {
+        let key = format!("{}-{:02}", dates[*i].year(), dates[*i].month());
+        *months
+            .entry(key)
+            .or_default()
+            .entry(c.asset.clone())
+            .or_insert(0.0) += c.value();
+    }
+
+    Attribution {
+        by_asset,
+        by_state,
+        by_month: months.into_iter().collect(),
+        drawdown: window.map(|(p, t)| (dates[p], dates[t])),
+    }
+}
+
+/// Every asset that ever held a position, hedge last.
+fn columns(contributions: &[Vec<Contribution>]) -> Vec<String> {
+    let names: BTreeSet<&str> = contributions
+        .iter()
+        .flatten()
+        .map(|c| c.asset.as_str())
+        .filter(|a| *a != HEDGE)
+        .collect();
+    let mut cols: Vec<String> = names.into_iter().map(str::to_string).collect();
+    cols.push(HEDGE.to_string());
+    cols
+}
+
+/// Write the per-day, per-asset contribution matrix (hedge and total as the last columns).
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_daily(
+    path: &Path,
+    dates: &[NaiveDate],
+    contributions: &[Vec<Contribution>],
+) -> Result<()> {
+    let cols = columns(contributions);
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    let mut header = vec!["date".to_string()];
+    header.extend(cols.iter().cloned());
+    header.push("total".to_string());
+    wtr.write_record(&header)?;
+    for (d, day) in dates.iter().zip(contributions) {
+        let mut rec = vec![d.to_string()];
+        for col in &cols {
+            let v: f64 = day.iter().filter(|c| c.asset == *col).map(Contribution::value).sum();
+            rec.push(format!("{v:.8}"));
+        }
+        rec.push(format!(
+            "{:.8}",
+            day.iter().map(Contribution::value).sum::<f64>()
+        ));
+        wtr.write_record(&rec)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write the by-asset and by-signal-state summary.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_summary(path: &Path, attribution: &Attribution) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    wtr.write_record([
+        "dimension",
+        "group",
+        "days",
+        "contribution",
+        "share",
+        "avg_weight",
+        "avg_asset_return",
+        "hit_rate",
+        "drawdown_contribution",
+    ])?;
+    let rows = attribution
+        .by_asset
+        .iter()
+        .map(|g| ("asset", g))
+        .chain(attribution.by_state.iter().map(|g| ("signal_state", g)));
+    for (dimension, g) in rows {
+        wtr.write_record(&[
+            dimension.to_string(),
+            g.group.clone(),
+            g.days.to_string(),
+            format!("{:.6}", g.contribution),
+            format!("{:.4}", g.share),
+            format!("{:.4}", g.avg_weight),
+            format!("{:.6}", g.avg_asset_return),
+            format!("{:.4}", g.hit_rate),
+            format!("{:.6}", g.drawdown_contribution),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write monthly contributions, one column per asset plus the hedge and the total.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_monthly(
+    path: &Path,
+    attribution: &Attribution,
+    contributions: &[Vec<Contribution>],
+) -> Result<()> {
+    let cols = columns(contributions);
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    let mut header = vec!["month".to_string()];
+    header.extend(cols.iter().cloned());
+    header.push("total".to_string());
+    wtr.write_record(&header)?;
+    for (month, by_asset) in &attribution.by_month {
+        let mut rec = vec![month.clone()];
+        rec.extend(
+            cols.iter()
+                .map(|c| format!("{:.6}", by_asset.get(c).copied().unwrap_or(0.0))),
+        );
+        rec.push(format!("{:.6}", by_asset.values().sum::<f64>()));
+        wtr.write_record(&rec)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
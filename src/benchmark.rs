@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{self, Metrics};
+
+/// Name of the BTC buy-and-hold benchmark
+pub const BTC: &str = "btc_buy_and_hold";
+/// Name of the equal-weight, daily-rebalanced universe benchmark
+pub const EQUAL_WEIGHT: &str = "equal_weight_universe";
+
+/// Per-bar returns of one benchmark on the strategy calendar (first bar 0).
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    pub name: String,
+    pub daily_ret: Vec<f64>,
+}
+
+/// Strategy statistics relative to one benchmark. Ratios are annualized; returns are
+/// arithmetic per bar, so alpha is Jensen's alpha with a zero risk-free rate.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct RelativeStats {
+    pub beta: f64,
+    pub alpha: f64,
+    pub correlation: f64,
+    pub tracking_error: f64,
+    /// Annualized active return over tracking error
+    pub information_ratio: f64,
+    /// Mean strategy return on benchmark-up bars over the benchmark's mean on those bars
+    pub up_capture: f64,
+    /// Same on benchmark-down bars; below 1.0 means the strategy loses less
+    pub down_capture: f64,
+}
+
+/// Close-to-close returns of a fully invested position. Bars where either close is missing
+/// earn nothing.
+#[must_use]
+pub fn buy_and_hold(closes: &[Option<f64>]) -> Vec<f64> {
+    let mut rets = vec![0.0; closes.len()];
+    for i in 1..closes.len() {
+        if let (Some(prev), Some(cur)) = (closes[i - 1], closes[i])
+            && prev > 0.0
+        {
+            rets[i] = cur / prev - 1.0;
+        }
+    }
+    rets
+}
+
+/// Equal weight across every asset with a return on the bar, rebalanced each bar. Series may
+/// differ in length; a shorter one has no return past its end.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn equal_weight(closes: &[Vec<Option<f64>>]) -> Vec<f64> {
+    let n = closes.iter().map(Vec::len).max().unwrap_or(0);
+    let per_asset: Vec<Vec<f64>> = closes.iter().map(|c| buy_and_hold(c)).collect();
+    (0..n)
+        .map(|i| {
+            let live: Vec<f64> = closes
+                .iter()
+                .zip(&per_asset)
+                .filter(|(c, _)| {
+                    i > 0 && matches!((c.get(i - 1), c.get(i)), (Some(Some(_)), Some(Some(_))))
+                })
+                .map(|(_, r)| r[i])
+                .collect();
+            if live.is_empty() {
+                0.0
+            } else {
+                live.iter().sum::<f64>() / live.len() as f64
+            }
+        })
+        .collect()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mean(x: &[f64]) -> f64 {
+    if x.is_empty() {
+        0.0
+    } else {
+        x.iter().sum::<f64>() / x.len() as f64
+    }
+}
+
+/// Sample covariance of two equally long series.
+#[allow(clippy::cast_precision_loss)]
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() < 2 {
+        return 0.0;
+    }
+    let (ma, mb) = (mean(a), mean(b));
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - ma) * (y - mb))
+        .sum::<f64>()
+        / (a.len() - 1) as f64
+}
+
+fn capture(rets: &[f64], bench: &[f64], up: bool) -> f64 {
+    let (r, b): (Vec<f64>, Vec<f64>) = rets
+        .iter()
+        .zip(bench)
+        .filter(|(_, b)| if up { **b > 0.0 } else { **b < 0.0 })
+        .map(|(r, b)| (*r, *b))
+        .unzip();
+    let mb = mean(&b);
+    if mb == 0.0 { 0.0 } else { mean(&r) / mb }
+}
+
+/// Relative statistics of `rets` against `bench` (same calendar, same length).
+#[must_use]
+pub fn relative(rets: &[f64], bench: &[f64], periods_per_year: f64) -> RelativeStats {
+    let n = rets.len().min(bench.len());
+    let (rets, bench) = (&rets[..n], &bench[..n]);
+    let var_b = covariance(bench, bench);
+    let var_r = covariance(rets, rets);
+    let cov = covariance(rets, bench);
+    let beta = if var_b > 0.0 { cov / var_b } else { 0.0 };
+    let active: Vec<f64> = rets.iter().zip(bench).map(|(r, b)| r - b).collect();
+    let tracking_error = covariance(&active, &active).sqrt() * periods_per_year.sqrt();
+    RelativeStats {
+        beta,
+        alpha: (mean(rets) - beta * mean(bench)) * periods_per_year,
+        correlation: if var_b > 0.0 && var_r > 0.0 {
+            cov / (var_b * var_r).sqrt()
+        } else {
+            0.0
+        },
+        tracking_error,
+        // A constant active return leaves only rounding noise in the tracking error
+        information_ratio: if tracking_error > 1e-12 {
+            mean(&active) * periods_per_year / tracking_error
+        } else {
+            0.0
+        },
+        up_capture: capture(rets, bench, true),
+        down_capture: capture(rets, bench, false),
+    }
+}
+
+/// Benchmark's own metrics and the strategy's statistics relative to it.
+#[must_use]
+pub fn compare(
+    rets: &[f64],
+    benchmark: &Benchmark,
+    periods_per_year: f64,
+) -> (Metrics, RelativeStats) {
+    (
+        metrics::compute(&benchmark.daily_ret, &[], periods_per_year, &[], &[]),
+        relative(rets, &benchmark.daily_ret, periods_per_year),
+    )
+}
+
+/// Report section for one benchmark.
+#[must_use]
+pub fn report(name: &str, bench: &Metrics, rel: &RelativeStats) -> String {
+    format!(
+        "\n[vs {}]\nBenchmark Total Return: {:.2}%\nBenchmark CAGR: {:.2}%\nBenchmark Sharpe (ann.): {:.2}\nBenchmark Max Drawdown: {:.2}%\nBeta: {:.2}\nAlpha (ann.): {:.2}%\nCorrelation: {:.2}\nTracking Error (ann.): {:.2}%\nInformation Ratio: {:.2}\nUp / Down Capture: {:.2} / {:.2}\n",
+        name,
+        bench.total_return * 100.0,
+        bench.cagr * 100.0,
+        bench.sharpe,
+        bench.max_drawdown * 100.0,
+        rel.beta,
+        rel.alpha * 100.0,
+        rel.correlation,
+        rel.tracking_error * 100.0,
+        rel.information_ratio,
+        rel.up_capture,
+        rel.down_capture
+    )
+}
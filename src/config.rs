@@ -0,0 +1,389 @@
+use crate::error::{Context, Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ai_insights::{self, AiSettings};
+use crate::{DaemonArgs, OhlcArgs, RankBy, SignalMode, StrategyArgs, TradeArgs};
+
+/// Config file read from the working directory when neither `--config` nor `CMAI_CONFIG` is set
+pub const DEFAULT_FILE: &str = "crypto-momentum.toml";
+/// Prefix of environment overrides (`CMAI_<SECTION>_<KEY>`, `CMAI_CONFIG`, `CMAI_PROFILE`)
+pub const ENV_PREFIX: &str = "CMAI_";
+
+const SECTIONS: [&str; 5] = ["ohlc", "strategy", "trade", "daemon", "ai"];
+/// Values that `config show` never prints
+const SECRETS: [(&str, &str); 1] = [("ohlc", "api_key")];
+
+/// Settings of every command, one section per subsystem.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub ohlc: OhlcArgs,
+    pub strategy: StrategyArgs,
+    pub trade: TradeArgs,
+    pub daemon: DaemonArgs,
+    pub ai: AiSettings,
+}
+
+impl Config {
+    /// Built-in defaults, the lowest layer.
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self {
+            ohlc: OhlcArgs {
+                out: Some(PathBuf::from("./out")),
+                top_n: Some(100),
+                vs: Some("usd".to_string()),
+                concurrency: Some(6),
+                request_delay_ms: Some(250),
+                write_manifest: Some(true),
+                resume: Some(false),
+                skip_btc: Some(false),
+                volume: Some(true),
+                ..OhlcArgs::default()
+            },
+            strategy: StrategyArgs {
+                btc: Some(PathBuf::from("./out/BTC.csv")),
+                out: Some(PathBuf::from("./out/signals")),
+                ma_short: Some(3),
+                ma_long: Some(7),
+                min_signals: Some(2),
+                short_alts: Some(false),
+                btc_hedge: Some(0.3),
+                stop_lookback: Some(14),
+                atr_mult: Some(3.0),
+                vol_mult: Some(2.5),
+                max_adv_fraction: Some(0.05),
+                adv_lookback: Some(30),
+                capital: Some(100_000.0),
+                regime_scaling: Some(false),
+                regime_vol_lookback: Some(30),
+                regime_high_vol: Some(0.90),
+                regime_breadth_bull: Some(0.60),
+                regime_breadth_bear: Some(0.40),
+                regime_neutral_exposure: Some(0.75),
+                regime_risk_off_exposure: Some(0.50),
+                regime_crisis_exposure: Some(0.25),
+                mode: Some(SignalMode::Threshold),
+                rank_by: Some(RankBy::Return),
+                rank_lookback: Some(30),
+                rank_skip: Some(1),
+                top_k: Some(5),
+                short_bottom: Some(false),
+                rebalance_days: Some(7),
+                max_fill_days: Some(3),
+                runs_dir: Some(PathBuf::from("./out/runs")),
+                record: Some(true),
+                ..StrategyArgs::default()
+            },
+            trade: TradeArgs {
+                signals_dir: Some("./out/signals".to_string()),
+                output_json: None,
+                max_adv_fraction: Some(0.05),
+                portfolio_value: Some(100_000.0),
+                account: None,
+                currency: Some("USD".to_string()),
+                instruments: None,
+                stop_atr_mult: Some(3.0),
+                profit_target_r: Some(2.0),
+                extended_threshold: Some(0.10),
+            },
+            daemon: DaemonArgs {
+                continuous: Some(false),
+                portfolio_value: Some(100_000.0),
+                account: None,
+                instruments: None,
+                position_book: Some(PathBuf::from("./out/positions.json")),
+                risk_cap_percent: Some(1.0),
+                check_interval: Some(60),
+                max_adv_fraction: Some(0.05),
+                max_portfolio_heat: Some(6.0),
+                correlation_lookback: Some(60),
+                fetch_days: Some(30),
+            },
+            ai: AiSettings {
+                enabled: Some(true),
+                model: Some(ai_insights::DEFAULT_MODEL.to_string()),
+                api_url: Some(ai_insights::DEFAULT_API_URL.to_string()),
+                timeout_secs: Some(ai_insights::DEFAULT_TIMEOUT_SECS),
+            },
+        }
+    }
+}
+
+/// Layer a resolved value came from, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    Default,
+    File,
+    Profile,
+    Env,
+    Cli,
+}
+
+impl Source {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::File => "file",
+            Self::Profile => "profile",
+            Self::Env => "env",
+            Self::Cli => "cli",
+        }
+    }
+}
+
+/// Configuration merged as defaults < file < profile < env; CLI flags go on top per command.
+#[derive(Debug, Clone)]
+pub struct Resolved {
+    pub config: Config,
+    pub file: Option<PathBuf>,
+    pub profile: Option<String>,
+    /// Merged values as `{section: {key: value}}`
+    values: Value,
+    sources: BTreeMap<(String, String), Source>,
+}
+
+fn env_lookup(env: &[(String, String)], name: &str) -> Option<String> {
+    env.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+}
+
+/// Parse an environment value: strings stay verbatim, anything else is read as JSON
+/// (numbers, booleans, `["a.csv","b.csv"]`) and falls back to a string.
+fn env_value(raw: &str, current: &Value) -> Value {
+    if current.is_string() {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Overlay the non-null `{section: {key: value}}` entries of `layer` onto `values`.
+fn overlay(
+    values: &mut Value,
+    layer: &Value,
+    source: Source,
+    sources: &mut BTreeMap<(String, String), Source>,
+    origin: &str,
+) -> Result<()> {
+    let Some(layer) = layer.as_object() else {
+        return Err(Error::config(format!("{origin}: expected a table of sections")));
+    };
+    for (section, table) in layer {
+        let Some(base) = values.get_mut(section).and_then(Value::as_object_mut) else {
+            return Err(Error::config(format!(
+                "{origin}: unknown section [{section}] (expected one of {})",
+                SECTIONS.join(", ")
+            )));
+        };
+        let Some(table) = table.as_object() else {
+            return Err(Error::config(format!("{origin}: [{section}] must be a table")));
+        };
+        for (key, value) in table {
+            if !base.contains_key(key) {
+                return Err(Error::config(format!("{origin}: unknown key '{section}.{key}'")));
+            }
+            if value.is_null() {
+                continue;
+            }
+            base.insert(key.clone(), value.clone());
+            sources.insert((section.clone(), key.clone()), source);
+        }
+    }
+    Ok(())
+}
+
+impl Resolved {
+    /// Resolve the config file (`file`, else `CMAI_CONFIG`, else `./crypto-momentum.toml` if
+    /// present), the profile (`profile`, else `CMAI_PROFILE`) and `CMAI_*` environment overrides.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, the profile does not exist, or a
+    /// key or value is invalid.
+    pub fn load(file: Option<&Path>, profile: Option<&str>) -> Result<Self> {
+        let env: Vec<(String, String)> = std::env::vars().collect();
+        let file = file
+            .map(Path::to_path_buf)
+            .or_else(|| env_lookup(&env, "CMAI_CONFIG").map(PathBuf::from))
+            .or_else(|| {
+                Path::new(DEFAULT_FILE)
+                    .is_file()
+                    .then(|| PathBuf::from(DEFAULT_FILE))
+            });
+        let profile = profile
+            .map(str::to_string)
+            .or_else(|| env_lookup(&env, "CMAI_PROFILE"));
+        let text = file
+            .as_ref()
+            .map(|f| fs::read_to_string(f).with_context(|| format!("read {}", f.display())))
+            .transpose()?;
+        let mut resolved = Self::from_sources(text.as_deref(), profile.as_deref(), &env)
+            .with_context(|| match &file {
+                Some(f) => format!("load configuration from {}", f.display()),
+                None => "load configuration".to_string(),
+            })?;
+        resolved.file = file;
+        Ok(resolved)
+    }
+
+    /// Merge TOML `text`, its `[profiles.<profile>]` table and `env` over the built-in defaults.
+    ///
+    /// # Errors
+    /// Returns an error on invalid TOML, an unknown profile, section or key, or a value of the
+    /// wrong type.
+    pub fn from_sources(
+        text: Option<&str>,
+        profile: Option<&str>,
+        env: &[(String, String)],
+    ) -> Result<Self> {
+        let mut values = serde_json::to_value(Config::builtin())?;
+        let mut sources = BTreeMap::new();
+        for section in SECTIONS {
+            for key in values[section].as_object().into_iter().flat_map(Map::keys) {
+                sources.insert((section.to_string(), key.clone()), Source::Default);
+            }
+        }
+
+        let mut profiles = None;
+        if let Some(text) = text {
+            let table: toml::Table = toml::from_str(text).context("parse TOML")?;
+            let mut doc = serde_json::to_value(table)?;
+            profiles = doc.as_object_mut().and_then(|d| d.remove("profiles"));
+            overlay(&mut values, &doc, Source::File, &mut sources, "config file")?;
+        }
+        if let Some(name) = profile {
+            let available: Vec<&String> = profiles
+                .as_ref()
+                .and_then(Value::as_object)
+                .map(|p| p.keys().collect())
+                .unwrap_or_default();
+            let layer = profiles
+                .as_ref()
+                .and_then(|p| p.get(name))
+                .ok_or_else(|| {
+                    Error::config(format!("unknown profile '{name}' (available: {available:?})"))
+                })?;
+            overlay(
+                &mut values,
+                layer,
+                Source::Profile,
+                &mut sources,
+                &format!("profile '{name}'"),
+            )?;
+        }
+
+        let mut env_layer = Map::new();
+        for (name, raw) in env {
+            let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if rest == "CONFIG" || rest == "PROFILE" {
+                continue;
+            }
+            let Some(section) = SECTIONS
+                .iter()
+                .find(|s| rest.starts_with(&format!("{}_", s.to_uppercase())))
+            else {
+                return Err(Error::config(format!("environment: {name} does not name a config section")));
+            };
+            let key = rest[section.len() + 1..].to_lowercase();
+            let current = values[*section].get(&key).unwrap_or(&Value::Null);
+            let value = env_value(raw, current);
+            if let Value::Object(table) = env_layer
+                .entry(section.to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                table.insert(key, value);
+            }
+        }
+        overlay(
+            &mut values,
+            &Value::Object(env_layer),
+            Source::Env,
+            &mut sources,
+            "environment",
+        )?;
+
+        let config = serde_json::from_value(values.clone())
+            .map_err(|e| Error::config(format!("invalid configuration value: {e}")))?;
+        Ok(Self {
+            config,
+            file: None,
+            profile: profile.map(str::to_string),
+            values,
+            sources,
+        })
+    }
+
+    /// Section `section` with the flags given on the command line (`cli`'s `Some` fields) on top.
+    ///
+    /// # Errors
+    /// Returns an error if `section` is unknown or the merged values do not deserialize.
+    pub fn with_cli<T: Serialize + DeserializeOwned>(&self, section: &str, cli: &T) -> Result<T> {
+        let mut merged = self
+            .values
+            .get(section)
+            .cloned()
+            .ok_or_else(|| Error::config(format!("unknown config section '{section}'")))?;
+        if let (Some(base), Value::Object(flags)) =
+            (merged.as_object_mut(), serde_json::to_value(cli)?)
+        {
+            for (key, value) in flags {
+                if !value.is_null() {
+                    base.insert(key, value);
+                }
+            }
+        }
+        serde_json::from_value(merged)
+            .map_err(|e| Error::config(format!("invalid [{section}] value: {e}")))
+    }
+
+    /// Layer that set `section.key`.
+    #[must_use]
+    pub fn source(&self, section: &str, key: &str) -> Option<Source> {
+        self.sources
+            .get(&(section.to_string(), key.to_string()))
+            .copied()
+    }
+
+    /// TOML-formatted effective values, each annotated with its layer; secrets are masked.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for section in SECTIONS {
+            out.push_str(&format!("[{section}]\n"));
+            for (key, value) in self.values[section].as_object().into_iter().flatten() {
+                let source = self.source(section, key).map_or("", Source::name);
+                let line = if value.is_null() {
+                    format!("# {key} (unset)")
+                } else if SECRETS.contains(&(section, key.as_str())) {
+                    format!("{key} = \"***\"")
+                } else {
+                    format!("{key} = {value}")
+                };
+                out.push_str(&format!("{line:<52} # {source}\n"));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Print the effective configuration (`config show`).
+    pub fn show(&self) {
+        println!("⚙️  Effective configuration");
+        println!(
+            "   File:    {}",
+            self.file
+                .as_ref()
+                .map_or_else(|| "(none)".to_string(), |f| f.display().to_string())
+        );
+        println!("   Profile: {}", self.profile.as_deref().unwrap_or("(none)"));
+        println!("   Order:   default < file < profile < env ({ENV_PREFIX}<SECTION>_<KEY>) < cli");
+        println!();
+        print!("{}", self.render());
+    }
+}
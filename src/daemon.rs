@@ -1,31 +1,84 @@
-use anyhow::Result;
+use crate::error::{Context, Recovery, Required, Result};
 use chrono::{Duration, Utc};
 use std::fs;
+use std::path::Path;
 use std::time::Duration as StdDuration;
 use tokio::time::sleep;
 
-use crate::{OhlcArgs, StrategyArgs, analyzer, ohlc, strategy, trade};
+use crate::account::Account;
+use crate::ai_insights::AiSettings;
+use crate::config::Config;
+use crate::positions::{BookRules, Entry, PositionBook};
+use crate::{
+    OhlcArgs, StrategyArgs, TradeArgs, analyzer, incremental, ohlc, positions, risk, strategy,
+    trade,
+};
+
+/// Shock size (in daily standard deviations) for the correlated stress scenario
+const STRESS_SIGMA: f64 = 3.0;
+
+/// Immediate retries of a cycle that failed with a transient (network or rate-limit) error
+const MAX_RETRIES: u32 = 3;
+
+/// Wait before retry `n` when the server gave no `Retry-After`: `n` times this
+const RETRY_BACKOFF: StdDuration = StdDuration::from_secs(30);
 
 /// Daemon mode for continuous signal generation and portfolio management
-/// Execute the daemon with the given parameters.
+/// Execute the daemon with the resolved configuration (`config.daemon` holds the schedule and
+/// sizing; the `ohlc`, `strategy`, `trade` and `ai` sections drive each step).
 ///
 /// # Errors
-/// Returns an error if any step of the daemon process fails.
+/// Returns the failing step's error in single-run mode; in continuous mode only errors whose
+/// [`Recovery`] is `Abort`, or transient errors that outlast the retries, are returned.
 #[allow(clippy::too_many_lines, clippy::cast_precision_loss, clippy::cast_possible_wrap, clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::ignored_unit_patterns, clippy::case_sensitive_file_extension_comparisons)]
-pub async fn execute(
-    continuous: bool,
-    portfolio_value: f64,
-    risk_cap_percent: f64,
-    check_interval: u64,
-) -> Result<()> {
+pub async fn execute(config: &Config) -> Result<()> {
+    let daemon = &config.daemon;
+    let continuous = daemon.continuous.required("daemon.continuous")?;
+    let portfolio_value = daemon.portfolio_value.required("daemon.portfolio_value")?;
+    let risk_cap_percent = daemon.risk_cap_percent.required("daemon.risk_cap_percent")?;
+    let check_interval = daemon.check_interval.required("daemon.check_interval")?;
+    let max_adv_fraction = daemon.max_adv_fraction.required("daemon.max_adv_fraction")?;
+    let max_portfolio_heat = daemon.max_portfolio_heat.required("daemon.max_portfolio_heat")?;
+    let signals_dir = config.strategy.out.as_ref().required("strategy.out")?.display().to_string();
+
+    // Strategy and playbooks size against the daemon's portfolio
+    let strategy_args = StrategyArgs {
+        capital: Some(portfolio_value),
+        max_adv_fraction: Some(max_adv_fraction),
+        // Scheduled cycles are not archived in the run registry
+        record: Some(false),
+        ..config.strategy.clone()
+    };
+    let trade_args = TradeArgs {
+        signals_dir: Some(signals_dir.clone()),
+        output_json: Some("./out/current_playbooks.json".to_string()),
+        portfolio_value: Some(portfolio_value),
+        account: daemon.account.clone().or_else(|| config.trade.account.clone()),
+        instruments: daemon.instruments.clone().or_else(|| config.trade.instruments.clone()),
+        max_adv_fraction: Some(max_adv_fraction),
+        ..config.trade.clone()
+    };
+
     println!("🚀 Starting Crypto Strategy Daemon");
-    println!("Portfolio Value: ${portfolio_value:.0}");
+    match &trade_args.account {
+        Some(path) => println!("Account File: {}", path.display()),
+        None => println!("Portfolio Value: ${portfolio_value:.0} (all cash)"),
+    }
+    if let Some(path) = &trade_args.instruments {
+        println!("Instruments File: {}", path.display());
+    }
+    if let Some(path) = &daemon.position_book {
+        println!("Position Book: {}", path.display());
+    }
     println!("Risk Cap per Position: {risk_cap_percent:.1}%");
+    println!("Max ADV Participation: {:.1}%", max_adv_fraction * 100.0);
+    println!("Max Portfolio Heat: {max_portfolio_heat:.1}%");
     println!("Check Interval: {check_interval} minutes");
     println!("Continuous Mode: {continuous}");
     println!();
 
     let mut iteration = 0;
+    let mut retries = 0;
 
     loop {
         iteration += 1;
@@ -37,89 +90,57 @@ pub async fn execute(
             start_time.format("%Y-%m-%d %H:%M:%S UTC")
         );
 
-        // Step 1: Fetch latest OHLC data
-        println!("1. Fetching latest OHLC data...");
-        let ohlc_result = fetch_latest_data().await;
-        match ohlc_result {
-            Ok(_) => println!("   ✅ OHLC data updated successfully"),
-            Err(e) => {
-                println!("   ❌ OHLC data fetch failed: {e}");
-                if !continuous {
-                    return Err(e);
-                }
-                println!(
-                    "   ⏭️  Skipping this cycle, will retry in {check_interval} minutes"
-                );
-                sleep(StdDuration::from_secs(check_interval * 60)).await;
-                continue;
-            }
-        }
-
-        // Step 2: Generate strategy signals
-        println!("2. Generating strategy signals...");
-        let strategy_result = generate_signals();
-        match strategy_result {
-            Ok(_) => println!("   ✅ Strategy signals generated successfully"),
-            Err(e) => {
-                println!("   ❌ Strategy signal generation failed: {e}");
-                if !continuous {
-                    return Err(e);
-                }
+        let cycle = run_cycle(
+            config,
+            &strategy_args,
+            &trade_args,
+            &signals_dir,
+            risk_cap_percent,
+        )
+        .await;
+        match cycle {
+            Ok(()) => {
+                retries = 0;
+                let duration = Utc::now() - start_time;
                 println!(
-                    "   ⏭️  Skipping this cycle, will retry in {check_interval} minutes"
+                    "   ✅ Cycle completed in {:.1} seconds",
+                    duration.num_seconds() as f64
                 );
-                sleep(StdDuration::from_secs(check_interval * 60)).await;
-                continue;
             }
-        }
-
-        // Step 3: Analyze profitable strategies
-        println!("3. Analyzing profitable strategies...");
-        let analysis_result = analyze_strategies();
-        match analysis_result {
-            Ok(_) => println!("   ✅ Strategy analysis completed successfully"),
             Err(e) => {
-                println!("   ❌ Strategy analysis failed: {e}");
+                println!("   ❌ {e}");
                 if !continuous {
                     return Err(e);
                 }
-                println!(
-                    "   ⏭️  Skipping this cycle, will retry in {check_interval} minutes"
-                );
-                sleep(StdDuration::from_secs(check_interval * 60)).await;
-                continue;
-            }
-        }
-
-        // Step 4: Generate trading playbooks with real execution values
-        println!("4. Generating trading playbooks...");
-        let trade_result = generate_playbooks(portfolio_value, risk_cap_percent).await;
-        match trade_result {
-            Ok(_) => println!("   ✅ Trading playbooks generated successfully"),
-            Err(e) => {
-                println!("   ❌ Trading playbook generation failed: {e}");
-                if !continuous {
-                    return Err(e);
+                // Transient failures retry soon; bad inputs wait for the next cycle;
+                // configuration and filesystem problems stop the daemon
+                match e.recovery() {
+                    Recovery::Abort => {
+                        println!("   🛑 Stopping: {} errors need operator attention", e.class());
+                        return Err(e);
+                    }
+                    Recovery::Retry(wait) if retries < MAX_RETRIES => {
+                        retries += 1;
+                        let wait = wait.unwrap_or(RETRY_BACKOFF * retries);
+                        println!(
+                            "   🔁 Retrying in {}s (attempt {retries}/{MAX_RETRIES})",
+                            wait.as_secs()
+                        );
+                        sleep(wait).await;
+                        continue;
+                    }
+                    Recovery::Retry(_) | Recovery::Skip => {
+                        retries = 0;
+                        println!(
+                            "   ⏭️  Skipping this cycle, will retry in {check_interval} minutes"
+                        );
+                        sleep(StdDuration::from_secs(check_interval * 60)).await;
+                        continue;
+                    }
                 }
-                println!(
-                    "   ⏭️  Skipping this cycle, will retry in {check_interval} minutes"
-                );
-                sleep(StdDuration::from_secs(check_interval * 60)).await;
-                continue;
             }
         }
 
-        // Step 5: Generate portfolio summary
-        println!("5. Generating portfolio summary...");
-        generate_portfolio_summary(portfolio_value, risk_cap_percent)?;
-
-        let end_time = Utc::now();
-        let duration = end_time - start_time;
-        println!(
-            "   ✅ Cycle completed in {:.1} seconds",
-            duration.num_seconds() as f64
-        );
-
         if !continuous {
             println!("🎯 Single run completed successfully!");
             break;
@@ -139,22 +160,82 @@ pub async fn execute(
     Ok(())
 }
 
-async fn fetch_latest_data() -> Result<()> {
-    // Use default OHLC args but with resume=true to only fetch missing data
+/// One daemon cycle: fetch data, generate signals, analyze, build playbooks and summarize.
+/// A failing step's error is prefixed with the step name.
+async fn run_cycle(
+    config: &Config,
+    strategy_args: &StrategyArgs,
+    trade_args: &TradeArgs,
+    signals_dir: &str,
+    risk_cap_percent: f64,
+) -> Result<()> {
+    let daemon = &config.daemon;
+    let book_path = daemon.position_book.as_ref().required("daemon.position_book")?;
+    let mut book = PositionBook::load(book_path).context("Position book load failed")?;
+    // Re-read every cycle so deposits between cycles are picked up; the book's own fills then
+    // move its cash and holdings
+    let mut account = book_account(trade_args, &book).context("Account load failed")?;
+
+    // Step 1: Fetch latest OHLC data
+    println!("1. Fetching latest OHLC data...");
+    fetch_latest_data(&config.ohlc, daemon.fetch_days.required("daemon.fetch_days")?)
+        .await
+        .context("OHLC data fetch failed")?;
+    println!("   ✅ OHLC data updated successfully");
+
+    // Step 2: Generate strategy signals
+    println!("2. Generating strategy signals...");
+    generate_signals(strategy_args.clone()).context("Strategy signal generation failed")?;
+    println!("   ✅ Strategy signals generated successfully");
+
+    // Step 3: Analyze profitable strategies
+    println!("3. Analyzing profitable strategies...");
+    analyze_strategies(signals_dir).context("Strategy analysis failed")?;
+    println!("   ✅ Strategy analysis completed successfully");
+
+    // Step 4: Generate trading playbooks with real execution values
+    println!("4. Generating trading playbooks...");
+    let entries = generate_playbooks(
+        trade_args,
+        &mut account,
+        &book,
+        &config.ai,
+        risk_cap_percent,
+        daemon.max_portfolio_heat.required("daemon.max_portfolio_heat")?,
+        daemon.correlation_lookback.required("daemon.correlation_lookback")?,
+    )
+    .await
+    .context("Trading playbook generation failed")?;
+    println!("   ✅ Trading playbooks generated successfully");
+
+    // Step 5: Ratchet stops and open new entries in the position book
+    println!("5. Updating position book...");
+    update_position_book(&mut book, book_path, signals_dir, trade_args, &entries)
+        .context("Position book update failed")?;
+    let account = book_account(trade_args, &book).context("Account load failed")?;
+
+    // Step 6: Generate portfolio summary
+    println!("6. Generating portfolio summary...");
+    generate_portfolio_summary(&account, &book, risk_cap_percent)
+}
+
+/// The configured account with the position book's fills applied.
+fn book_account(trade_args: &TradeArgs, book: &PositionBook) -> Result<Account> {
+    let mut account = Account::from_args(trade_args)?;
+    book.apply_to(&mut account);
+    Ok(account)
+}
+
+async fn fetch_latest_data(ohlc_args: &OhlcArgs, fetch_days: i64) -> Result<()> {
+    // Resume so only missing data is fetched
     let mut ohlc_args = OhlcArgs {
-        out: Some(std::path::PathBuf::from("./out")),
-        top_n: Some(100),
-        vs: Some("usd".to_string()),
-        concurrency: Some(6),
-        request_delay_ms: Some(250),
-        write_manifest: Some(true),
-        resume: Some(true), // Only fetch missing data
-        ..Default::default()
+        resume: Some(true),
+        ..ohlc_args.clone()
     };
 
-    // Set date range to last 30 days
+    // Set date range to the last `fetch_days` days
     let end_date = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
-    let start_date = end_date - chrono::Duration::days(30);
+    let start_date = end_date - chrono::Duration::days(fetch_days);
 
     ohlc_args.start = Some(start_date.format("%Y-%m-%d").to_string());
     ohlc_args.end = Some(end_date.format("%Y-%m-%d").to_string());
@@ -162,59 +243,86 @@ async fn fetch_latest_data() -> Result<()> {
     ohlc::execute(&ohlc_args).await
 }
 
-fn generate_signals() -> Result<()> {
-    // Use default strategy args
-    let mut strategy_args = StrategyArgs {
-        out: Some(std::path::PathBuf::from("./out/signals")),
-        ma_short: Some(3),
-        ma_long: Some(7),
-        stop_lookback: Some(14),
-        min_signals: Some(2),
-        atr_mult: Some(3.0),
-        vol_mult: Some(2.5),
-        btc_hedge: Some(0.0),
-        btc: Some(std::path::PathBuf::from("./out/BTC.csv")),
-        ..Default::default()
-    };
-
-    // Get all CSV files in the out directory (excluding BTC)
-    let out_dir = std::path::Path::new("./out");
-    let mut asset_paths = Vec::new();
+fn generate_signals(mut strategy_args: StrategyArgs) -> Result<()> {
+    // Every asset CSV next to the BTC baseline
+    if strategy_args.assets.as_ref().is_none_or(Vec::is_empty) {
+        let data_dir = strategy_args
+            .btc
+            .as_ref()
+            .and_then(|b| b.parent())
+            .map_or_else(|| Path::new("./out").to_path_buf(), Path::to_path_buf);
+        strategy_args.assets = Some(strategy::asset_files(&data_dir)?);
+    }
 
-    if let Ok(entries) = std::fs::read_dir(out_dir) {
-        for entry in entries.flatten() {
-            if let Some(file_name) = entry.file_name().to_str()
-                && file_name.ends_with(".csv") && !file_name.starts_with("BTC_")
-            {
-                asset_paths.push(entry.path());
+    // Only bars appended since the last cycle are processed unless history changed
+    let update = incremental::update(&strategy_args)?;
+    match &update.recomputed {
+        Some(reason) => {
+            for (name, days) in &update.skipped {
+                println!("   Skipping {name} (only {days} days of history)");
             }
+            println!(
+                "   🔁 Full signal recompute ({reason}): {} dates, {} assets",
+                update.dates.len(),
+                update.assets
+            );
         }
+        None if update.dates.is_empty() => println!("   💤 No new bars since the last cycle"),
+        None => println!(
+            "   ➕ Appended {} new date(s) for {} assets",
+            update.dates.len(),
+            update.assets
+        ),
     }
-
-    strategy_args.assets = Some(asset_paths);
-
-    strategy::execute(&strategy_args)
+    Ok(())
 }
 
-fn analyze_strategies() -> Result<()> {
-    analyzer::execute("./out/signals", None)
+fn analyze_strategies(signals_dir: &str) -> Result<()> {
+    analyzer::execute(signals_dir, None)
 }
 
-async fn generate_playbooks(portfolio_value: f64, risk_cap_percent: f64) -> Result<()> {
-    // Generate playbooks with current execution values
-    trade::execute("./out/signals", Some("./out/current_playbooks.json")).await?;
-
-    // Also generate a portfolio-specific playbook
-    generate_portfolio_playbook(portfolio_value, risk_cap_percent).await?;
-
-    Ok(())
+async fn generate_playbooks(
+    trade_args: &TradeArgs,
+    account: &mut Account,
+    book: &PositionBook,
+    ai: &AiSettings,
+    risk_cap_percent: f64,
+    max_portfolio_heat: f64,
+    correlation_lookback: usize,
+) -> Result<Vec<Entry>> {
+    // Generate playbooks with current execution values and the book's stops
+    trade::execute_with(trade_args, account, book, ai).await?;
+
+    // Also generate a portfolio-specific playbook, whose positions are the book's entries
+    generate_portfolio_playbook(
+        trade_args,
+        account,
+        book,
+        ai,
+        risk_cap_percent,
+        max_portfolio_heat,
+        correlation_lookback,
+    )
+    .await
 }
 
-async fn generate_portfolio_playbook(portfolio_value: f64, risk_cap_percent: f64) -> Result<()> {
+async fn generate_portfolio_playbook(
+    trade_args: &TradeArgs,
+    account: &mut Account,
+    book: &PositionBook,
+    ai: &AiSettings,
+    risk_cap_percent: f64,
+    max_portfolio_heat: f64,
+    correlation_lookback: usize,
+) -> Result<Vec<Entry>> {
     println!("   📊 Generating portfolio-specific playbook...");
+    let signals_dir = trade_args.signals_dir.as_deref().required("signals_dir")?;
+    let max_adv_fraction = trade_args.max_adv_fraction.required("max_adv_fraction")?;
 
-    // Load current playbooks
-    let playbooks = trade::generate_top_10_playbooks("./out/signals").await?;
+    // Load current playbooks, sized against the account marked to the latest closes
+    let playbooks =
+        trade::generate_top_10_playbooks(signals_dir, trade_args, account, book, ai).await?;
+    let equity = account.equity();
 
     // Filter for assets with active signals (all_signals = true)
     let active_playbooks: Vec<_> = playbooks
@@ -224,7 +332,7 @@ async fn generate_portfolio_playbook(portfolio_value: f64, risk_cap_percent: f64
 
     if active_playbooks.is_empty() {
         println!("   ⚠️  No assets with active signals found");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     println!(
@@ -232,19 +340,102 @@ async fn generate_portfolio_playbook(portfolio_value: f64, risk_cap_percent: f64
         active_playbooks.len()
     );
 
+    // Pass 1: the playbook's stand-alone size (risk, position, liquidity and cash caps against
+    // the account), further capped by the daemon's per-position risk budget
+    let mut target_shares = Vec::with_capacity(active_playbooks.len());
+    let mut liquidity = Vec::with_capacity(active_playbooks.len());
+    for playbook in &active_playbooks {
+        let cv = &playbook.computed_values;
+        let risk_budget_shares = (equity * risk_cap_percent / 100.0) / cv.risk_per_share;
+        target_shares.push(cv.recommended_shares.min(risk_budget_shares));
+        let liquidity_cap_value = cv.max_shares_by_liquidity.map(|n| n * cv.current_price);
+        liquidity.push((liquidity_cap_value, cv.is_illiquid));
+    }
+    let target_values: Vec<f64> = active_playbooks
+        .iter()
+        .zip(&target_shares)
+        .map(|(p, n)| n * p.computed_values.current_price)
+        .collect();
+
+    // Pass 2: correlation-aware aggregation and portfolio heat cap
+    let assets: Vec<String> = active_playbooks.iter().map(|p| p.asset.clone()).collect();
+    let aligned =
+        risk::load_aligned_returns(Path::new(signals_dir), &assets, correlation_lookback)?;
+    let cov = risk::covariance_matrix(&aligned.returns);
+    let corr = risk::correlation_matrix(&cov);
+
+    // Loss of each position if it is stopped out
+    let stop_risks_of = |values: &[f64]| -> Vec<f64> {
+        active_playbooks
+            .iter()
+            .zip(values)
+            .map(|(p, v)| v * p.computed_values.risk_per_share / p.computed_values.current_price)
+            .collect()
+    };
+    let pre_risk = risk::portfolio_risk(&target_values, &cov, equity, STRESS_SIGMA);
+    let pre_heat = risk::portfolio_heat(&stop_risks_of(&target_values), &corr, &pre_risk, equity);
+    let scale = risk::heat_scale(pre_heat, max_portfolio_heat);
+    if scale < 1.0 {
+        println!(
+            "   🔥 Portfolio heat {pre_heat:.1}% exceeds cap {max_portfolio_heat:.1}%, scaling all positions by {scale:.2}"
+        );
+    }
+
+    // Pass 3: every buy draws on the same cash (each plan was only capped by all of it), so
+    // scale the buy legs down together until their notional fits
+    let buy_value: f64 = active_playbooks
+        .iter()
+        .zip(&target_shares)
+        .map(|(p, n)| {
+            let cv = &p.computed_values;
+            (n * scale - cv.held_shares).max(0.0) * cv.current_price
+        })
+        .sum();
+    let cash = account.cash.max(0.0);
+    let cash_scale = if buy_value > cash { cash / buy_value } else { 1.0 };
+    if cash_scale < 1.0 {
+        println!(
+            "   💵 Buys of ${buy_value:.0} exceed cash ${cash:.0}, scaling buy orders by {cash_scale:.2}"
+        );
+    }
+
+    // Round down to the exchange lot; a scaled size below its minimum order is dropped
+    let shares: Vec<f64> = active_playbooks
+        .iter()
+        .zip(&target_shares)
+        .map(|(p, n)| {
+            let cv = &p.computed_values;
+            let n = n * scale;
+            let n = if n > cv.held_shares {
+                (n - cv.held_shares).mul_add(cash_scale, cv.held_shares)
+            } else {
+                n
+            };
+            cv.instrument.quantity(n, cv.current_price)
+        })
+        .collect();
+    let position_values: Vec<f64> = active_playbooks
+        .iter()
+        .zip(&shares)
+        .map(|(p, n)| n * p.computed_values.current_price)
+        .collect();
+    let port_risk = risk::portfolio_risk(&position_values, &cov, equity, STRESS_SIGMA);
+
     let mut total_position_value = 0.0;
     let mut total_risk = 0.0;
+    let illiquid_count = liquidity.iter().filter(|(_, illiquid)| *illiquid).count();
     let mut portfolio_playbook = Vec::new();
+    let mut entries = Vec::with_capacity(active_playbooks.len());
 
     for (i, playbook) in active_playbooks.iter().enumerate() {
         let cv = &playbook.computed_values;
-
-        // Calculate position size based on portfolio value
-        let position_value =
-            (portfolio_value * risk_cap_percent / 100.0) / (cv.risk_per_share / cv.current_price);
-        let shares = (position_value / cv.current_price).floor() as u64;
-        let actual_position_value = shares as f64 * cv.current_price;
-        let actual_risk = shares as f64 * cv.risk_per_share;
+        let shares = shares[i];
+        let (liquidity_cap_value, is_illiquid) = liquidity[i];
+        let actual_position_value = position_values[i];
+        let actual_risk = shares * cv.risk_per_share;
+        // Half of the final size at the target, in whole lots
+        let scale_out_shares = cv.instrument.quantity(shares * 0.5, cv.profit_target);
+        let remaining_shares = cv.instrument.round_quantity(shares - scale_out_shares);
 
         total_position_value += actual_position_value;
         total_risk += actual_risk;
@@ -261,51 +452,93 @@ async fn generate_portfolio_playbook(portfolio_value: f64, risk_cap_percent: f64
             "signal_strength": cv.signal_strength,
             "position": {
                 "shares": shares,
+                "held_shares": cv.held_shares,
+                "order_shares": cv.instrument.round_quantity(shares - cv.held_shares),
                 "value": actual_position_value,
-                "percent_of_portfolio": (actual_position_value / portfolio_value) * 100.0
+                "percent_of_portfolio": (actual_position_value / equity) * 100.0
             },
             "risk_management": {
                 "stop_price": cv.stop_price,
                 "risk_per_share": cv.risk_per_share,
                 "total_risk": actual_risk,
-                "risk_percent": (actual_risk / portfolio_value) * 100.0
+                "risk_percent": (actual_risk / equity) * 100.0,
+                "marginal_risk": port_risk.marginal_risk[i],
+                "risk_contribution_percent": port_risk.risk_contribution[i] * 100.0
             },
             "profit_taking": {
                 "target_price": cv.profit_target,
                 "target_percent": cv.profit_target_percent,
-                "scale_out_shares": cv.scale_out_shares,
-                "remaining_shares": cv.remaining_shares
+                "scale_out_shares": scale_out_shares,
+                "remaining_shares": remaining_shares
             },
             "execution": {
                 "is_extended": cv.is_extended,
                 "ma30_pullback_price": cv.ma30_pullback_price,
-                "extended_percent": cv.extended_percent
+                "extended_percent": cv.extended_percent,
+                "instrument": cv.instrument
+            },
+            "liquidity": {
+                "avg_daily_dollar_volume": cv.avg_daily_dollar_volume,
+                "max_position_value": liquidity_cap_value,
+                "is_illiquid": is_illiquid
             }
         });
 
         portfolio_playbook.push(entry);
+        entries.push(Entry {
+            asset: playbook.asset.clone(),
+            quantity: shares,
+            stop: cv.stop_price,
+            instrument: cv.instrument,
+        });
 
         println!(
-            "   {}. {} - ${:.2} ({} shares, ${:.0} value, {:.1}% risk)",
+            "   {}. {} - ${:.2} ({} shares, ${:.0} value, {:.1}% risk, {:.0}% of port. variance){}",
             i + 1,
             playbook.asset,
             cv.current_price,
-            shares,
+            cv.instrument.format_quantity(shares),
             actual_position_value,
-            (actual_risk / portfolio_value) * 100.0
+            (actual_risk / equity) * 100.0,
+            port_risk.risk_contribution[i] * 100.0,
+            if is_illiquid { " [ILLIQUID: capped by ADV]" } else { "" }
         );
     }
 
+    let heat = risk::portfolio_heat(&stop_risks_of(&position_values), &corr, &port_risk, equity);
+
     // Create portfolio summary
     let portfolio_summary = serde_json::json!({
         "timestamp": chrono::Utc::now().to_rfc3339(),
-        "portfolio_value": portfolio_value,
+        "currency": account.currency,
+        "equity": equity,
+        "cash": account.cash,
+        "held_positions": account.positions,
         "risk_cap_percent": risk_cap_percent,
+        "max_adv_fraction": max_adv_fraction,
         "active_positions": active_playbooks.len(),
+        "illiquid_positions": illiquid_count,
         "total_position_value": total_position_value,
         "total_risk": total_risk,
-        "portfolio_utilization": (total_position_value / portfolio_value) * 100.0,
-        "total_risk_percent": (total_risk / portfolio_value) * 100.0,
+        "portfolio_utilization": (total_position_value / equity) * 100.0,
+        "total_risk_percent": (total_risk / equity) * 100.0,
+        "portfolio_risk": {
+            "correlation_lookback_days": aligned.dates.len(),
+            "ex_ante_daily_volatility": port_risk.daily_volatility,
+            "ex_ante_daily_volatility_percent": port_risk.daily_volatility_percent * 100.0,
+            "ex_ante_annual_volatility_percent": port_risk.annual_volatility_percent * 100.0,
+            "stress_sigma": STRESS_SIGMA,
+            "perfect_correlation_stress_loss": port_risk.perfect_correlation_stress_loss,
+            "diversified_stress_loss": port_risk.diversified_stress_loss,
+            "heat_percent": heat,
+            "pre_scaling_heat_percent": pre_heat,
+            "max_heat_percent": max_portfolio_heat,
+            "heat_scale": scale,
+            "correlation": {
+                "assets": aligned.assets,
+                "matrix": corr
+            }
+        },
         "positions": portfolio_playbook
     });
 
@@ -317,23 +550,68 @@ async fn generate_portfolio_playbook(portfolio_value: f64, risk_cap_percent: f64
     println!(
         "      Total Position Value: ${:.0} ({:.1}% of portfolio)",
         total_position_value,
-        (total_position_value / portfolio_value) * 100.0
+        (total_position_value / equity) * 100.0
     );
     println!(
         "      Total Risk: ${:.0} ({:.1}% of portfolio)",
         total_risk,
-        (total_risk / portfolio_value) * 100.0
+        (total_risk / equity) * 100.0
+    );
+    println!(
+        "      Ex-ante Volatility: {:.2}% daily, {:.1}% annualized",
+        port_risk.daily_volatility_percent * 100.0,
+        port_risk.annual_volatility_percent * 100.0
     );
+    println!(
+        "      {STRESS_SIGMA:.0}σ Stress Loss at Correlation 1: ${:.0} (diversified: ${:.0})",
+        port_risk.perfect_correlation_stress_loss, port_risk.diversified_stress_loss
+    );
+    println!("      Portfolio Heat: {heat:.1}% (cap {max_portfolio_heat:.1}%)");
     println!("      Active Positions: {}", active_playbooks.len());
+    if illiquid_count > 0 {
+        println!("      Illiquid (ADV-capped) Positions: {illiquid_count}");
+    }
 
+    Ok(entries)
+}
+
+/// Apply this cycle's closes to `book` and save it to `path`: stop-outs, scale-outs at the
+/// profit target and ratcheted trailing stops, then entries for newly active positions.
+fn update_position_book(
+    book: &mut PositionBook,
+    path: &Path,
+    signals_dir: &str,
+    trade_args: &TradeArgs,
+    entries: &[Entry],
+) -> Result<()> {
+    let rules = BookRules::from_args(trade_args)?;
+    let marks = positions::latest_marks(&analyzer::analyze_signals_directory(signals_dir)?);
+    let actions = book.update(&marks, entries, &rules);
+    if actions.is_empty() {
+        println!("   💤 No position actions");
+    }
+    for action in actions {
+        println!("   👉 {}", action.describe());
+    }
+    book.save(path)?;
+    println!(
+        "   📒 {} open position(s) saved to {}",
+        book.positions.len(),
+        path.display()
+    );
     Ok(())
 }
 
-fn generate_portfolio_summary(portfolio_value: f64, risk_cap_percent: f64) -> Result<()> {
+fn generate_portfolio_summary(
+    account: &Account,
+    book: &PositionBook,
+    risk_cap_percent: f64,
+) -> Result<()> {
     // Create a simple text summary for quick reference
     let summary = format!(
         "=== PORTFOLIO SUMMARY - {} ===\n\
-        Portfolio Value: ${:.0}\n\
+        Account: {}\n\
+        Position Book: {} open, {} stopped out\n\
         Risk Cap per Position: {:.1}%\n\
         Generated: {}\n\
         \n\
@@ -341,7 +619,9 @@ fn generate_portfolio_summary(portfolio_value: f64, risk_cap_percent: f64) -> Re
         Run 'cargo run -- trade' to see detailed playbooks\n\
         Run 'cargo run -- analyze' to see strategy analysis\n",
         chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
-        portfolio_value,
+        account.describe(),
+        book.positions.len(),
+        book.closed.len(),
         risk_cap_percent,
         chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
     );
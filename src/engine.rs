@@ -0,0 +1,912 @@
+//! Event-driven backtest. Each asset's bars are replayed as events in three phases (open,
+//! intraday, close); orders rest in a book and fill against the first phase that reaches
+//! them. This simulates the playbook's execution instead of the vectorized close-to-close
+//! fills: market-on-close or next-open entries, MA30 pullback limits that expire, intrabar
+//! stops that gap, and a scale-out at a multiple of the initial risk.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use chrono::{Duration, NaiveDate};
+use csv::WriterBuilder;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::error::{Context, Required, Result};
+use crate::strategy::{self, AlignedBar, Backtest};
+use crate::{ExecutionStyle, SimulateArgs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// Order types the simulated broker accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderType {
+    /// Fill at the close of the bar it was submitted on
+    MarketOnClose,
+    /// Fill at the next bar's open
+    MarketOnOpen,
+    /// Buy at or below, or sell at or above, the price
+    Limit(f64),
+    /// Sell once the price trades at or below the level
+    Stop(f64),
+}
+
+impl OrderType {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::MarketOnClose => "market-on-close",
+            Self::MarketOnOpen => "market-on-open",
+            Self::Limit(_) => "limit",
+            Self::Stop(_) => "stop",
+        }
+    }
+
+    #[must_use]
+    pub const fn price(self) -> Option<f64> {
+        match self {
+            Self::Limit(p) | Self::Stop(p) => Some(p),
+            Self::MarketOnClose | Self::MarketOnOpen => None,
+        }
+    }
+}
+
+/// Role of an order in the playbook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Purpose {
+    Entry,
+    ScaleOut,
+    Stop,
+    Exit,
+}
+
+/// Part of a bar in which an event happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// The opening print; resting orders the open gaps through fill here
+    Open,
+    /// Between open and close, bounded by the bar's high and low
+    Intraday,
+    /// The closing print; strategy decisions and market-on-close fills
+    Close,
+}
+
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub id: usize,
+    pub asset: String,
+    pub side: Side,
+    pub quantity: f64,
+    pub kind: OrderType,
+    pub purpose: Purpose,
+    pub submitted: NaiveDate,
+    /// Orders fill only in phases after the one they were submitted in
+    pub submitted_phase: Phase,
+    /// Last date the order may fill on
+    pub expires: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub order_id: usize,
+    pub asset: String,
+    pub date: NaiveDate,
+    pub phase: Phase,
+    pub side: Side,
+    pub quantity: f64,
+    pub price: f64,
+    pub purpose: Purpose,
+    /// The bar opened through the order's price, so it filled at the open
+    pub gap: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Bar {
+        date: NaiveDate,
+        asset: String,
+        phase: Phase,
+    },
+    Submitted(Order),
+    /// A resting order's price changed (trailing-stop ratchet)
+    Amended {
+        date: NaiveDate,
+        order: Order,
+    },
+    Filled(Fill),
+    Cancelled {
+        date: NaiveDate,
+        order: Order,
+        reason: &'static str,
+    },
+}
+
+/// Hours a pullback limit rests when `--limit-hours` is not given
+pub const DEFAULT_LIMIT_HOURS: u32 = 48;
+
+/// Execution rules of one simulation.
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    pub style: ExecutionStyle,
+    /// Pullback entries use a limit at MA30 when the close is this far above it
+    pub extended_threshold: f64,
+    pub limit_hours: u32,
+    /// Scale out half at this multiple of the initial risk; 0 disables
+    pub profit_target_r: f64,
+}
+
+impl Rules {
+    /// Rules of resolved simulation arguments.
+    ///
+    /// # Errors
+    /// Returns a configuration error if a rule parameter is not set.
+    pub fn from_args(args: &SimulateArgs, style: ExecutionStyle) -> Result<Self> {
+        Ok(Self {
+            style,
+            extended_threshold: args.extended_threshold.required("extended_threshold")?,
+            limit_hours: args.limit_hours.required("limit_hours")?,
+            profit_target_r: args.profit_target_r.required("profit_target_r")?,
+        })
+    }
+
+    /// Last date a pullback limit submitted on `date` may fill: whole days, rounded up.
+    fn limit_expiry(&self, date: NaiveDate) -> NaiveDate {
+        date + Duration::days(i64::from(self.limit_hours.div_ceil(24).max(1)))
+    }
+}
+
+/// One simulated round trip.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimTrade {
+    pub asset: String,
+    pub entry_date: NaiveDate,
+    pub exit_date: NaiveDate,
+    /// Close on the signal day the entry was decided
+    pub signal_close: f64,
+    pub entry_price: f64,
+    /// Quantity-weighted average of every exit fill
+    pub exit_price: f64,
+    pub initial_stop: Option<f64>,
+    pub trade_return: f64,
+    /// Profit in units of the initial risk (entry − initial stop)
+    pub r_multiple: Option<f64>,
+    pub scaled_out: bool,
+    /// "stop", "signal" or "open" (still held at the end, marked at the last close)
+    pub exit_reason: &'static str,
+}
+
+/// Fill statistics of one simulation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub style: ExecutionStyle,
+    pub entry_orders: usize,
+    pub entries_filled: usize,
+    pub entries_expired: usize,
+    /// Pending entries withdrawn because the signal turned off first
+    pub entries_cancelled: usize,
+    pub trades: usize,
+    pub win_rate: f64,
+    pub avg_return: f64,
+    pub avg_r_multiple: f64,
+    /// Mean entry price relative to the signal close (negative: bought below it)
+    pub avg_entry_slippage: f64,
+    pub stops_hit: usize,
+    /// Stops filled at an open that gapped below the stop
+    pub stop_gaps: usize,
+    pub scale_outs: usize,
+}
+
+/// Everything one simulation produced.
+pub struct Simulation {
+    pub rules: Rules,
+    pub events: Vec<Event>,
+    pub trades: Vec<SimTrade>,
+    pub summary: Summary,
+}
+
+#[derive(Debug, Clone)]
+struct Position {
+    entry_date: NaiveDate,
+    entry_price: f64,
+    quantity: f64,
+    remaining: f64,
+    signal_close: f64,
+    initial_stop: Option<f64>,
+    scaled_out: bool,
+    /// Proceeds of exit fills so far
+    proceeds: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingEntry {
+    order_id: usize,
+    signal_close: f64,
+    stop: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct AssetBook {
+    position: Option<Position>,
+    pending_entry: Option<PendingEntry>,
+    /// Current bar and phase while the asset's bar is being replayed
+    bar: Option<(NaiveDate, AlignedBar, Phase)>,
+    last_close: Option<(NaiveDate, f64)>,
+}
+
+#[derive(Default)]
+struct Counts {
+    entry_orders: usize,
+    entries_expired: usize,
+    entries_cancelled: usize,
+    stop_gaps: usize,
+}
+
+struct Engine<'a> {
+    bt: &'a Backtest,
+    rules: Rules,
+    day: usize,
+    next_id: usize,
+    /// Resting orders in submission order
+    orders: Vec<Order>,
+    books: BTreeMap<String, AssetBook>,
+    log: Vec<Event>,
+    trades: Vec<SimTrade>,
+    counts: Counts,
+}
+
+/// Open, high and low of a bar, falling back to the close when the CSV has no OHLC.
+fn range(bar: &AlignedBar) -> (f64, f64, f64) {
+    let open = bar.open.unwrap_or(bar.close);
+    let high = bar.high.unwrap_or_else(|| open.max(bar.close));
+    let low = bar.low.unwrap_or_else(|| open.min(bar.close));
+    (open, high, low)
+}
+
+impl<'a> Engine<'a> {
+    fn new(bt: &'a Backtest, rules: Rules) -> Self {
+        Self {
+            bt,
+            rules,
+            day: 0,
+            next_id: 1,
+            orders: Vec::new(),
+            books: bt
+                .signals
+                .keys()
+                .map(|k| (k.clone(), AssetBook::default()))
+                .collect(),
+            log: Vec::new(),
+            trades: Vec::new(),
+            counts: Counts::default(),
+        }
+    }
+
+    fn run(mut self) -> Simulation {
+        for day in 0..self.bt.dates.len() {
+            self.day = day;
+            let date = self.bt.dates[day];
+            for asset in self.bt.signals.keys() {
+                // Forward-filled bars are stale prices: nothing trades on them
+                let Some(bar) = self.bt.bars[asset][day].filter(|b| !b.filled) else {
+                    continue;
+                };
+                for phase in [Phase::Open, Phase::Intraday, Phase::Close] {
+                    if let Some(book) = self.books.get_mut(asset) {
+                        book.bar = Some((date, bar, phase));
+                    }
+                    self.emit(Event::Bar {
+                        date,
+                        asset: asset.clone(),
+                        phase,
+                    });
+                }
+                if let Some(book) = self.books.get_mut(asset) {
+                    book.bar = None;
+                    book.last_close = Some((date, bar.close));
+                }
+            }
+        }
+        self.mark_open_positions();
+        let summary = self.summary();
+        Simulation {
+            rules: self.rules,
+            events: self.log,
+            trades: self.trades,
+            summary,
+        }
+    }
+
+    /// Record an event and handle it before returning, so its consequences are logged in
+    /// causal order.
+    fn emit(&mut self, event: Event) {
+        self.log.push(event.clone());
+        match event {
+            Event::Bar { date, asset, phase } => self.on_bar(date, &asset, phase),
+            Event::Submitted(order) => self.on_submitted(order),
+            Event::Filled(fill) => self.on_fill(&fill),
+            Event::Cancelled { order, reason, .. } => self.on_cancelled(&order, reason),
+            Event::Amended { .. } => {}
+        }
+    }
+
+    fn submit(
+        &mut self,
+        asset: &str,
+        side: Side,
+        quantity: f64,
+        kind: OrderType,
+        purpose: Purpose,
+        expires: Option<NaiveDate>,
+    ) -> usize {
+        let Some(&(date, _, phase)) = self.books.get(asset).and_then(|b| b.bar.as_ref()) else {
+            return 0;
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.emit(Event::Submitted(Order {
+            id,
+            asset: asset.to_string(),
+            side,
+            quantity,
+            kind,
+            purpose,
+            submitted: date,
+            submitted_phase: phase,
+            expires,
+        }));
+        id
+    }
+
+    fn cancel_where(
+        &mut self,
+        date: NaiveDate,
+        reason: &'static str,
+        pred: impl Fn(&Order) -> bool,
+    ) {
+        let cancelled: Vec<Order> = self.orders.iter().filter(|o| pred(o)).cloned().collect();
+        for order in cancelled {
+            self.emit(Event::Cancelled {
+                date,
+                order,
+                reason,
+            });
+        }
+    }
+
+    fn on_bar(&mut self, date: NaiveDate, asset: &str, phase: Phase) {
+        match phase {
+            Phase::Open => {
+                self.cancel_where(date, "expired", |o| {
+                    o.asset == asset && o.expires.is_some_and(|e| e < date)
+                });
+                self.match_resting(asset, phase);
+            }
+            Phase::Intraday => self.match_resting(asset, phase),
+            Phase::Close => {
+                self.match_resting(asset, phase);
+                self.decide(date, asset);
+            }
+        }
+    }
+
+    /// Fill resting orders the current phase reaches, one at a time, since each fill can
+    /// cancel or resize the others. Intraday, stops are checked before limits: a daily bar
+    /// does not say which was touched first.
+    fn match_resting(&mut self, asset: &str, phase: Phase) {
+        let Some(&(date, bar, _)) = self.books.get(asset).and_then(|b| b.bar.as_ref()) else {
+            return;
+        };
+        let (open, high, low) = range(&bar);
+        loop {
+            let eligible =
+                |o: &&Order| o.asset == asset && (o.submitted, o.submitted_phase) < (date, phase);
+            let hit = |o: &Order| -> Option<(f64, bool)> {
+                match (phase, o.kind, o.side) {
+                    (Phase::Open, OrderType::MarketOnOpen, _) => Some((open, false)),
+                    (Phase::Open, OrderType::Stop(p), Side::Sell) if open <= p => {
+                        Some((open, true))
+                    }
+                    (Phase::Open, OrderType::Limit(p), Side::Buy) if open <= p => {
+                        Some((open, true))
+                    }
+                    (Phase::Open, OrderType::Limit(p), Side::Sell) if open >= p => {
+                        Some((open, true))
+                    }
+                    (Phase::Intraday, OrderType::Stop(p), Side::Sell) if low <= p => {
+                        Some((p, false))
+                    }
+                    (Phase::Intraday, OrderType::Limit(p), Side::Buy) if low <= p => {
+                        Some((p, false))
+                    }
+                    (Phase::Intraday, OrderType::Limit(p), Side::Sell) if high >= p => {
+                        Some((p, false))
+                    }
+                    (Phase::Close, OrderType::MarketOnClose, _) => Some((bar.close, false)),
+                    _ => None,
+                }
+            };
+            let next = self
+                .orders
+                .iter()
+                .filter(eligible)
+                .filter_map(|o| hit(o).map(|(price, gap)| (o, price, gap)))
+                .min_by_key(|(o, ..)| (!matches!(o.kind, OrderType::Stop(_)), o.id));
+            let Some((order, price, gap)) = next else {
+                break;
+            };
+            let fill = Fill {
+                order_id: order.id,
+                asset: asset.to_string(),
+                date,
+                phase,
+                side: order.side,
+                quantity: order.quantity,
+                price,
+                purpose: order.purpose,
+                gap,
+            };
+            self.emit(Event::Filled(fill));
+        }
+    }
+
+    fn on_submitted(&mut self, order: Order) {
+        let at_close = order.kind == OrderType::MarketOnClose
+            && self
+                .books
+                .get(&order.asset)
+                .and_then(|b| b.bar)
+                .is_some_and(|(date, _, phase)| date == order.submitted && phase == Phase::Close);
+        if order.purpose == Purpose::Entry {
+            self.counts.entry_orders += 1;
+        }
+        let asset = order.asset.clone();
+        self.orders.push(order);
+        if at_close {
+            // Decided on the closing print and crossed in the closing auction
+            self.match_close_now(&asset);
+        }
+    }
+
+    fn match_close_now(&mut self, asset: &str) {
+        let Some((date, bar, _)) = self.books.get(asset).and_then(|b| b.bar) else {
+            return;
+        };
+        let now: Vec<Order> = self
+            .orders
+            .iter()
+            .filter(|o| {
+                o.asset == asset && o.kind == OrderType::MarketOnClose && o.submitted == date
+            })
+            .cloned()
+            .collect();
+        for order in now {
+            self.emit(Event::Filled(Fill {
+                order_id: order.id,
+                asset: asset.to_string(),
+                date,
+                phase: Phase::Close,
+                side: order.side,
+                quantity: order.quantity,
+                price: bar.close,
+                purpose: order.purpose,
+                gap: false,
+            }));
+        }
+    }
+
+    fn on_cancelled(&mut self, order: &Order, reason: &'static str) {
+        self.orders.retain(|o| o.id != order.id);
+        if order.purpose == Purpose::Entry {
+            if let Some(book) = self.books.get_mut(&order.asset) {
+                book.pending_entry = None;
+            }
+            if reason == "expired" {
+                self.counts.entries_expired += 1;
+            } else {
+                self.counts.entries_cancelled += 1;
+            }
+        }
+    }
+
+    fn on_fill(&mut self, fill: &Fill) {
+        self.orders.retain(|o| o.id != fill.order_id);
+        let asset = fill.asset.as_str();
+        match fill.purpose {
+            Purpose::Entry => {
+                let Some(book) = self.books.get_mut(asset) else {
+                    return;
+                };
+                let Some(pending) = book.pending_entry.take() else {
+                    return;
+                };
+                book.position = Some(Position {
+                    entry_date: fill.date,
+                    entry_price: fill.price,
+                    quantity: fill.quantity,
+                    remaining: fill.quantity,
+                    signal_close: pending.signal_close,
+                    initial_stop: pending.stop,
+                    scaled_out: false,
+                    proceeds: 0.0,
+                });
+                if let Some(stop) = pending.stop {
+                    self.submit(
+                        asset,
+                        Side::Sell,
+                        fill.quantity,
+                        OrderType::Stop(stop),
+                        Purpose::Stop,
+                        None,
+                    );
+                    let risk = fill.price - stop;
+                    if self.rules.profit_target_r > 0.0 && risk > 0.0 {
+                        let target = self.rules.profit_target_r.mul_add(risk, fill.price);
+                        self.submit(
+                            asset,
+                            Side::Sell,
+                            fill.quantity / 2.0,
+                            OrderType::Limit(target),
+                            Purpose::ScaleOut,
+                            None,
+                        );
+                    }
+                }
+            }
+            Purpose::ScaleOut => {
+                let Some(pos) = self.books.get_mut(asset).and_then(|b| b.position.as_mut()) else {
+                    return;
+                };
+                pos.remaining -= fill.quantity;
+                pos.proceeds += fill.quantity * fill.price;
+                pos.scaled_out = true;
+                let remaining = pos.remaining;
+                // Protective and exit orders now cover only what is left
+                for o in self
+                    .orders
+                    .iter_mut()
+                    .filter(|o| o.asset == asset && o.side == Side::Sell)
+                {
+                    o.quantity = remaining;
+                }
+            }
+            Purpose::Stop | Purpose::Exit => {
+                if fill.purpose == Purpose::Stop && fill.gap {
+                    self.counts.stop_gaps += 1;
+                }
+                let reason = if fill.purpose == Purpose::Stop {
+                    "stop"
+                } else {
+                    "signal"
+                };
+                self.close_position(asset, fill.date, fill.quantity * fill.price, reason);
+                self.cancel_where(fill.date, "position closed", |o| o.asset == asset);
+            }
+        }
+    }
+
+    fn close_position(
+        &mut self,
+        asset: &str,
+        date: NaiveDate,
+        proceeds: f64,
+        reason: &'static str,
+    ) {
+        let Some(pos) = self.books.get_mut(asset).and_then(|b| b.position.take()) else {
+            return;
+        };
+        let exit_price = (pos.proceeds + proceeds) / pos.quantity;
+        let r_multiple = pos
+            .initial_stop
+            .map(|stop| pos.entry_price - stop)
+            .filter(|risk| *risk > 0.0)
+            .map(|risk| (exit_price - pos.entry_price) / risk);
+        self.trades.push(SimTrade {
+            asset: asset.to_string(),
+            entry_date: pos.entry_date,
+            exit_date: date,
+            signal_close: pos.signal_close,
+            entry_price: pos.entry_price,
+            exit_price,
+            initial_stop: pos.initial_stop,
+            trade_return: exit_price / pos.entry_price - 1.0,
+            r_multiple,
+            scaled_out: pos.scaled_out,
+            exit_reason: reason,
+        });
+    }
+
+    /// Playbook decisions on the closing print: enter on a long signal, withdraw a pending
+    /// entry or exit when it turns off, otherwise ratchet the stop.
+    fn decide(&mut self, date: NaiveDate, asset: &str) {
+        let signal = &self.bt.signals[asset][self.day];
+        if !signal.available() {
+            return;
+        }
+        let long = signal.raw_weight() > 0.0;
+        let Some(book) = self.books.get(asset) else {
+            return;
+        };
+        let exiting = self
+            .orders
+            .iter()
+            .any(|o| o.asset == asset && o.purpose == Purpose::Exit);
+        match (&book.position, book.pending_entry) {
+            (None, None) if long => {
+                let close = signal.price();
+                let quantity = signal.raw_weight().min(1.0) / close;
+                let pullback = signal
+                    .ma_long()
+                    .filter(|ma| close > ma * (1.0 + self.rules.extended_threshold));
+                let (kind, expires) = match (self.rules.style, pullback) {
+                    (ExecutionStyle::NextOpen, _) => (OrderType::MarketOnOpen, None),
+                    (ExecutionStyle::Pullback, Some(ma)) => {
+                        (OrderType::Limit(ma), Some(self.rules.limit_expiry(date)))
+                    }
+                    _ => (OrderType::MarketOnClose, Some(date)),
+                };
+                if let Some(book) = self.books.get_mut(asset) {
+                    book.pending_entry = Some(PendingEntry {
+                        order_id: self.next_id,
+                        signal_close: close,
+                        stop: signal.stop_level(),
+                    });
+                }
+                self.submit(asset, Side::Buy, quantity, kind, Purpose::Entry, expires);
+            }
+            (None, Some(pending)) if !long => {
+                self.cancel_where(date, "signal off", |o| o.id == pending.order_id);
+            }
+            (Some(pos), _) if !long && !exiting => {
+                let kind = if self.rules.style == ExecutionStyle::NextOpen {
+                    OrderType::MarketOnOpen
+                } else {
+                    OrderType::MarketOnClose
+                };
+                let remaining = pos.remaining;
+                self.submit(asset, Side::Sell, remaining, kind, Purpose::Exit, None);
+            }
+            (Some(_), _) if long => {
+                // Trailing stop: max(prior stop, today's close − k·ATR)
+                let Some(level) = signal.stop_level() else {
+                    return;
+                };
+                let amended = self.orders.iter_mut().find(|o| {
+                    o.asset == asset && matches!(o.kind, OrderType::Stop(stop) if level > stop)
+                });
+                if let Some(order) = amended {
+                    order.kind = OrderType::Stop(level);
+                    let order = order.clone();
+                    self.emit(Event::Amended { date, order });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn mark_open_positions(&mut self) {
+        let open: Vec<(String, NaiveDate, f64)> = self
+            .books
+            .iter()
+            .filter(|(_, b)| b.position.is_some())
+            .filter_map(|(name, b)| b.last_close.map(|(d, c)| (name.clone(), d, c)))
+            .collect();
+        for (asset, date, close) in open {
+            let remaining = self.books[&asset]
+                .position
+                .as_ref()
+                .map_or(0.0, |p| p.remaining);
+            self.close_position(&asset, date, remaining * close, "open");
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn summary(&self) -> Summary {
+        let n = self.trades.len();
+        let mean = |values: Vec<f64>| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+        };
+        Summary {
+            style: self.rules.style,
+            entry_orders: self.counts.entry_orders,
+            entries_filled: n,
+            entries_expired: self.counts.entries_expired,
+            entries_cancelled: self.counts.entries_cancelled,
+            trades: n,
+            win_rate: if n == 0 {
+                0.0
+            } else {
+                self.trades.iter().filter(|t| t.trade_return > 0.0).count() as f64 / n as f64
+            },
+            avg_return: mean(self.trades.iter().map(|t| t.trade_return).collect()),
+            avg_r_multiple: mean(self.trades.iter().filter_map(|t| t.r_multiple).collect()),
+            avg_entry_slippage: mean(
+                self.trades
+                    .iter()
+                    .map(|t| t.entry_price / t.signal_close - 1.0)
+                    .collect(),
+            ),
+            stops_hit: self
+                .trades
+                .iter()
+                .filter(|t| t.exit_reason == "stop")
+                .count(),
+            stop_gaps: self.counts.stop_gaps,
+            scale_outs: self.trades.iter().filter(|t| t.scaled_out).count(),
+        }
+    }
+}
+
+/// Replay `bt`'s signals and bars through the order simulator under `rules`.
+#[must_use]
+pub fn simulate(bt: &Backtest, rules: Rules) -> Simulation {
+    Engine::new(bt, rules).run()
+}
+
+/// One row of `<style>_events.csv`; columns follow `EVENT_COLUMNS`.
+fn event_record(event: &Event) -> [String; 11] {
+    let order = |date: NaiveDate, event: &str, phase: String, o: &Order, note: &str| {
+        [
+            date.to_string(),
+            event.to_string(),
+            phase,
+            o.asset.clone(),
+            o.id.to_string(),
+            serde_plain(o.side),
+            o.kind.name().to_string(),
+            serde_plain(o.purpose),
+            format!("{:.8}", o.quantity),
+            o.kind
+                .price()
+                .map(|p| format!("{p:.8}"))
+                .unwrap_or_default(),
+            note.to_string(),
+        ]
+    };
+    match event {
+        Event::Bar { date, asset, phase } => {
+            let mut row: [String; 11] = Default::default();
+            row[0] = date.to_string();
+            row[1] = "bar".to_string();
+            row[2] = serde_plain(*phase);
+            row[3].clone_from(asset);
+            row
+        }
+        Event::Submitted(o) => order(
+            o.submitted,
+            "submitted",
+            serde_plain(o.submitted_phase),
+            o,
+            "",
+        ),
+        Event::Amended { date, order: o } => order(*date, "amended", String::new(), o, ""),
+        Event::Cancelled {
+            date,
+            order: o,
+            reason,
+        } => order(*date, "cancelled", String::new(), o, reason),
+        Event::Filled(f) => [
+            f.date.to_string(),
+            "filled".to_string(),
+            serde_plain(f.phase),
+            f.asset.clone(),
+            f.order_id.to_string(),
+            serde_plain(f.side),
+            String::new(),
+            serde_plain(f.purpose),
+            format!("{:.8}", f.quantity),
+            format!("{:.8}", f.price),
+            if f.gap { "gap" } else { "" }.to_string(),
+        ],
+    }
+}
+
+const EVENT_COLUMNS: [&str; 11] = [
+    "date",
+    "event",
+    "phase",
+    "asset",
+    "order_id",
+    "side",
+    "order_type",
+    "purpose",
+    "quantity",
+    "price",
+    "note",
+];
+
+/// Kebab-case name of a unit enum variant.
+fn serde_plain(v: impl Serialize) -> String {
+    serde_json::to_value(v)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Run the backtest, simulate the requested execution style (or all of them) and write
+/// `<style>_trades.csv`, `<style>_events.csv` and `simulation.csv` to `--out`.
+///
+/// # Errors
+/// Returns an error if the backtest fails or an output file cannot be written.
+pub fn execute(args: &SimulateArgs) -> Result<()> {
+    let out_dir = args.base.out.as_ref().required("out")?;
+    fs::create_dir_all(out_dir).context("create out dir")?;
+    let data = strategy::load_market_data(&args.base)?;
+    let bt = strategy::run_backtest(&data, &args.base)?;
+
+    let styles: Vec<ExecutionStyle> = args
+        .execution
+        .map_or_else(|| ExecutionStyle::ALL.to_vec(), |s| vec![s]);
+    println!(
+        "🎬 Event-driven simulation: {} assets, {} days, {} vectorized trades",
+        bt.signals.len(),
+        bt.dates.len(),
+        bt.trades.len()
+    );
+    let rules = styles
+        .into_iter()
+        .map(|style| Rules::from_args(args, style))
+        .collect::<Result<Vec<_>>>()?;
+    let sims: Vec<Simulation> = rules.par_iter().map(|&r| simulate(&bt, r)).collect();
+
+    for sim in &sims {
+        let name = sim.rules.style.name();
+        let mut wtr = WriterBuilder::new().from_path(out_dir.join(format!("{name}_trades.csv")))?;
+        for t in &sim.trades {
+            wtr.serialize(t)?;
+        }
+        wtr.flush()?;
+        let mut wtr = WriterBuilder::new().from_path(out_dir.join(format!("{name}_events.csv")))?;
+        wtr.write_record(EVENT_COLUMNS)?;
+        for e in &sim.events {
+            wtr.write_record(event_record(e))?;
+        }
+        wtr.flush()?;
+    }
+    let mut wtr = WriterBuilder::new().from_path(out_dir.join("simulation.csv"))?;
+    for sim in &sims {
+        wtr.serialize(&sim.summary)?;
+    }
+    wtr.flush()?;
+
+    println!(
+        "   {:<16} {:>7} {:>7} {:>7} {:>7} {:>8} {:>9} {:>8} {:>10} {:>10}",
+        "execution",
+        "orders",
+        "filled",
+        "expired",
+        "stops",
+        "gaps",
+        "win rate",
+        "avg R",
+        "avg ret",
+        "slippage"
+    );
+    for s in sims.iter().map(|s| &s.summary) {
+        println!(
+            "   {:<16} {:>7} {:>7} {:>7} {:>7} {:>8} {:>8.1}% {:>8.2} {:>9.2}% {:>9.2}%",
+            s.style.name(),
+            s.entry_orders,
+            s.entries_filled,
+            s.entries_expired,
+            s.stops_hit,
+            s.stop_gaps,
+            s.win_rate * 100.0,
+            s.avg_r_multiple,
+            s.avg_return * 100.0,
+            s.avg_entry_slippage * 100.0
+        );
+    }
+    println!("📄 Simulation written to {}", out_dir.display());
+    Ok(())
+}
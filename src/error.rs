@@ -0,0 +1,198 @@
+//! Crate error type. Each variant is a failure class, so long-running callers such as the
+//! daemon can decide whether to retry, skip or abort without parsing messages.
+
+use std::fmt::Display;
+use std::time::Duration;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Missing or invalid settings from the config file, environment or CLI flags
+    #[error("configuration error: {0}")]
+    Config(String),
+    /// Input data that is missing, malformed or too short to use
+    #[error("data error: {0}")]
+    Data(String),
+    /// Transport failure or unexpected HTTP status from the market-data API
+    #[error("network error: {0}")]
+    Network(String),
+    /// The market-data API kept answering 429 after the built-in retries
+    #[error("rate limited: {message}")]
+    RateLimited {
+        message: String,
+        /// Server-suggested wait from the last `Retry-After` header
+        retry_after: Option<Duration>,
+    },
+    /// AI provider request or response failure
+    #[error("AI provider error: {0}")]
+    Ai(String),
+    #[error("{context}: {error}")]
+    Io {
+        context: String,
+        error: std::io::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// What a long-running caller should do after an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Transient: try again, after the given wait if the server named one
+    Retry(Option<Duration>),
+    /// This cycle's inputs are unusable; later cycles may succeed
+    Skip,
+    /// Retrying cannot help until an operator intervenes
+    Abort,
+}
+
+impl Error {
+    pub fn config(msg: impl Display) -> Self {
+        Self::Config(msg.to_string())
+    }
+
+    pub fn data(msg: impl Display) -> Self {
+        Self::Data(msg.to_string())
+    }
+
+    pub fn network(msg: impl Display) -> Self {
+        Self::Network(msg.to_string())
+    }
+
+    pub fn ai(msg: impl Display) -> Self {
+        Self::Ai(msg.to_string())
+    }
+
+    /// Short name of the failure class, for logs.
+    #[must_use]
+    pub const fn class(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::Data(_) => "data",
+            Self::Network(_) => "network",
+            Self::RateLimited { .. } => "rate-limit",
+            Self::Ai(_) => "ai",
+            Self::Io { .. } => "io",
+        }
+    }
+
+    /// Network failures and rate limits are transient; bad data or an AI outage only spoils
+    /// the current cycle; configuration and filesystem errors need an operator.
+    #[must_use]
+    pub const fn recovery(&self) -> Recovery {
+        match self {
+            Self::Network(_) => Recovery::Retry(None),
+            Self::RateLimited { retry_after, .. } => Recovery::Retry(*retry_after),
+            Self::Data(_) | Self::Ai(_) => Recovery::Skip,
+            Self::Config(_) | Self::Io { .. } => Recovery::Abort,
+        }
+    }
+
+    /// Prefix the message with `ctx`, keeping the class.
+    #[must_use]
+    pub fn context(self, ctx: impl Display) -> Self {
+        match self {
+            Self::Config(m) => Self::Config(format!("{ctx}: {m}")),
+            Self::Data(m) => Self::Data(format!("{ctx}: {m}")),
+            Self::Network(m) => Self::Network(format!("{ctx}: {m}")),
+            Self::RateLimited {
+                message,
+                retry_after,
+            } => Self::RateLimited {
+                message: format!("{ctx}: {message}"),
+                retry_after,
+            },
+            Self::Ai(m) => Self::Ai(format!("{ctx}: {m}")),
+            Self::Io { context, error } => Self::Io {
+                context: format!("{ctx}: {context}"),
+                error,
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io {
+            context: "I/O error".to_string(),
+            error,
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        let message = e.to_string();
+        match e.into_kind() {
+            csv::ErrorKind::Io(io) => io.into(),
+            _ => Self::Data(message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::data(e)
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(e: chrono::ParseError) -> Self {
+        Self::data(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Self::config(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            Self::data(e)
+        } else {
+            Self::network(e)
+        }
+    }
+}
+
+impl From<tempfile::PersistError> for Error {
+    fn from(e: tempfile::PersistError) -> Self {
+        e.error.into()
+    }
+}
+
+/// Attach context to a fallible result, converting its error into [`Error`].
+pub trait Context<T> {
+    /// # Errors
+    /// Returns the original error, converted and prefixed with `ctx`.
+    fn context(self, ctx: impl Display) -> Result<T>;
+
+    /// # Errors
+    /// Returns the original error, converted and prefixed with the lazily built context.
+    fn with_context<C: Display>(self, f: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context(self, ctx: impl Display) -> Result<T> {
+        self.map_err(|e| e.into().context(ctx))
+    }
+
+    fn with_context<C: Display>(self, f: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|e| e.into().context(f()))
+    }
+}
+
+/// Settings are `Option`s until configuration defaults are applied.
+pub trait Required<T> {
+    /// # Errors
+    /// Returns [`Error::Config`] naming the unset setting.
+    fn required(self, name: &str) -> Result<T>;
+}
+
+impl<T> Required<T> for Option<T> {
+    fn required(self, name: &str) -> Result<T> {
+        self.ok_or_else(|| Error::Config(format!("`{name}` is not set")))
+    }
+}
@@ -0,0 +1,494 @@
+//! Incremental signal engine for the daemon. Rolling indicator state per asset is saved next
+//! to the signal files, so a cycle only parses the bars appended to the input CSVs since the
+//! last one and appends their signal and regime rows. Any change to history that was already
+//! processed (an edited or removed row, a late bar, a new asset, different settings) falls
+//! back to a full recompute.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Context, Error, Required, Result};
+use crate::regime::{self, RegimeConfig};
+use crate::strategy::{
+    self, DailySignal, ForwardFill, IndicatorState, RankRules, RankState, Series,
+};
+use crate::{SignalMode, StrategyArgs};
+
+/// Engine state, written to the output directory next to `signals_<asset>.csv`.
+pub const STATE_FILE: &str = "signal_state.json";
+
+/// Outcome of one [`update`].
+#[derive(Debug)]
+pub struct Update {
+    /// Why every signal was recomputed; `None` when new bars were appended
+    pub recomputed: Option<String>,
+    /// Dates written this cycle (every date on a full recompute)
+    pub dates: Vec<NaiveDate>,
+    /// Assets with signal files
+    pub assets: usize,
+    /// Assets dropped for insufficient history: (name, observed days)
+    pub skipped: Vec<(String, usize)>,
+}
+
+/// Prefix of an input CSV that has been processed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Consumed {
+    path: PathBuf,
+    bytes: usize,
+    /// SHA-256 (hex) of the processed bytes
+    sha256: String,
+    rows: usize,
+}
+
+impl Consumed {
+    fn new(path: &Path, csv: &[u8], rows: usize) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            bytes: csv.len(),
+            sha256: sha256(csv),
+            rows,
+        }
+    }
+
+    /// Bytes appended since the last cycle, or `None` if processed bytes changed.
+    fn appended<'a>(&self, csv: &'a [u8]) -> Option<&'a [u8]> {
+        (csv.len() >= self.bytes && sha256(&csv[..self.bytes]) == self.sha256)
+            .then(|| &csv[self.bytes..])
+    }
+
+    fn advance(&mut self, csv: &[u8], new_rows: usize) {
+        *self = Self::new(&self.path, csv, self.rows + new_rows);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AssetState {
+    name: String,
+    input: Consumed,
+    /// Dropped for insufficient history at the last full recompute
+    skipped: bool,
+    fill: ForwardFill,
+    /// Indicators of the current tradeable run; `None` while the asset is untradeable
+    indicators: Option<IndicatorState>,
+    /// Latest signals, enough to cover the cross-sectional ranking window
+    recent: VecDeque<DailySignal>,
+}
+
+/// Trailing dates with BTC and aligned asset closes: the regime classifier's inputs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RegimeTail {
+    dates: Vec<NaiveDate>,
+    btc_close: Vec<f64>,
+    asset_closes: Vec<Vec<Option<f64>>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EngineState {
+    /// Settings that shape signals; any change forces a recompute
+    settings: Value,
+    btc: Consumed,
+    btc_fill: ForwardFill,
+    /// In input order, skipped assets included
+    assets: Vec<AssetState>,
+    /// Last union-calendar date processed
+    last_date: Option<NaiveDate>,
+    ranks: RankState,
+    tail: RegimeTail,
+}
+
+/// New signal rows from one [`EngineState::advance`].
+struct Batch {
+    dates: Vec<NaiveDate>,
+    signals: BTreeMap<String, Vec<DailySignal>>,
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Parse rows appended after the processed prefix, reusing the file's header line.
+fn parse_appended(csv: &[u8], appended: &[u8]) -> Result<Series> {
+    let header = csv
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(csv, |i| &csv[..=i]);
+    strategy::parse_series(&[header, appended].concat())
+}
+
+fn settings(args: &StrategyArgs, assets: &[PathBuf]) -> Value {
+    json!({
+        "btc": args.btc,
+        "assets": assets,
+        "ma_short": args.ma_short,
+        "ma_long": args.ma_long,
+        "min_signals": args.min_signals,
+        "short_alts": args.short_alts,
+        "stop_lookback": args.stop_lookback,
+        "atr_mult": args.atr_mult,
+        "vol_mult": args.vol_mult,
+        "adv_lookback": args.adv_lookback,
+        "max_fill_days": args.max_fill_days,
+        "mode": args.mode,
+        "rank_by": args.rank_by,
+        "rank_lookback": args.rank_lookback,
+        "rank_skip": args.rank_skip,
+        "top_k": args.top_k,
+        "short_bottom": args.short_bottom,
+        "rebalance_days": args.rebalance_days,
+        "regime_vol_lookback": args.regime_vol_lookback,
+        "regime_high_vol": args.regime_high_vol,
+        "regime_breadth_bull": args.regime_breadth_bull,
+        "regime_breadth_bear": args.regime_breadth_bear,
+        "regime_neutral_exposure": args.regime_neutral_exposure,
+        "regime_risk_off_exposure": args.regime_risk_off_exposure,
+        "regime_crisis_exposure": args.regime_crisis_exposure,
+    })
+}
+
+/// Dates the regime classifier needs before the newest one to reproduce its last reading.
+fn regime_window(config: &RegimeConfig) -> usize {
+    config
+        .ma_long
+        .max(config.ma_short)
+        .max(config.vol_lookback * 3)
+        + 1
+}
+
+impl EngineState {
+    /// Advance every series over the union-calendar dates after `last_date` found in `btc`
+    /// and `assets` (new rows only, aligned with `self.assets`).
+    fn advance(&mut self, btc: &Series, assets: &[Series], args: &StrategyArgs) -> Result<Batch> {
+        let max_fill = args.max_fill_days.required("max_fill_days")?;
+        let rank_rules = (args.mode.required("mode")? == SignalMode::CrossSectional)
+            .then(|| RankRules::from_args(args))
+            .transpose()?;
+        let keep = rank_rules.map_or(1, |r| r.history());
+
+        let after = self
+            .last_date
+            .or_else(|| btc.dates().first().map(|d| *d - chrono::Duration::days(1)));
+        let index = |ser: &Series| -> BTreeMap<NaiveDate, usize> {
+            ser.dates()
+                .iter()
+                .enumerate()
+                .map(|(i, d)| (*d, i))
+                .collect()
+        };
+        let btc_idx = index(btc);
+        let asset_idx: Vec<BTreeMap<NaiveDate, usize>> = assets.iter().map(index).collect();
+        let calendar: BTreeSet<NaiveDate> = btc
+            .dates()
+            .iter()
+            .chain(
+                self.assets
+                    .iter()
+                    .zip(assets)
+                    .filter(|(a, _)| !a.skipped)
+                    .flat_map(|(_, ser)| ser.dates()),
+            )
+            .copied()
+            .filter(|d| after.is_some_and(|after| *d > after))
+            .collect();
+
+        let mut batch = Batch {
+            dates: Vec::new(),
+            signals: BTreeMap::new(),
+        };
+        for d in calendar {
+            self.last_date = Some(d);
+            let btc_bar = self
+                .btc_fill
+                .step(btc_idx.get(&d).map(|&j| btc.bar(j)), max_fill);
+            let mut bars = Vec::new();
+            for ((asset, ser), idx) in self.assets.iter_mut().zip(assets).zip(&asset_idx) {
+                if !asset.skipped {
+                    bars.push(asset.fill.step(idx.get(&d).map(|&j| ser.bar(j)), max_fill));
+                }
+            }
+            // BTC is the numeraire: dates it cannot cover are dropped from the calendar
+            let Some(btc_bar) = btc_bar else {
+                continue;
+            };
+            batch.dates.push(d);
+            self.tail.dates.push(d);
+            self.tail.btc_close.push(btc_bar.close);
+            self.tail.asset_closes.resize_with(bars.len(), Vec::new);
+
+            let active = self.assets.iter_mut().filter(|a| !a.skipped);
+            for ((asset, bar), closes) in active.zip(bars).zip(&mut self.tail.asset_closes) {
+                closes.push(bar.map(|b| b.close));
+                let signal = match bar {
+                    Some(bar) => {
+                        // Indicators restart on each contiguous tradeable run
+                        let mut state = match asset.indicators.take() {
+                            Some(state) => state,
+                            None => IndicatorState::new(args)?,
+                        };
+                        let signal = state.step(d, &bar, btc_bar.close);
+                        asset.indicators = Some(state);
+                        signal
+                    }
+                    None => {
+                        asset.indicators = None;
+                        DailySignal::unavailable(d)
+                    }
+                };
+                asset.recent.push_back(signal);
+                if asset.recent.len() > keep {
+                    asset.recent.pop_front();
+                }
+            }
+
+            if let Some(rules) = &rank_rules {
+                let mut universe: Vec<(&str, &mut [DailySignal])> = self
+                    .assets
+                    .iter_mut()
+                    .filter(|a| !a.skipped)
+                    .map(|a| (a.name.as_str(), a.recent.make_contiguous()))
+                    .collect();
+                universe.sort_by(|a, b| a.0.cmp(b.0));
+                self.ranks.step(&mut universe, rules);
+            }
+            for asset in self.assets.iter().filter(|a| !a.skipped) {
+                if let Some(signal) = asset.recent.back() {
+                    batch
+                        .signals
+                        .entry(asset.name.clone())
+                        .or_default()
+                        .push(signal.clone());
+                }
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Classify the regime on the last `n` tail dates, then drop tail dates no longer needed.
+    fn classify_latest(&mut self, n: usize, config: &RegimeConfig) -> Vec<regime::RegimeState> {
+        let tail = &mut self.tail;
+        let mut states = regime::classify(&tail.dates, &tail.btc_close, &tail.asset_closes, config);
+        let states = states.split_off(states.len().saturating_sub(n));
+        let drop = tail.dates.len().saturating_sub(regime_window(config));
+        tail.dates.drain(..drop);
+        tail.btc_close.drain(..drop);
+        for closes in &mut tail.asset_closes {
+            closes.drain(..drop);
+        }
+        states
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let tmp = tempfile::NamedTempFile::new_in(dir)?;
+        serde_json::to_writer(&tmp, self)?;
+        tmp.persist(path)?;
+        Ok(())
+    }
+}
+
+/// Input CSVs read for one cycle.
+struct Inputs {
+    btc: (PathBuf, Vec<u8>),
+    /// (series name, path, contents) in input order
+    assets: Vec<(String, PathBuf, Vec<u8>)>,
+}
+
+impl Inputs {
+    fn read(args: &StrategyArgs, assets: &[PathBuf]) -> Result<Self> {
+        let btc = args.btc.clone().required("btc")?;
+        let btc_csv = fs::read(&btc).context("read BTC")?;
+        let mut out = Vec::new();
+        for p in assets {
+            let name = p
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .ok_or_else(|| Error::config(format!("{} is not a file path", p.display())))?;
+            let csv = fs::read(p).with_context(|| format!("read {}", p.display()))?;
+            out.push((name, p.clone(), csv));
+        }
+        Ok(Self {
+            btc: (btc, btc_csv),
+            assets: out,
+        })
+    }
+}
+
+/// Rebuild every signal from the full input history and overwrite the signal files.
+fn recompute(
+    args: &StrategyArgs,
+    settings: Value,
+    inputs: &Inputs,
+    out: &Path,
+) -> Result<(EngineState, Update)> {
+    let min_required_days = args.ma_long.required("ma_long")? + 10;
+    let (btc_path, btc_csv) = &inputs.btc;
+    let btc = strategy::parse_series(btc_csv).context("read BTC")?;
+    let mut series = Vec::new();
+    let mut assets = Vec::new();
+    let mut skipped = Vec::new();
+    for (name, path, csv) in &inputs.assets {
+        let ser =
+            strategy::parse_series(csv).with_context(|| format!("read {}", path.display()))?;
+        let rows = ser.dates().len();
+        if rows < min_required_days {
+            skipped.push((name.clone(), rows));
+        }
+        assets.push(AssetState {
+            name: name.clone(),
+            input: Consumed::new(path, csv, rows),
+            skipped: rows < min_required_days,
+            fill: ForwardFill::default(),
+            indicators: None,
+            recent: VecDeque::new(),
+        });
+        series.push(ser);
+    }
+    let mut state = EngineState {
+        settings,
+        btc: Consumed::new(btc_path, btc_csv, btc.dates().len()),
+        btc_fill: ForwardFill::default(),
+        assets,
+        last_date: None,
+        ranks: RankState::default(),
+        tail: RegimeTail::default(),
+    };
+    let batch = state.advance(&btc, &series, args)?;
+    if batch.dates.len() < min_required_days {
+        return Err(Error::data("Not enough overlapping data after alignment."));
+    }
+
+    fs::create_dir_all(out).context("create out dir")?;
+    for (name, signals) in &batch.signals {
+        strategy::write_signals_csv(&out.join(format!("signals_{name}.csv")), signals)?;
+    }
+    let regimes = state.classify_latest(batch.dates.len(), &RegimeConfig::from_args(args)?);
+    regime::write_regimes(&out.join("regime.csv"), &regimes)?;
+    let update = Update {
+        recomputed: None,
+        dates: batch.dates,
+        assets: batch.signals.len(),
+        skipped,
+    };
+    Ok((state, update))
+}
+
+/// Resume from `state`: parse only appended rows and append their signals, or return the
+/// reason a full recompute is needed.
+fn resume(
+    mut state: EngineState,
+    args: &StrategyArgs,
+    inputs: &Inputs,
+    out: &Path,
+) -> Result<std::result::Result<(EngineState, Update), String>> {
+    let min_required_days = args.ma_long.required("ma_long")? + 10;
+    let (btc_path, btc_csv) = &inputs.btc;
+    let Some(appended) = state.btc.appended(btc_csv) else {
+        return Ok(Err(format!("{} history changed", btc_path.display())));
+    };
+    let btc = parse_appended(btc_csv, appended).context("read BTC")?;
+
+    let mut series = Vec::new();
+    for (asset, (name, path, csv)) in state.assets.iter().zip(&inputs.assets) {
+        let Some(appended) = asset.input.appended(csv) else {
+            return Ok(Err(format!("{} history changed", path.display())));
+        };
+        let ser =
+            parse_appended(csv, appended).with_context(|| format!("read {}", path.display()))?;
+        if asset.skipped && asset.input.rows + ser.dates().len() >= min_required_days {
+            return Ok(Err(format!("{name} now has enough history")));
+        }
+        if !asset.skipped && !out.join(format!("signals_{name}.csv")).is_file() {
+            return Ok(Err(format!("signals_{name}.csv is missing")));
+        }
+        series.push(ser);
+    }
+    if !out.join("regime.csv").is_file() {
+        return Ok(Err("regime.csv is missing".to_string()));
+    }
+
+    // A bar dated on or before the last processed date rewrites history
+    let late = std::iter::once(("BTC", &btc))
+        .chain(
+            state
+                .assets
+                .iter()
+                .zip(&series)
+                .filter(|(a, _)| !a.skipped)
+                .map(|(a, s)| (a.name.as_str(), s)),
+        )
+        .find(|(_, s)| {
+            s.dates()
+                .iter()
+                .any(|d| state.last_date.is_some_and(|last| *d <= last))
+        });
+    if let Some((name, _)) = late {
+        return Ok(Err(format!(
+            "{name} has a new bar inside processed history"
+        )));
+    }
+
+    let batch = state.advance(&btc, &series, args)?;
+    state.btc.advance(btc_csv, btc.dates().len());
+    for ((asset, (_, _, csv)), ser) in state.assets.iter_mut().zip(&inputs.assets).zip(&series) {
+        asset.input.advance(csv, ser.dates().len());
+    }
+    for (name, signals) in &batch.signals {
+        strategy::append_signals_csv(&out.join(format!("signals_{name}.csv")), signals)?;
+    }
+    if !batch.dates.is_empty() {
+        let regimes = state.classify_latest(batch.dates.len(), &RegimeConfig::from_args(args)?);
+        regime::append_regimes(&out.join("regime.csv"), &regimes)?;
+    }
+    let skipped = state
+        .assets
+        .iter()
+        .filter(|a| a.skipped)
+        .map(|a| (a.name.clone(), a.input.rows))
+        .collect();
+    let update = Update {
+        recomputed: None,
+        dates: batch.dates,
+        assets: state.assets.iter().filter(|a| !a.skipped).count(),
+        skipped,
+    };
+    Ok(Ok((state, update)))
+}
+
+/// Bring `signals_<asset>.csv` and `regime.csv` in `args.out` up to date with the input CSVs,
+/// appending rows for new bars from saved indicator state when history is unchanged and
+/// recomputing everything otherwise. Backtest outputs (equity curve, trades, report) are not written.
+///
+/// # Errors
+/// Returns an error if an input CSV cannot be read or parsed, there is not enough data, or
+/// the output files cannot be written.
+pub fn update(args: &StrategyArgs) -> Result<Update> {
+    let out = args.out.as_ref().required("out")?;
+    let assets = args.assets.as_ref().required("assets")?;
+    let settings = settings(args, assets);
+    let inputs = Inputs::read(args, assets)?;
+    let state_path = out.join(STATE_FILE);
+
+    let saved = fs::read(&state_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<EngineState>(&bytes).ok());
+    let reason = match saved {
+        None => "no saved state".to_string(),
+        Some(state) if state.settings != settings => "settings or asset list changed".to_string(),
+        Some(state) => match resume(state, args, &inputs, out)? {
+            Ok((state, update)) => {
+                state.save(&state_path)?;
+                return Ok(update);
+            }
+            Err(reason) => reason,
+        },
+    };
+    let (state, mut update) = recompute(args, settings, &inputs, out)?;
+    state.save(&state_path)?;
+    update.recomputed = Some(reason);
+    Ok(update)
+}
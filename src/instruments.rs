@@ -0,0 +1,155 @@
+//! Exchange trading rules per asset: quantity step, price tick, minimum quantity and minimum
+//! notional. Read from a local JSON file (`--instruments`) keyed by asset name; assets without
+//! an entry trade in unrounded fractional quantities and prices.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Context, Error, Result};
+
+/// Trading rules of one asset; a missing rule does not constrain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Instrument {
+    /// Quantities are whole multiples of this (lot size)
+    pub step_size: Option<f64>,
+    /// Prices are whole multiples of this
+    pub tick_size: Option<f64>,
+    pub min_qty: Option<f64>,
+    /// Minimum order value (quantity × price)
+    pub min_notional: Option<f64>,
+}
+
+/// Decimal places of `step`, so that multiples of it print and compare exactly.
+fn decimals(step: f64) -> i32 {
+    let mut d = 0;
+    while d < 12 && ((step * 10f64.powi(d)).round() - step * 10f64.powi(d)).abs() > 1e-9 {
+        d += 1;
+    }
+    d
+}
+
+/// Direction a value moves onto the step grid.
+#[derive(Debug, Clone, Copy)]
+enum Snap {
+    Down,
+    Up,
+    Nearest,
+}
+
+impl Snap {
+    /// Steps in `x`, snapped. Floor and ceil allow a tiny tolerance so that exact multiples
+    /// do not move a whole step through float error; rounding needs none.
+    fn steps(self, x: f64) -> f64 {
+        match self {
+            Self::Down => (x + 1e-9).floor(),
+            Self::Up => (x - 1e-9).ceil(),
+            Self::Nearest => x.round(),
+        }
+    }
+}
+
+/// Multiple of `step` reached from `x` by `snap`.
+fn to_step(x: f64, step: Option<f64>, snap: Snap) -> f64 {
+    match step.filter(|s| *s > 0.0) {
+        Some(step) => {
+            let n = snap.steps(x / step);
+            let scale = 10f64.powi(decimals(step));
+            (n * step * scale).round() / scale
+        }
+        None => x,
+    }
+}
+
+impl Instrument {
+    /// Largest valid quantity not above `quantity`, or 0 when that is below the minimum
+    /// quantity or its value at `price` below the minimum notional.
+    #[must_use]
+    pub fn quantity(&self, quantity: f64, price: f64) -> f64 {
+        let q = to_step(quantity.max(0.0), self.step_size, Snap::Down);
+        if self.below_minimum(q, price) { 0.0 } else { q }
+    }
+
+    /// Whether a non-zero `quantity` at `price` is too small to be accepted.
+    #[must_use]
+    pub fn below_minimum(&self, quantity: f64, price: f64) -> bool {
+        self.min_qty.is_some_and(|m| quantity < m)
+            || self.min_notional.is_some_and(|m| quantity * price < m)
+    }
+
+    /// `quantity` snapped to the nearest step, e.g. a difference of two valid quantities.
+    #[must_use]
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        to_step(quantity, self.step_size, Snap::Nearest)
+    }
+
+    /// `quantity` for display: the step's decimals, or up to 8 without a step.
+    #[must_use]
+    pub fn format_quantity(&self, quantity: f64) -> String {
+        match self.step_size {
+            Some(step) => format!(
+                "{quantity:.*}",
+                usize::try_from(decimals(step)).unwrap_or(0)
+            ),
+            None => {
+                let s = format!("{quantity:.8}");
+                s.trim_end_matches('0').trim_end_matches('.').to_string()
+            }
+        }
+    }
+
+    /// Highest valid price at or below `price` (long stops and buy limits).
+    #[must_use]
+    pub fn price_down(&self, price: f64) -> f64 {
+        to_step(price, self.tick_size, Snap::Down)
+    }
+
+    /// Lowest valid price at or above `price` (sell limits such as profit targets).
+    #[must_use]
+    pub fn price_up(&self, price: f64) -> f64 {
+        to_step(price, self.tick_size, Snap::Up)
+    }
+}
+
+/// Trading rules by asset name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Instruments(pub BTreeMap<String, Instrument>);
+
+impl Instruments {
+    /// Read an instruments file, e.g.
+    /// `{"BTC": {"step_size": 0.00001, "tick_size": 0.01, "min_qty": 0.00001, "min_notional": 5}}`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, or a rule is not positive.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let instruments: Self = serde_json::from_str(&text)
+            .map_err(|e| Error::config(format!("instruments file {}: {e}", path.display())))?;
+        for (asset, i) in &instruments.0 {
+            let rules = [i.step_size, i.tick_size, i.min_qty, i.min_notional];
+            if rules.iter().flatten().any(|v| !(v.is_finite() && *v > 0.0)) {
+                return Err(Error::config(format!(
+                    "instrument {asset}: step, tick and minimums must be positive"
+                )));
+            }
+        }
+        Ok(instruments)
+    }
+
+    /// The `--instruments` file, or no constraints without one.
+    ///
+    /// # Errors
+    /// Returns an error if the file is invalid.
+    pub fn from_path(path: Option<&Path>) -> Result<Self> {
+        path.map_or_else(|| Ok(Self::default()), Self::load)
+    }
+
+    /// Rules of `asset`; unconstrained if it has no entry.
+    #[must_use]
+    pub fn get(&self, asset: &str) -> Instrument {
+        self.0.get(asset).copied().unwrap_or_default()
+    }
+}
@@ -4,18 +4,100 @@
 //! AI-powered insights, and automated execution to maximize profit through advanced signal generation, 
 //! risk management, and portfolio optimization.
 
+pub mod account;
 pub mod ai_insights;
 pub mod analyzer;
+pub mod attribution;
+pub mod benchmark;
+pub mod config;
 pub mod daemon;
+pub mod engine;
+pub mod error;
+pub mod incremental;
+pub mod instruments;
+pub mod metrics;
 pub mod ohlc;
+pub mod overfit;
+pub mod positions;
+pub mod regime;
+pub mod report;
+pub mod risk;
+pub mod robustness;
+pub mod runs;
 pub mod strategy;
+pub mod sweep;
 pub mod trade;
+pub mod walkforward;
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// How raw signal weights are produced
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignalMode {
+    /// Score each asset independently against trend/momentum/RS thresholds
+    #[default]
+    Threshold,
+    /// Rank the universe each rebalance and hold the top K (optionally short the bottom K)
+    CrossSectional,
+}
+
+/// Ranking metric for cross-sectional mode
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RankBy {
+    /// N-day price return
+    #[default]
+    Return,
+    /// OLS slope of ln(RS vs BTC) over N days
+    RsSlope,
+}
+
+/// How the event-driven simulation enters after a signal at the close
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionStyle {
+    /// Market-on-close on the signal day
+    #[default]
+    MarketOnClose,
+    /// Market order at the next bar's open
+    NextOpen,
+    /// Limit at the MA30 pullback price when the signal close is extended, else market-on-close
+    Pullback,
+}
+
+impl ExecutionStyle {
+    pub const ALL: [Self; 3] = [Self::MarketOnClose, Self::NextOpen, Self::Pullback];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::MarketOnClose => "market-on-close",
+            Self::NextOpen => "next-open",
+            Self::Pullback => "pullback",
+        }
+    }
+}
+
+/// Objective used to pick parameters on a training window
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Objective {
+    /// Annualized Sharpe ratio
+    #[default]
+    Sharpe,
+    /// Compound annual growth rate
+    Cagr,
+    /// CAGR divided by max drawdown
+    Calmar,
+    /// Total return over the window
+    TotalReturn,
+}
+
 /// CLI args
-#[derive(Parser, Debug, Clone, Default)]
+#[derive(Parser, Serialize, Deserialize, Debug, Clone, Default)]
 #[command(
     version,
     about = "CoinGecko OHLC CSV exporter (top-N by mcap) with resume + simple scheduler"
@@ -73,10 +155,14 @@ pub struct OhlcArgs {
     /// Skip pulling BTC baseline (useful if you run it separately)
     #[arg(long)]
     pub skip_btc: Option<bool>,
+
+    /// Also fetch daily quote-currency volume (one extra request per 180-day chunk)
+    #[arg(long)]
+    pub volume: Option<bool>,
 }
 
 /// Backtests a relative-strength + trend strategy over daily OHLCV CSVs.
-#[derive(Parser, Debug, Clone, Default)]
+#[derive(Parser, Serialize, Deserialize, Debug, Clone, Default)]
 #[command(version, about)]
 pub struct StrategyArgs {
     /// Path to BTC CSV (used for relative strength baseline)
@@ -116,4 +202,279 @@ pub struct StrategyArgs {
     /// Vol-based stop (if no H/L): k * rolling std of daily returns
     #[arg(long)]
     pub vol_mult: Option<f64>,
+
+    /// Liquidity cap: max position as a fraction of trailing average daily dollar volume (0 disables)
+    #[arg(long)]
+    pub max_adv_fraction: Option<f64>,
+    /// Lookback (days) for average daily dollar volume
+    #[arg(long)]
+    pub adv_lookback: Option<usize>,
+    /// Starting capital used to turn the liquidity cap into a portfolio weight
+    #[arg(long)]
+    pub capital: Option<f64>,
+
+    /// Scale gross long exposure by the market regime (trend, breadth, volatility, dispersion)
+    #[arg(long)]
+    pub regime_scaling: Option<bool>,
+    /// Window (days) for the regime's BTC realized volatility and return dispersion
+    #[arg(long)]
+    pub regime_vol_lookback: Option<usize>,
+    /// Annualized BTC realized volatility above which the market is stressed
+    #[arg(long)]
+    pub regime_high_vol: Option<f64>,
+    /// Share of the universe above its long MA for a bullish breadth vote
+    #[arg(long)]
+    pub regime_breadth_bull: Option<f64>,
+    /// Share of the universe above its long MA below which breadth votes bearish
+    #[arg(long)]
+    pub regime_breadth_bear: Option<f64>,
+    /// Gross long exposure multiplier in a neutral regime
+    #[arg(long)]
+    pub regime_neutral_exposure: Option<f64>,
+    /// Gross long exposure multiplier in a risk-off regime
+    #[arg(long)]
+    pub regime_risk_off_exposure: Option<f64>,
+    /// Gross long exposure multiplier in a crisis
+    #[arg(long)]
+    pub regime_crisis_exposure: Option<f64>,
+
+    /// Signal mode: independent thresholds or cross-sectional ranking
+    #[arg(long, value_enum)]
+    pub mode: Option<SignalMode>,
+    /// Cross-sectional ranking metric
+    #[arg(long, value_enum)]
+    pub rank_by: Option<RankBy>,
+    /// Cross-sectional lookback (days) for the ranking metric
+    #[arg(long)]
+    pub rank_lookback: Option<usize>,
+    /// Skip the most recent N days when ranking (short-term reversal buffer)
+    #[arg(long)]
+    pub rank_skip: Option<usize>,
+    /// Number of assets to hold long (and short, if enabled)
+    #[arg(long)]
+    pub top_k: Option<usize>,
+    /// Also short the bottom K assets in cross-sectional mode
+    #[arg(long)]
+    pub short_bottom: Option<bool>,
+    /// Days between cross-sectional rebalances
+    #[arg(long)]
+    pub rebalance_days: Option<usize>,
+
+    /// Forward-fill data gaps up to this many days; longer gaps make the asset untradeable
+    #[arg(long)]
+    pub max_fill_days: Option<usize>,
+
+    /// Extra benchmark CSV (date, close) reported next to BTC and the equal-weight universe
+    #[arg(long)]
+    pub benchmark: Option<PathBuf>,
+
+    /// Run registry directory; each run's args, input hashes and outputs are archived there
+    #[arg(long)]
+    pub runs_dir: Option<PathBuf>,
+    /// Archive this run in the run registry
+    #[arg(long)]
+    pub record: Option<bool>,
+}
+
+/// Grid-searches strategy parameters over a shared, once-loaded data set.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(version, about)]
+pub struct SweepArgs {
+    #[command(flatten)]
+    pub base: StrategyArgs,
+
+    /// Parameter grid (repeatable): `name=start:end:step` or `name=v1,v2,...`
+    #[arg(long, value_name = "NAME=VALUES")]
+    pub grid: Vec<String>,
+    /// Number of best parameter sets to print
+    #[arg(long)]
+    pub top: Option<usize>,
+}
+
+/// Picks parameters on rolling or anchored training windows and applies them out of sample.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(version, about)]
+pub struct WalkForwardArgs {
+    #[command(flatten)]
+    pub base: StrategyArgs,
+
+    /// Parameter grid (repeatable): `name=start:end:step` or `name=v1,v2,...`
+    #[arg(long, value_name = "NAME=VALUES")]
+    pub grid: Vec<String>,
+    /// Training window length (days)
+    #[arg(long)]
+    pub train_days: Option<usize>,
+    /// Test window length (days); windows roll forward by this much
+    #[arg(long)]
+    pub test_days: Option<usize>,
+    /// Anchor every training window at the first date instead of rolling it
+    #[arg(long)]
+    pub anchored: Option<bool>,
+    /// Objective used to select parameters on each training window
+    #[arg(long, value_enum)]
+    pub objective: Option<Objective>,
+}
+
+/// Monte Carlo and bootstrap robustness analysis of a strategy backtest.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(version, about)]
+pub struct RobustnessArgs {
+    #[command(flatten)]
+    pub base: StrategyArgs,
+
+    /// Simulations per resampling method
+    #[arg(long)]
+    pub simulations: Option<usize>,
+    /// Mean block length (days) for the stationary bootstrap
+    #[arg(long)]
+    pub block_length: Option<f64>,
+    /// Minimum days kept when subsampling random start dates
+    #[arg(long)]
+    pub min_days: Option<usize>,
+    /// Two-sided confidence level for the intervals
+    #[arg(long)]
+    pub confidence: Option<f64>,
+    /// Loss (fraction) for the probability-of-loss and drawdown estimates
+    #[arg(long)]
+    pub loss_threshold: Option<f64>,
+    /// RNG seed for reproducible runs
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Replays the strategy's signals through the event-driven order simulator.
+#[derive(Parser, Serialize, Deserialize, Debug, Clone, Default)]
+#[command(version, about)]
+pub struct SimulateArgs {
+    #[command(flatten)]
+    pub base: StrategyArgs,
+
+    /// Entry execution to simulate; every style is compared when omitted
+    #[arg(long)]
+    pub execution: Option<ExecutionStyle>,
+    /// Pullback entries: the signal close must be this far above MA30 (fraction) to use a limit
+    #[arg(long)]
+    pub extended_threshold: Option<f64>,
+    /// Hours a pullback limit order rests before it expires
+    #[arg(long)]
+    pub limit_hours: Option<u32>,
+    /// Scale out half the position at this multiple of the initial risk R (0 disables)
+    #[arg(long)]
+    pub profit_target_r: Option<f64>,
+}
+
+/// Builds top-10 trading playbooks from a signals directory.
+#[derive(Parser, Serialize, Deserialize, Debug, Clone, Default)]
+#[command(version, about)]
+pub struct TradeArgs {
+    /// Signals directory to generate playbooks from
+    #[arg(long)]
+    pub signals_dir: Option<String>,
+    /// Output JSON file for playbooks
+    #[arg(long)]
+    pub output_json: Option<String>,
+    /// Cap each position at this fraction of trailing average daily dollar volume
+    #[arg(long)]
+    pub max_adv_fraction: Option<f64>,
+    /// Portfolio value used for position sizing when no account file is given
+    #[arg(long)]
+    pub portfolio_value: Option<f64>,
+    /// Account file (JSON: currency, cash, positions) to size against instead of an
+    /// all-cash `--portfolio-value`
+    #[arg(long)]
+    pub account: Option<PathBuf>,
+    /// Currency of an all-cash `--portfolio-value` account
+    #[arg(long)]
+    pub currency: Option<String>,
+    /// Instruments file (JSON by asset: step_size, tick_size, min_qty, min_notional) that
+    /// sizes and prices are rounded to; assets without an entry are not rounded
+    #[arg(long)]
+    pub instruments: Option<PathBuf>,
+    /// Initial stop distance in ATR(14) multiples
+    #[arg(long)]
+    pub stop_atr_mult: Option<f64>,
+    /// Scale-out target in multiples of the initial risk R
+    #[arg(long)]
+    pub profit_target_r: Option<f64>,
+    /// Extension above MA30 that switches a good asset to the pullback entry; stable assets
+    /// get 1.5x this, volatile ones 0.5x
+    #[arg(long)]
+    pub extended_threshold: Option<f64>,
+}
+
+/// Runs the fetch → signals → analysis → playbook cycle once or on a schedule.
+#[derive(Parser, Serialize, Deserialize, Debug, Clone, Default)]
+#[command(version, about)]
+pub struct DaemonArgs {
+    /// Run continuously and generate signals on every check interval
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub continuous: Option<bool>,
+    /// Portfolio value for position sizing when no account file is given
+    #[arg(long)]
+    pub portfolio_value: Option<f64>,
+    /// Account file (JSON: currency, cash, positions), re-read every cycle
+    #[arg(long)]
+    pub account: Option<PathBuf>,
+    /// Instruments file (JSON: lot, tick and minimums by asset), re-read every cycle
+    #[arg(long)]
+    pub instruments: Option<PathBuf>,
+    /// Position book file (JSON) of entries, trailing stops and scale-outs, updated every cycle
+    #[arg(long)]
+    pub position_book: Option<PathBuf>,
+    /// Risk cap per position (% of portfolio)
+    #[arg(long)]
+    pub risk_cap_percent: Option<f64>,
+    /// Check interval in minutes
+    #[arg(long)]
+    pub check_interval: Option<u64>,
+    /// Cap each position at this fraction of trailing average daily dollar volume
+    #[arg(long)]
+    pub max_adv_fraction: Option<f64>,
+    /// Total portfolio heat cap (% of portfolio); all positions scale down when exceeded
+    #[arg(long)]
+    pub max_portfolio_heat: Option<f64>,
+    /// Lookback (days) for the return correlation/covariance matrix
+    #[arg(long)]
+    pub correlation_lookback: Option<usize>,
+    /// Days of OHLC history re-fetched each cycle
+    #[arg(long)]
+    pub fetch_days: Option<i64>,
+}
+
+/// Action on the layered configuration
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print every resolved value and the layer it came from
+    Show,
+}
+
+/// Action on the run registry
+#[derive(Subcommand, Debug, Clone)]
+pub enum RunsCommand {
+    /// List recorded runs, oldest first
+    List,
+    /// Show a run's metadata, input integrity, parameters and metrics
+    Show {
+        /// Run id or unique prefix
+        id: String,
+    },
+    /// Compare code, inputs, parameters and metrics of two runs
+    Diff {
+        /// Run id or unique prefix
+        a: String,
+        /// Run id or unique prefix
+        b: String,
+    },
+}
+
+/// Browse and compare archived strategy runs.
+#[derive(Parser, Debug, Clone)]
+#[command(version, about)]
+pub struct RunsArgs {
+    /// Run registry directory
+    #[arg(long)]
+    pub runs_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: RunsCommand,
 }
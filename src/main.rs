@@ -1,7 +1,13 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use crypto_momentum_ai::{OhlcArgs, StrategyArgs, analyzer, daemon, ohlc, strategy, trade};
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::error::Required;
+use crypto_momentum_ai::{
+    ConfigCommand, DaemonArgs, OhlcArgs, RobustnessArgs, RunsArgs, RunsCommand, SimulateArgs,
+    StrategyArgs, SweepArgs, TradeArgs, WalkForwardArgs, analyzer, daemon, engine, ohlc, report,
+    robustness, runs, strategy, sweep, trade, walkforward,
+};
 
 use clap::{Parser, Subcommand};
 use tracing_subscriber::EnvFilter;
@@ -9,6 +15,13 @@ use tracing_subscriber::EnvFilter;
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
+    /// Config file (TOML); defaults to `CMAI_CONFIG` or ./crypto-momentum.toml if present
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Config profile (`[profiles.<name>]`) layered over the file; defaults to `CMAI_PROFILE`
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -17,6 +30,16 @@ struct Args {
 enum Command {
     Ohlc(OhlcArgs),
     Strategy(StrategyArgs),
+    /// Grid-search strategy parameters over a shared data set
+    Sweep(SweepArgs),
+    /// Walk-forward parameter selection with out-of-sample reporting
+    WalkForward(WalkForwardArgs),
+    /// Bootstrap, trade-shuffle and random-start confidence intervals for the backtest
+    Robustness(RobustnessArgs),
+    /// Event-driven simulation of playbook order execution (entries, limits, stops)
+    Simulate(SimulateArgs),
+    /// List, inspect and compare archived strategy runs
+    Runs(RunsArgs),
     Analyze {
         /// Signals directory to analyze
         #[arg(long, default_value = "./out/signals")]
@@ -25,27 +48,20 @@ enum Command {
         #[arg(long)]
         detailed: Option<String>,
     },
-    Trade {
-        /// Signals directory to generate playbooks from
+    /// Rebuild the HTML backtest report of a stored strategy run
+    Report {
+        /// Strategy output directory (equity_curve.csv, benchmarks.csv, ...)
         #[arg(long, default_value = "./out/signals")]
-        signals_dir: String,
-        /// Output JSON file for playbooks
-        #[arg(long)]
-        output_json: Option<String>,
+        run_dir: String,
     },
-    Daemon {
-        /// Run continuously and generate signals daily
-        #[arg(long, default_value = "false")]
-        continuous: bool,
-        /// Portfolio value for position sizing
-        #[arg(long, default_value = "100000")]
-        portfolio_value: f64,
-        /// Risk cap per position (% of portfolio)
-        #[arg(long, default_value = "1.0")]
-        risk_cap_percent: f64,
-        /// Check interval in minutes (default: 60)
-        #[arg(long, default_value = "60")]
-        check_interval: u64,
+    /// Build top-10 trading playbooks from a signals directory
+    Trade(TradeArgs),
+    /// Fetch, backtest, analyze and size positions once or on a schedule
+    Daemon(DaemonArgs),
+    /// Inspect the layered configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
     },
     DeploySystemd {
         /// Portfolio value for position sizing
@@ -76,67 +92,19 @@ enum Command {
     },
 }
 
-fn apply_ohlc_defaults(args: &mut OhlcArgs) {
-    if args.out.is_none() {
-        args.out = Some(PathBuf::from("./out"));
-    }
-    if args.top_n.is_none() {
-        args.top_n = Some(100);
-    }
-    if args.vs.is_none() {
-        args.vs = Some("usd".to_string());
-    }
-    if args.concurrency.is_none() {
-        args.concurrency = Some(6);
-    }
-    if args.request_delay_ms.is_none() {
-        args.request_delay_ms = Some(250);
-    }
-    if args.write_manifest.is_none() {
-        args.write_manifest = Some(true);
-    }
-    if args.resume.is_none() {
-        args.resume = Some(false);
-    }
-    if args.skip_btc.is_none() {
-        args.skip_btc = Some(false);
-    }
-}
-
-fn apply_strategy_defaults(args: &mut StrategyArgs) {
-    if args.btc.is_none() {
-        args.btc = Some(PathBuf::from("./out/BTC.csv"));
-    }
-    if args.assets.is_none() {
-        args.assets = Some(get_files_in_directory(&PathBuf::from("./out")).unwrap());
-    }
-    if args.out.is_none() {
-        args.out = Some(PathBuf::from("./out/signals"));
-    }
-    if args.ma_short.is_none() {
-        args.ma_short = Some(3);
-    }
-    if args.ma_long.is_none() {
-        args.ma_long = Some(7);
-    }
-    if args.min_signals.is_none() {
-        args.min_signals = Some(2);
-    }
-    if args.short_alts.is_none() {
-        args.short_alts = Some(false);
-    }
-    if args.btc_hedge.is_none() {
-        args.btc_hedge = Some(0.3);
-    }
-    if args.stop_lookback.is_none() {
-        args.stop_lookback = Some(14);
-    }
-    if args.atr_mult.is_none() {
-        args.atr_mult = Some(3.0);
-    }
-    if args.vol_mult.is_none() {
-        args.vol_mult = Some(2.5);
-    }
+/// Strategy args from the config layers with `cli` on top; assets default to every CSV next
+/// to the BTC baseline.
+fn resolve_strategy(config: &Resolved, cli: &StrategyArgs) -> Result<StrategyArgs> {
+    let mut args = config.with_cli("strategy", cli)?;
+    if args.assets.as_ref().is_none_or(Vec::is_empty) {
+        let data_dir = args
+            .btc
+            .as_ref()
+            .and_then(|b| b.parent())
+            .map_or_else(|| PathBuf::from("./out"), PathBuf::from);
+        args.assets = Some(strategy::asset_files(&data_dir)?);
+    }
+    Ok(args)
 }
 
 #[tokio::main]
@@ -149,45 +117,82 @@ async fn main() -> Result<()> {
         .init();
 
     let args = Args::parse();
+    let config = Resolved::load(args.config.as_deref(), args.profile.as_deref())?;
     match args.command {
-        Some(Command::Ohlc(mut ohlc_args)) => {
-            apply_ohlc_defaults(&mut ohlc_args);
+        Some(Command::Ohlc(ohlc_args)) => {
+            let ohlc_args: OhlcArgs = config.with_cli("ohlc", &ohlc_args)?;
             ohlc::execute(&ohlc_args).await?;
         }
-        Some(Command::Strategy(mut strategy_args)) => {
-            apply_strategy_defaults(&mut strategy_args);
-            if strategy_args.assets.as_ref().unwrap().is_empty() {
-                let out_dir = strategy_args.out.as_ref().unwrap();
-                let files = get_files_in_directory(out_dir)?;
-                strategy_args.assets = Some(files);
-            }
+        Some(Command::Strategy(strategy_args)) => {
+            let strategy_args = resolve_strategy(&config, &strategy_args)?;
             strategy::execute(&strategy_args)?;
         }
+        Some(Command::Sweep(mut sweep_args)) => {
+            if sweep_args.base.out.is_none() {
+                sweep_args.base.out = Some(PathBuf::from("./out/sweep"));
+            }
+            sweep_args.base = resolve_strategy(&config, &sweep_args.base)?;
+            sweep::execute(&sweep_args)?;
+        }
+        Some(Command::WalkForward(mut wf_args)) => {
+            if wf_args.base.out.is_none() {
+                wf_args.base.out = Some(PathBuf::from("./out/walkforward"));
+            }
+            wf_args.base = resolve_strategy(&config, &wf_args.base)?;
+            walkforward::execute(&wf_args)?;
+        }
+        Some(Command::Robustness(mut robustness_args)) => {
+            if robustness_args.base.out.is_none() {
+                robustness_args.base.out = Some(PathBuf::from("./out/robustness"));
+            }
+            robustness_args.base = resolve_strategy(&config, &robustness_args.base)?;
+            robustness::execute(&robustness_args)?;
+        }
+        Some(Command::Simulate(mut sim_args)) => {
+            if sim_args.base.out.is_none() {
+                sim_args.base.out = Some(PathBuf::from("./out/simulate"));
+            }
+            sim_args.base = resolve_strategy(&config, &sim_args.base)?;
+            // Pullback and scale-out rules are shared with the resolved `trade` section
+            let trade = &config.config.trade;
+            sim_args.extended_threshold = sim_args.extended_threshold.or(trade.extended_threshold);
+            sim_args.profit_target_r = sim_args.profit_target_r.or(trade.profit_target_r);
+            sim_args.limit_hours = sim_args.limit_hours.or(Some(engine::DEFAULT_LIMIT_HOURS));
+            engine::execute(&sim_args)?;
+        }
+        Some(Command::Runs(runs_args)) => {
+            let runs_dir = runs_args
+                .runs_dir
+                .unwrap_or_else(|| PathBuf::from("./out/runs"));
+            match runs_args.command {
+                RunsCommand::List => runs::list(&runs_dir)?,
+                RunsCommand::Show { id } => runs::show(&runs_dir, &id)?,
+                RunsCommand::Diff { a, b } => runs::diff(&runs_dir, &a, &b)?,
+            }
+        }
         Some(Command::Analyze {
             signals_dir,
             detailed,
         }) => {
             analyzer::execute(&signals_dir, detailed.as_deref())?;
         }
-        Some(Command::Trade {
-            signals_dir,
-            output_json,
-        }) => {
-            trade::execute(&signals_dir, output_json.as_deref()).await?;
+        Some(Command::Report { run_dir }) => {
+            report::execute(&run_dir)?;
         }
-        Some(Command::Daemon {
-            continuous,
-            portfolio_value,
-            risk_cap_percent,
-            check_interval,
+        Some(Command::Trade(trade_args)) => {
+            let trade_args: TradeArgs = config.with_cli("trade", &trade_args)?;
+            trade::execute(&trade_args, &config.config.ai).await?;
+        }
+        Some(Command::Daemon(daemon_args)) => {
+            let mut resolved = config.config.clone();
+            resolved.daemon = config.with_cli("daemon", &daemon_args)?;
+            resolved.strategy = resolve_strategy(&config, &StrategyArgs::default())?;
+            daemon::execute(&resolved).await?;
+        }
+        Some(Command::Config {
+            command: ConfigCommand::Show,
         }) => {
-            daemon::execute(
-                continuous,
-                portfolio_value,
-                risk_cap_percent,
-                check_interval,
-            )
-            .await?;
+            config.show();
         }
         Some(Command::DeploySystemd {
             portfolio_value,
@@ -210,44 +215,24 @@ async fn main() -> Result<()> {
             // Default behavior: run OHLC, strategy, and analyze with defaults
             println!("Running with default arguments...");
             println!("1. Fetching OHLC data...");
-            let mut ohlc_args = OhlcArgs::default();
-            apply_ohlc_defaults(&mut ohlc_args);
-            ohlc::execute(&ohlc_args).await?;
+            ohlc::execute(&config.config.ohlc).await?;
 
             println!("2. Running strategy backtest...");
-            let mut strategy_args = StrategyArgs::default();
-            apply_strategy_defaults(&mut strategy_args);
+            let strategy_args = resolve_strategy(&config, &StrategyArgs::default())?;
             strategy::execute(&strategy_args)?;
 
             println!("3. Analyzing profitable strategies...");
-            analyzer::execute("./out/signals", None)?;
+            let signals_dir = strategy_args.out.as_ref().required("out")?.display().to_string();
+            analyzer::execute(&signals_dir, None)?;
 
             println!("4. Generating top-10 trading playbooks...");
-            trade::execute("./out/signals", Some("./out/playbooks.json")).await?;
+            let trade_args = TradeArgs {
+                signals_dir: Some(signals_dir),
+                output_json: Some("./out/playbooks.json".to_string()),
+                ..config.config.trade.clone()
+            };
+            trade::execute(&trade_args, &config.config.ai).await?;
         }
     }
     Ok(())
 }
-
-fn get_files_in_directory(dir_path: &PathBuf) -> Result<Vec<PathBuf>, std::io::Error> {
-    let mut files = Vec::new();
-    let entries = std::fs::read_dir(dir_path)?;
-
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() && path.extension().unwrap_or_default() == "csv" {
-            // Filter out strategy output files
-            let filename = path.file_name().unwrap().to_string_lossy();
-            if !filename.starts_with("signals_")
-                && filename != "equity_curve.csv"
-                && filename != "metrics.txt"
-            {
-                files.push(path);
-            }
-        }
-    }
-
-    Ok(files)
-}
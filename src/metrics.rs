@@ -0,0 +1,321 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::overfit;
+
+/// Bars per year assumed when the bar interval cannot be inferred (daily, 24/7 markets)
+pub const DAILY_PERIODS_PER_YEAR: f64 = 365.25;
+
+/// Risk and performance statistics of one return series.
+///
+/// Every ratio is annualized with `periods_per_year`; drawdown durations are in bars.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metrics {
+    pub days: usize,
+    pub periods_per_year: f64,
+    pub total_return: f64,
+    pub cagr: f64,
+    pub volatility: f64,
+    pub sharpe: f64,
+    /// Mean return over downside deviation (target 0)
+    pub sortino: f64,
+    /// CAGR over max drawdown
+    pub calmar: f64,
+    /// Sum of gains over sum of losses (threshold 0)
+    pub omega: f64,
+    pub max_drawdown: f64,
+    /// Root mean square of the percentage drawdown
+    pub ulcer_index: f64,
+    /// Longest peak-to-new-high span, including a drawdown still open at the end
+    pub max_drawdown_duration: usize,
+    /// Bars from the deepest trough back to the previous peak; `None` if never recovered
+    pub recovery_time: Option<usize>,
+    /// Share of non-zero bars that were positive
+    pub win_rate: f64,
+    /// Share of bars with a position (non-zero exposure, or non-zero return without exposure)
+    pub time_in_market: f64,
+    /// Annualized one-way turnover (mean absolute weight change x periods per year)
+    pub turnover: f64,
+    /// Average gross exposure (sum of absolute weights incl. hedge)
+    pub exposure: f64,
+    /// Historical value at risk as a positive loss fraction
+    pub var_95: f64,
+    pub var_99: f64,
+    /// Expected shortfall: mean loss beyond the VaR
+    pub cvar_95: f64,
+    pub cvar_99: f64,
+    pub skew: f64,
+    pub excess_kurtosis: f64,
+    /// Compounded calendar-month returns (0 when no dates are supplied)
+    pub best_month: f64,
+    pub worst_month: f64,
+}
+
+/// Bars per year from the median spacing of `dates`; daily bars give 365.25.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn periods_per_year(dates: &[NaiveDate]) -> f64 {
+    let mut gaps: Vec<i64> = dates
+        .windows(2)
+        .map(|w| (w[1] - w[0]).num_days())
+        .filter(|d| *d > 0)
+        .collect();
+    if gaps.is_empty() {
+        return DAILY_PERIODS_PER_YEAR;
+    }
+    gaps.sort_unstable();
+    DAILY_PERIODS_PER_YEAR / gaps[gaps.len() / 2] as f64
+}
+
+/// Linear-interpolated percentile of sorted `x` (`q` in [0, 1]).
+#[must_use]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    let frac = pos - lo as f64;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * frac
+}
+
+fn mean(x: &[f64]) -> f64 {
+    if x.is_empty() {
+        0.0
+    } else {
+        x.iter().sum::<f64>() / x.len() as f64
+    }
+}
+
+/// Historical VaR and CVaR of `sorted` returns at `confidence`, as positive losses.
+fn tail_risk(sorted: &[f64], confidence: f64) -> (f64, f64) {
+    if sorted.is_empty() {
+        return (0.0, 0.0);
+    }
+    let cutoff = percentile(sorted, 1.0 - confidence);
+    let tail: Vec<f64> = sorted.iter().copied().take_while(|r| *r <= cutoff).collect();
+    (-cutoff, -mean(&tail))
+}
+
+/// Drawdown statistics of the equity curve implied by `rets` (starting capital 1.0):
+/// (max drawdown, ulcer index, max duration, recovery time of the deepest drawdown).
+#[allow(clippy::cast_precision_loss)]
+fn drawdowns(rets: &[f64]) -> (f64, f64, usize, Option<usize>) {
+    let mut equity = 1.0;
+    let mut peak = 1.0;
+    let mut peak_idx = 0;
+    let mut mdd = 0.0;
+    let mut mdd_trough_idx = 0;
+    let mut mdd_open = false;
+    let mut sum_sq = 0.0;
+    let mut longest = 0;
+    let mut recovery = None;
+    // Index 0 is the starting capital, index i the equity after rets[i - 1]
+    for (i, r) in rets.iter().enumerate().map(|(i, r)| (i + 1, r)) {
+        equity *= 1.0 + r;
+        if equity >= peak {
+            if mdd_open {
+                recovery = Some(i - mdd_trough_idx);
+                mdd_open = false;
+            }
+            longest = longest.max(i - peak_idx);
+            peak = equity;
+            peak_idx = i;
+            continue;
+        }
+        let dd = 1.0 - equity / peak;
+        sum_sq += dd * dd;
+        if dd > mdd {
+            mdd = dd;
+            mdd_trough_idx = i;
+            mdd_open = true;
+            recovery = None;
+        }
+    }
+    longest = longest.max(rets.len() - peak_idx);
+    let ulcer = if rets.is_empty() {
+        0.0
+    } else {
+        (sum_sq / rets.len() as f64).sqrt()
+    };
+    (mdd, ulcer, longest, recovery)
+}
+
+/// Compounded return per calendar month of `dates[i]`, in date order.
+fn monthly_returns(rets: &[f64], dates: &[NaiveDate]) -> Vec<f64> {
+    let mut months: BTreeMap<(i32, u32), f64> = BTreeMap::new();
+    for (r, d) in rets.iter().zip(dates) {
+        *months.entry((d.year(), d.month())).or_insert(1.0) *= 1.0 + r;
+    }
+    months.into_values().map(|g| g - 1.0).collect()
+}
+
+/// Compute the full metric suite for per-bar returns `rets`.
+///
+/// `dates` (same length as `rets`) only feed the monthly statistics and may be empty;
+/// `turnover` and `exposure` are per-bar series and may be empty as well.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn compute(
+    rets: &[f64],
+    dates: &[NaiveDate],
+    periods_per_year: f64,
+    turnover: &[f64],
+    exposure: &[f64],
+) -> Metrics {
+    let rets: Vec<f64> = rets
+        .iter()
+        .map(|r| if r.is_finite() { *r } else { 0.0 })
+        .collect();
+    let n = rets.len();
+    let ppy = if periods_per_year > 0.0 {
+        periods_per_year
+    } else {
+        DAILY_PERIODS_PER_YEAR
+    };
+
+    let growth: f64 = rets.iter().map(|r| 1.0 + r).product();
+    let total_return = growth - 1.0;
+    let years = n as f64 / ppy;
+    let cagr = if years > 0.0 && growth > 0.0 {
+        growth.powf(1.0 / years) - 1.0
+    } else {
+        0.0
+    };
+
+    let moments = overfit::moments(&rets);
+    let volatility = moments.std * ppy.sqrt();
+    let sharpe = if moments.std > 0.0 {
+        moments.mean / moments.std * ppy.sqrt()
+    } else {
+        0.0
+    };
+    let downside = if n > 0 {
+        (rets.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n as f64).sqrt()
+    } else {
+        0.0
+    };
+    let sortino = if downside > 0.0 {
+        moments.mean / downside * ppy.sqrt()
+    } else {
+        0.0
+    };
+
+    let gains: f64 = rets.iter().filter(|r| **r > 0.0).sum();
+    let losses: f64 = -rets.iter().filter(|r| **r < 0.0).sum::<f64>();
+    let omega = if losses > 0.0 {
+        gains / losses
+    } else if gains > 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    };
+
+    let (max_drawdown, ulcer_index, max_drawdown_duration, recovery_time) = drawdowns(&rets);
+    let calmar = if max_drawdown > 0.0 {
+        cagr / max_drawdown
+    } else {
+        0.0
+    };
+
+    let active = rets.iter().filter(|r| **r != 0.0).count();
+    let wins = rets.iter().filter(|r| **r > 0.0).count();
+    let win_rate = if active == 0 {
+        0.0
+    } else {
+        wins as f64 / active as f64
+    };
+    let time_in_market = if n == 0 {
+        0.0
+    } else if exposure.is_empty() {
+        active as f64 / n as f64
+    } else {
+        exposure.iter().filter(|e| e.abs() > 1e-9).count() as f64 / exposure.len() as f64
+    };
+
+    let mut sorted = rets.clone();
+    sorted.sort_by(f64::total_cmp);
+    let (var_95, cvar_95) = tail_risk(&sorted, 0.95);
+    let (var_99, cvar_99) = tail_risk(&sorted, 0.99);
+
+    let months = if dates.len() == n {
+        monthly_returns(&rets, dates)
+    } else {
+        Vec::new()
+    };
+
+    Metrics {
+        days: n,
+        periods_per_year: ppy,
+        total_return,
+        cagr,
+        volatility,
+        sharpe,
+        sortino,
+        calmar,
+        omega,
+        max_drawdown,
+        ulcer_index,
+        max_drawdown_duration,
+        recovery_time,
+        win_rate,
+        time_in_market,
+        turnover: mean(turnover) * ppy,
+        exposure: mean(exposure),
+        var_95,
+        var_99,
+        cvar_95,
+        cvar_99,
+        skew: moments.skew,
+        excess_kurtosis: if n < 2 { 0.0 } else { moments.kurtosis - 3.0 },
+        // No months (no dates) reports 0 for both
+        best_month: if months.is_empty() {
+            0.0
+        } else {
+            months.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        },
+        worst_month: if months.is_empty() {
+            0.0
+        } else {
+            months.iter().copied().fold(f64::INFINITY, f64::min)
+        },
+    }
+}
+
+/// Multi-line report of `m`, one `Label: value` per line.
+#[must_use]
+pub fn report(m: &Metrics) -> String {
+    format!(
+        "Total Return: {:.2}%\nCAGR: {:.2}%\nVolatility (ann.): {:.2}%\nSharpe (ann.): {:.2}\nSortino (ann.): {:.2}\nCalmar: {:.2}\nOmega: {:.2}\nMax Drawdown: {:.2}%\nUlcer Index: {:.2}\nMax Drawdown Duration: {} bars\nRecovery Time: {}\nWin Rate: {:.2}%\nTime in Market: {:.1}%\nVaR 95% / 99%: {:.2}% / {:.2}%\nCVaR 95% / 99%: {:.2}% / {:.2}%\nSkew: {:.2}\nExcess Kurtosis: {:.2}\nBest / Worst Month: {:.2}% / {:.2}%\nPeriods per Year: {:.2}\n",
+        m.total_return * 100.0,
+        m.cagr * 100.0,
+        m.volatility * 100.0,
+        m.sharpe,
+        m.sortino,
+        m.calmar,
+        m.omega,
+        m.max_drawdown * 100.0,
+        m.ulcer_index * 100.0,
+        m.max_drawdown_duration,
+        m.recovery_time
+            .map_or_else(|| "not recovered".to_string(), |d| format!("{d} bars")),
+        m.win_rate * 100.0,
+        m.time_in_market * 100.0,
+        m.var_95 * 100.0,
+        m.var_99 * 100.0,
+        m.cvar_95 * 100.0,
+        m.cvar_99 * 100.0,
+        m.skew,
+        m.excess_kurtosis,
+        m.best_month * 100.0,
+        m.worst_month * 100.0,
+        m.periods_per_year
+    )
+}
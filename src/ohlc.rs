@@ -1,10 +1,16 @@
-use anyhow::{Context, Result, bail};
-use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone};
 use csv::{ReaderBuilder, WriterBuilder};
 use itertools::Itertools;
-use reqwest::{Client, header};
+use reqwest::{Client, StatusCode, header};
 use serde::{Deserialize, Serialize};
-use std::{cmp::min, collections::HashSet, env, fs, io::Write, path::Path, time::Duration};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, HashSet},
+    env, fs,
+    io::Write,
+    path::Path,
+    time::Duration,
+};
 use tokio::time::sleep;
 use tracing::{error, info};
 
@@ -13,6 +19,7 @@ use std::fs::OpenOptions;
 use tempfile::NamedTempFile;
 
 use crate::OhlcArgs;
+use crate::error::{Context, Error, Recovery, Required, Result};
 
 /// Market coin
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -58,32 +65,38 @@ pub struct DailyBar {
     high: f64,
     low: f64,
     close: f64,
+    volume: Option<f64>,
 }
 
+/// Export daily OHLC CSVs for BTC and the top coins by market cap, once or every day at
+/// `--daily-at`.
+///
+/// # Errors
+/// Returns [`Error::Config`] for a missing API key or bad dates, and the first error of a
+/// run that cannot be recovered by waiting for the next one.
 pub async fn execute(args: &OhlcArgs) -> Result<()> {
     let api_key = match args.api_key.clone() {
         Some(key) => key,
-        None => env::var("CG_PRO_API_KEY").ok().unwrap(),
+        None => env::var("CG_PRO_API_KEY")
+            .map_err(|_| Error::config("no API key: pass --api-key or set CG_PRO_API_KEY"))?,
     };
 
-    let out_dir = args.out.as_ref().unwrap();
-    fs::create_dir_all(out_dir)
-        .context("create output dir")
-        .unwrap();
+    let out_dir = args.out.as_ref().required("out")?;
+    fs::create_dir_all(out_dir).context("create output dir")?;
 
     // Optional single-instance lock (covers daemon & cron)
     let _lock_guard = args
         .lock_file
         .as_ref()
-        .map(|lock_path| acquire_lock(lock_path).unwrap());
+        .map(|lock_path| acquire_lock(lock_path))
+        .transpose()?;
 
-    let client = mk_client(&api_key).unwrap();
+    let client = mk_client(&api_key)?;
 
     // Default end date to yesterday if not provided (to avoid "future date" API error)
     let end = if let Some(end_str) = &args.end {
         NaiveDate::parse_from_str(end_str, "%Y-%m-%d")
-            .context("invalid --end")
-            .unwrap()
+            .map_err(|e| Error::config(format!("invalid --end {end_str}: {e}")))?
     } else {
         chrono::Local::now().date_naive() - chrono::Duration::days(1)
     };
@@ -91,8 +104,7 @@ pub async fn execute(args: &OhlcArgs) -> Result<()> {
     // Default start date to 30 days ago if not provided
     let start = if let Some(start_str) = &args.start {
         NaiveDate::parse_from_str(start_str, "%Y-%m-%d")
-            .context("invalid --start")
-            .unwrap()
+            .map_err(|e| Error::config(format!("invalid --start {start_str}: {e}")))?
     } else {
         // Ensure start is at least 1 day before end
         let default_start = end - chrono::Duration::days(30);
@@ -105,19 +117,23 @@ pub async fn execute(args: &OhlcArgs) -> Result<()> {
 
     if let Some(hhmm) = args.daily_at.clone() {
         // Daemon mode: run now (once), then sleep until next HH:MM each day
-        let hhmm = parse_hhmm(&hhmm)
-            .context("invalid --daily-at (expected HH:MM)")
-            .unwrap();
+        let hhmm = parse_hhmm(&hhmm).context("invalid --daily-at (expected HH:MM)")?;
         loop {
-            run_once(&client, args, start, end).await.unwrap();
+            // A failed day is retried at the next scheduled run unless it needs an operator
+            if let Err(e) = run_once(&client, args, start, end).await {
+                if e.recovery() == Recovery::Abort {
+                    return Err(e);
+                }
+                error!("daily run failed ({}): {}", e.class(), e);
+            }
             // Sleep to next occurrence of hh:mm local time
-            let dur = duration_until_next_local(hhmm).unwrap();
+            let dur = duration_until_next_local(hhmm)?;
             info!("sleeping until next daily run: {}s", dur.as_secs());
             sleep(dur).await;
         }
     } else {
         // One-shot (use with cron/systemd/launchd)
-        run_once(&client, args, start, end).await.unwrap();
+        run_once(&client, args, start, end).await?;
     }
     // (unreachable in daemon loop)
     // lock guard drops here automatically
@@ -125,30 +141,30 @@ pub async fn execute(args: &OhlcArgs) -> Result<()> {
     Ok(())
 }
 
+/// Fetch and write every coin's CSV for `start..=end`.
+///
+/// # Errors
+/// Returns an error if the coin list or the BTC series cannot be fetched or written;
+/// failures for other coins are logged and skipped.
 pub async fn run_once(
     client: &Client,
     args: &OhlcArgs,
     start: NaiveDate,
     end: NaiveDate,
 ) -> Result<()> {
-    info!(
-        "starting run (resume={}, start={}, end={})",
-        args.resume.unwrap_or(false),
-        start,
-        end
-    );
-    let start_ts = Utc
-        .from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap())
-        .timestamp();
-    let end_ts = Utc
-        .from_utc_datetime(&end.and_hms_opt(23, 59, 59).unwrap())
-        .timestamp();
+    let resume = args.resume.required("resume")?;
+    let skip_btc = args.skip_btc.required("skip_btc")?;
+    let with_volume = args.volume.required("volume")?;
+    info!("starting run (resume={resume}, start={start}, end={end})");
+    let start_ts = start.and_time(NaiveTime::MIN).and_utc().timestamp();
+    // Last second of the end date
+    let end_ts = end.and_time(NaiveTime::MIN).and_utc().timestamp() + 86_399;
     if end_ts <= start_ts {
-        bail!("end must be after start");
+        return Err(Error::config("end must be after start"));
     }
 
     // Gather coins: always include BTC unless skipped
-    let mut coins: Vec<MarketCoin> = if args.skip_btc.unwrap_or(false) {
+    let mut coins: Vec<MarketCoin> = if skip_btc {
         vec![]
     } else {
         vec![MarketCoin {
@@ -159,8 +175,10 @@ pub async fn run_once(
         }]
     };
 
-    let vs = args.vs.as_ref().unwrap();
-    let top_n = args.top_n.unwrap();
+    let vs = args.vs.as_ref().required("vs")?;
+    let top_n = args.top_n.required("top_n")?;
+    let out_dir = args.out.as_ref().required("out")?;
+    let request_delay = args.request_delay_ms.required("request_delay_ms")?;
     let top = fetch_top_by_mcap(client, vs, top_n).await?;
     let _coins = coins.clone();
     let existing: HashSet<&str> = _coins.iter().map(|c| c.id.as_str()).collect();
@@ -169,8 +187,7 @@ pub async fn run_once(
             .filter(|c| !existing.contains(c.id.as_str())),
     );
 
-    if args.write_manifest.unwrap_or(true) {
-        let out_dir = args.out.as_ref().unwrap();
+    if args.write_manifest.required("write_manifest")? {
         fs::write(
             out_dir.join("manifest.json"),
             serde_json::to_string_pretty(&coins)?,
@@ -178,11 +195,8 @@ pub async fn run_once(
     }
 
     // BTC first (optional)
-    if !args.skip_btc.unwrap_or(false) {
-        let out_dir = args.out.as_ref().unwrap();
+    if !skip_btc {
         let path = out_dir.join("BTC.csv");
-        let request_delay = args.request_delay_ms.unwrap();
-        let resume = args.resume.unwrap_or(false);
         update_csv_for_coin(
             client,
             vs,
@@ -193,30 +207,33 @@ pub async fn run_once(
             end_ts,
             request_delay,
             resume,
+            with_volume,
         )
         .await?;
     }
 
     // Parallel fetch with bounded concurrency
     use tokio::sync::Semaphore;
-    let concurrency = args.concurrency.unwrap();
+    let concurrency = args.concurrency.required("concurrency")?;
     let sem = std::sync::Arc::new(Semaphore::new(concurrency));
     let mut tasks = vec![];
     for c in coins.into_iter().filter(|c| c.id != "bitcoin") {
-        let permit = sem.clone().acquire_owned().await.unwrap();
+        // The semaphore is never closed, so acquiring only waits for a free slot
+        let Ok(permit) = sem.clone().acquire_owned().await else {
+            break;
+        };
         let client = client.clone();
         let vs = vs.clone();
-        let out = args.out.as_ref().unwrap().clone();
+        let out = out_dir.clone();
         let sym = c.symbol.to_uppercase();
         let id = c.id.clone();
-        let delay = args.request_delay_ms.unwrap();
-        let resume = args.resume.unwrap_or(false);
+        let delay = request_delay;
 
         let task = tokio::spawn(async move {
             let _p = permit;
             let path = out.join(format!("{}_{}.csv", sym, id));
             if let Err(e) = update_csv_for_coin(
-                &client, &vs, &id, &sym, &path, start_ts, end_ts, delay, resume,
+                &client, &vs, &id, &sym, &path, start_ts, end_ts, delay, resume, with_volume,
             )
             .await
             {
@@ -234,6 +251,9 @@ pub async fn run_once(
 }
 
 /// Acquire an exclusive file lock; keep the file handle alive to hold the lock.
+///
+/// # Errors
+/// Returns an error if the lock file cannot be opened or locked.
 pub fn acquire_lock(lock_path: &Path) -> Result<std::fs::File> {
     fs::create_dir_all(lock_path.parent().unwrap_or(Path::new("."))).ok();
     let file = OpenOptions::new()
@@ -241,42 +261,56 @@ pub fn acquire_lock(lock_path: &Path) -> Result<std::fs::File> {
         .truncate(true)
         .read(true)
         .write(true)
-        .open(lock_path)?;
-    file.lock_exclusive()?;
+        .open(lock_path)
+        .with_context(|| format!("open lock file {}", lock_path.display()))?;
+    file.lock_exclusive()
+        .with_context(|| format!("lock {}", lock_path.display()))?;
     Ok(file)
 }
 
 /// Parse HH:MM to NaiveTime
+///
+/// # Errors
+/// Returns [`Error::Config`] if `s` is not a valid HH:MM time.
 pub fn parse_hhmm(s: &str) -> Result<NaiveTime> {
-    let parts: Vec<_> = s.split(':').collect();
-    if parts.len() != 2 {
-        bail!("bad time format");
-    }
-    let h: u32 = parts[0].parse()?;
-    let m: u32 = parts[1].parse()?;
-    NaiveTime::from_hms_opt(h, m, 0).context("invalid hh:mm")
+    let invalid = || Error::config(format!("invalid time {s}"));
+    let (h, m) = s.split_once(':').ok_or_else(invalid)?;
+    let h: u32 = h.parse().map_err(|_| invalid())?;
+    let m: u32 = m.parse().map_err(|_| invalid())?;
+    NaiveTime::from_hms_opt(h, m, 0).ok_or_else(invalid)
 }
 
 /// Duration until the next local occurrence of time `t`
+///
+/// # Errors
+/// Returns [`Error::Config`] if `t` does not exist on the target day (DST gap).
 pub fn duration_until_next_local(t: NaiveTime) -> Result<Duration> {
     use chrono::Local;
     let now_local = Local::now();
     let today_target = now_local.date_naive().and_time(t);
-    let next = if now_local.naive_local() < today_target {
-        Local.from_local_datetime(&today_target).unwrap()
+    let target = if now_local.naive_local() < today_target {
+        today_target
     } else {
         // tomorrow
-        let tomorrow = now_local.date_naive().succ_opt().unwrap();
-        Local.from_local_datetime(&tomorrow.and_time(t)).unwrap()
+        today_target + chrono::Duration::days(1)
     };
+    let next = Local
+        .from_local_datetime(&target)
+        .earliest()
+        .ok_or_else(|| Error::config(format!("{target} does not exist in local time")))?;
     let dur = next - now_local;
     Ok(Duration::from_millis(dur.num_milliseconds().max(0) as u64))
 }
 
 /// Make an HTTP client with Pro key header
+///
+/// # Errors
+/// Returns [`Error::Config`] if the key is not a valid header value.
 pub fn mk_client(api_key: &str) -> Result<Client> {
     let mut headers = header::HeaderMap::new();
-    headers.insert("x-cg-pro-api-key", header::HeaderValue::from_str(api_key)?);
+    let key = header::HeaderValue::from_str(api_key)
+        .map_err(|_| Error::config("API key contains characters not allowed in a header"))?;
+    headers.insert("x-cg-pro-api-key", key);
     let client = Client::builder()
         .default_headers(headers)
         .user_agent("cg_ohlc_exporter/0.2 (rust)")
@@ -288,6 +322,9 @@ pub fn mk_client(api_key: &str) -> Result<Client> {
 }
 
 /// Fetch top-N by market cap
+///
+/// # Errors
+/// Returns an error if the markets endpoint fails.
 pub async fn fetch_top_by_mcap(client: &Client, vs: &str, top_n: usize) -> Result<Vec<MarketCoin>> {
     let base = "https://pro-api.coingecko.com/api/v3/coins/markets";
     let mut page = 1usize;
@@ -304,7 +341,8 @@ pub async fn fetch_top_by_mcap(client: &Client, vs: &str, top_n: usize) -> Resul
                 ("price_change_percentage", "24h"),
                 ("sparkline", "false"),
             ],
-        )?;
+        )
+        .map_err(|e| Error::config(format!("invalid currency {vs}: {e}")))?;
         let resp = do_get_json::<Vec<serde_json::Value>>(client, url).await?;
         let mut batch = vec![];
         for v in resp {
@@ -345,7 +383,10 @@ pub async fn fetch_top_by_mcap(client: &Client, vs: &str, top_n: usize) -> Resul
 }
 
 /// Build URL for chunked OHLC range
-pub fn ohlc_range_url(coin_id: &str, vs: &str, from_ts: i64, to_ts: i64) -> reqwest::Url {
+///
+/// # Errors
+/// Returns [`Error::Data`] if the coin id does not form a valid URL.
+pub fn ohlc_range_url(coin_id: &str, vs: &str, from_ts: i64, to_ts: i64) -> Result<reqwest::Url> {
     let base = format!(
         "https://pro-api.coingecko.com/api/v3/coins/{}/ohlc/range",
         coin_id
@@ -359,10 +400,15 @@ pub fn ohlc_range_url(coin_id: &str, vs: &str, from_ts: i64, to_ts: i64) -> reqw
             ("interval", "daily".into()),
         ],
     )
-    .unwrap()
+    .map_err(|e| Error::data(format!("invalid coin id {coin_id}: {e}")))
 }
 
 /// Core HTTP GET with retry/backoff (+Retry-After)
+///
+/// # Errors
+/// Returns [`Error::RateLimited`] if the API still answers 429 after the retries,
+/// [`Error::Config`] if it rejects the API key, [`Error::Network`] for other failures and
+/// [`Error::Data`] if the body does not decode.
 pub async fn do_get_json<T: for<'de> serde::Deserialize<'de>>(
     client: &Client,
     url: reqwest::Url,
@@ -382,7 +428,15 @@ pub async fn do_get_json<T: for<'de> serde::Deserialize<'de>>(
         attempt += 1;
         if attempt > 6 {
             let txt = resp.text().await.unwrap_or_default();
-            bail!("HTTP {} after retries; body: {}", status, txt);
+            let message = format!("HTTP {status} after retries; body: {txt}");
+            return Err(match status {
+                StatusCode::TOO_MANY_REQUESTS => Error::RateLimited {
+                    message,
+                    retry_after: retry_after.map(Duration::from_secs),
+                },
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Config(message),
+                _ => Error::Network(message),
+            });
         }
         let backoff_ms = retry_after
             .map(|s| s * 1000)
@@ -395,6 +449,9 @@ pub async fn do_get_json<T: for<'de> serde::Deserialize<'de>>(
 /// Idempotent CSV update: fetch missing rows and append atomically.
 /// If !resume or file doesn't exist: write fresh file.
 /// Ensures daily dedupe by date.
+///
+/// # Errors
+/// Returns an error if the API request fails or the CSV cannot be written.
 #[allow(clippy::too_many_arguments)]
 pub async fn update_csv_for_coin(
     client: &Client,
@@ -406,6 +463,7 @@ pub async fn update_csv_for_coin(
     end_ts: i64,
     delay_ms: u64,
     resume: bool,
+    with_volume: bool,
 ) -> Result<()> {
     fs::create_dir_all(out_path.parent().unwrap_or(Path::new("."))).ok();
 
@@ -417,10 +475,7 @@ pub async fn update_csv_for_coin(
         None
     };
     if let Some(ld) = last_date {
-        let next = ld.succ_opt().unwrap();
-        eff_start_ts = Utc
-            .from_utc_datetime(&next.and_hms_opt(0, 0, 0).unwrap())
-            .timestamp();
+        eff_start_ts = ld.and_time(NaiveTime::MIN).and_utc().timestamp() + 86_400;
         if eff_start_ts > end_ts {
             info!("{} up-to-date through {}; skipping", symbol, ld);
             return Ok(());
@@ -430,6 +485,15 @@ pub async fn update_csv_for_coin(
     // Fetch chunked OHLC rows
     let mut rows = fetch_ohlc_rows(client, vs, coin_id, eff_start_ts, end_ts, delay_ms).await?;
 
+    // Attach daily quote-currency volume (OHLC endpoint doesn't carry it)
+    if with_volume && !rows.is_empty() {
+        let volumes =
+            fetch_volume_rows(client, vs, coin_id, eff_start_ts, end_ts, delay_ms).await?;
+        for r in &mut rows {
+            r.volume = volumes.get(&r.date).copied();
+        }
+    }
+
     // If resume and file exists, drop any overlapping dates (defensive)
     if resume
         && out_path.exists()
@@ -445,10 +509,11 @@ pub async fn update_csv_for_coin(
 
     // Append or create, atomically
     if out_path.exists() && resume {
-        // append without headers
+        // append without headers; keep the column layout of the existing file
+        let has_volume = csv_has_volume_column(out_path)?;
         let mut f = OpenOptions::new().append(true).open(out_path)?;
         for r in rows {
-            writeln!(
+            write!(
                 f,
                 "{},{:.8},{:.8},{:.8},{:.8}",
                 r.date.format("%Y-%m-%d"),
@@ -457,6 +522,10 @@ pub async fn update_csv_for_coin(
                 r.low,
                 r.close
             )?;
+            if has_volume {
+                write!(f, ",{}", r.volume.map(|v| format!("{v:.2}")).unwrap_or_default())?;
+            }
+            writeln!(f)?;
         }
         f.flush()?;
     } else {
@@ -464,7 +533,7 @@ pub async fn update_csv_for_coin(
         let mut tmp = NamedTempFile::new_in(out_path.parent().unwrap_or(Path::new(".")))?;
         {
             let mut wtr = WriterBuilder::new().from_writer(tmp.as_file_mut());
-            wtr.write_record(["date", "open", "high", "low", "close"])?;
+            wtr.write_record(["date", "open", "high", "low", "close", "volume"])?;
             for r in rows {
                 wtr.write_record(&[
                     r.date.format("%Y-%m-%d").to_string(),
@@ -472,6 +541,7 @@ pub async fn update_csv_for_coin(
                     format!("{:.8}", r.high),
                     format!("{:.8}", r.low),
                     format!("{:.8}", r.close),
+                    r.volume.map(|v| format!("{v:.2}")).unwrap_or_default(),
                 ])?;
             }
             wtr.flush()?;
@@ -487,9 +557,6 @@ pub async fn update_csv_for_coin(
 ///
 /// # Errors
 /// Returns an error if the API request fails or if the response cannot be parsed.
-///
-/// # Panics
-/// Panics if `partial_cmp` returns `None` when sorting timestamps.
 #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
 pub async fn fetch_ohlc_rows(
     client: &Client,
@@ -506,7 +573,7 @@ pub async fn fetch_ohlc_rows(
 
     while cur_from < to_ts {
         let cur_to = (cur_from + max_days * one_day).min(to_ts);
-        let url = ohlc_range_url(coin_id, vs, cur_from, cur_to);
+        let url = ohlc_range_url(coin_id, vs, cur_from, cur_to)?;
         let val = do_get_json::<serde_json::Value>(client, url).await?;
         if let Some(arr) = val.as_array() {
             for r in arr {
@@ -530,27 +597,101 @@ pub async fn fetch_ohlc_rows(
     }
 
     // Normalize to daily bars keyed by date, pick last per date
-    raws.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    raws.sort_by(|a, b| a.0.total_cmp(&b.0));
     let mut out = vec![];
-    for (_date, group) in &raws.into_iter().chunk_by(|r| {
+    let dated = raws.into_iter().filter_map(|r| {
         let ts = (r.0 / 1000.0) as i64; // ms -> s
-        Utc.timestamp_opt(ts, 0).unwrap().date_naive()
-    }) {
-        if let Some(last) = group.last() {
-            let ts = (last.0 / 1000.0) as i64;
-            let d = Utc.timestamp_opt(ts, 0).unwrap().date_naive();
+        Some((DateTime::from_timestamp(ts, 0)?.date_naive(), r))
+    });
+    for (date, group) in &dated.chunk_by(|(d, _)| *d) {
+        if let Some((_, last)) = group.last() {
             out.push(DailyBar {
-                date: d,
+                date,
                 open: last.1,
                 high: last.2,
                 low: last.3,
                 close: last.4,
+                volume: None,
             });
         }
     }
     Ok(out)
 }
 
+/// Build URL for chunked market chart range (prices, market caps, total volumes)
+///
+/// # Errors
+/// Returns [`Error::Data`] if the coin id does not form a valid URL.
+pub fn market_chart_range_url(
+    coin_id: &str,
+    vs: &str,
+    from_ts: i64,
+    to_ts: i64,
+) -> Result<reqwest::Url> {
+    let base = format!(
+        "https://pro-api.coingecko.com/api/v3/coins/{}/market_chart/range",
+        coin_id
+    );
+    reqwest::Url::parse_with_params(
+        &base,
+        &[
+            ("vs_currency", vs.to_string()),
+            ("from", from_ts.to_string()),
+            ("to", to_ts.to_string()),
+            ("interval", "daily".into()),
+        ],
+    )
+    .map_err(|e| Error::data(format!("invalid coin id {coin_id}: {e}")))
+}
+
+/// Return daily quote-currency volume for [`from_ts..=to_ts`] keyed by date (last point per date wins).
+///
+/// # Errors
+/// Returns an error if the API request fails or if the response cannot be parsed.
+#[allow(clippy::cast_possible_truncation)]
+pub async fn fetch_volume_rows(
+    client: &Client,
+    vs: &str,
+    coin_id: &str,
+    from_ts: i64,
+    to_ts: i64,
+    delay_ms: u64,
+) -> Result<BTreeMap<NaiveDate, f64>> {
+    let mut cur_from = from_ts;
+    let one_day = 86_400i64;
+    let max_days = 180i64;
+    let mut out = BTreeMap::new();
+
+    while cur_from < to_ts {
+        let cur_to = (cur_from + max_days * one_day).min(to_ts);
+        let url = market_chart_range_url(coin_id, vs, cur_from, cur_to)?;
+        let val = do_get_json::<serde_json::Value>(client, url).await?;
+        if let Some(arr) = val.get("total_volumes").and_then(|v| v.as_array()) {
+            for r in arr {
+                if let Some(a) = r.as_array()
+                    && a.len() >= 2
+                    && let (Some(ts_ms), Some(v)) = (a[0].as_f64(), a[1].as_f64())
+                    && let Some(dt) = DateTime::from_timestamp((ts_ms / 1000.0) as i64, 0)
+                {
+                    out.insert(dt.date_naive(), v);
+                }
+            }
+        }
+        sleep(Duration::from_millis(delay_ms)).await;
+        cur_from = cur_to + 1;
+    }
+    Ok(out)
+}
+
+/// Whether an existing OHLC CSV was written with a trailing `volume` column.
+///
+/// # Errors
+/// Returns an error if the file cannot be read.
+pub fn csv_has_volume_column(path: &Path) -> Result<bool> {
+    let mut rdr = ReaderBuilder::new().from_path(path)?;
+    Ok(rdr.headers()?.iter().any(|h| h.trim() == "volume"))
+}
+
 /// Read the last date from a CSV file.
 ///
 /// # Errors
@@ -0,0 +1,209 @@
+use itertools::Itertools;
+use statrs::function::erf;
+use std::f64::consts::SQRT_2;
+
+/// Euler-Mascheroni constant, used in the expected maximum of N Gaussian draws
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// Sample moments of a return series.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Moments {
+    pub n: usize,
+    pub mean: f64,
+    pub std: f64,
+    pub skew: f64,
+    /// Non-excess kurtosis (3.0 for a normal distribution)
+    pub kurtosis: f64,
+}
+
+/// Overfitting diagnostics for one reported Sharpe ratio.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SharpeDiagnostics {
+    /// Per-period (non-annualized) Sharpe ratio
+    pub sharpe: f64,
+    /// P(true Sharpe > 0) given sample length, skew and kurtosis
+    pub psr: f64,
+    /// PSR against the Sharpe expected from the best of `trials` unskilled strategies
+    pub dsr: f64,
+    pub trials: usize,
+}
+
+/// Probability of backtest overfitting from combinatorially symmetric cross-validation.
+#[derive(Debug, Clone, Default)]
+pub struct Pbo {
+    /// Share of splits where the in-sample winner ranks below the out-of-sample median
+    pub pbo: f64,
+    /// Logit of the in-sample winner's relative out-of-sample rank per split
+    pub logits: Vec<f64>,
+    pub splits: usize,
+}
+
+/// Standard normal CDF.
+fn norm_cdf(x: f64) -> f64 {
+    0.5 * erf::erfc(-x / SQRT_2)
+}
+
+/// Standard normal quantile.
+fn norm_quantile(p: f64) -> f64 {
+    -SQRT_2 * erf::erfc_inv(2.0 * p)
+}
+
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn moments(returns: &[f64]) -> Moments {
+    let n = returns.len();
+    if n < 2 {
+        return Moments {
+            n,
+            kurtosis: 3.0,
+            ..Moments::default()
+        };
+    }
+    let nf = n as f64;
+    let mean = returns.iter().sum::<f64>() / nf;
+    let m2 = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / nf;
+    let m3 = returns.iter().map(|r| (r - mean).powi(3)).sum::<f64>() / nf;
+    let m4 = returns.iter().map(|r| (r - mean).powi(4)).sum::<f64>() / nf;
+    let std = (m2 * nf / (nf - 1.0)).sqrt();
+    let (skew, kurtosis) = if m2 > 0.0 {
+        (m3 / m2.powf(1.5), m4 / (m2 * m2))
+    } else {
+        (0.0, 3.0)
+    };
+    Moments {
+        n,
+        mean,
+        std,
+        skew,
+        kurtosis,
+    }
+}
+
+/// Per-period Sharpe ratio (mean / sample std).
+#[must_use]
+pub fn sharpe(returns: &[f64]) -> f64 {
+    let m = moments(returns);
+    if m.std > 0.0 { m.mean / m.std } else { 0.0 }
+}
+
+/// Probabilistic Sharpe ratio (Bailey & López de Prado): probability that the true
+/// per-period Sharpe exceeds `benchmark`, correcting for sample length and non-normality.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn probabilistic_sharpe(returns: &[f64], benchmark: f64) -> f64 {
+    let m = moments(returns);
+    if m.n < 3 || m.std <= 0.0 {
+        return 0.0;
+    }
+    let sr = m.mean / m.std;
+    let denom = (1.0 - m.skew * sr + (m.kurtosis - 1.0) / 4.0 * sr * sr).max(1e-12);
+    norm_cdf((sr - benchmark) * ((m.n - 1) as f64).sqrt() / denom.sqrt())
+}
+
+/// Expected maximum per-period Sharpe among `trials` strategies with zero true Sharpe whose
+/// estimated Sharpes have variance `sharpe_variance`.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn expected_max_sharpe(trials: usize, sharpe_variance: f64) -> f64 {
+    if trials < 2 || sharpe_variance <= 0.0 {
+        return 0.0;
+    }
+    let n = trials as f64;
+    sharpe_variance.sqrt()
+        * ((1.0 - EULER_GAMMA) * norm_quantile(1.0 - 1.0 / n)
+            + EULER_GAMMA * norm_quantile(1.0 - 1.0 / (n * std::f64::consts::E)))
+}
+
+/// Deflated Sharpe ratio: PSR measured against the expected best Sharpe of `trials` tries.
+#[must_use]
+pub fn deflated_sharpe(returns: &[f64], trials: usize, sharpe_variance: f64) -> f64 {
+    probabilistic_sharpe(returns, expected_max_sharpe(trials, sharpe_variance))
+}
+
+/// PSR and DSR for every trial, deflating by the number of trials and the dispersion of
+/// their Sharpe ratios.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn sharpe_diagnostics(trials: &[&[f64]]) -> Vec<SharpeDiagnostics> {
+    let sharpes: Vec<f64> = trials.iter().map(|r| sharpe(r)).collect();
+    let n = sharpes.len();
+    let variance = if n > 1 {
+        let mean = sharpes.iter().sum::<f64>() / n as f64;
+        sharpes.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n as f64 - 1.0)
+    } else {
+        0.0
+    };
+    trials
+        .iter()
+        .zip(&sharpes)
+        .map(|(r, &sr)| SharpeDiagnostics {
+            sharpe: sr,
+            psr: probabilistic_sharpe(r, 0.0),
+            dsr: deflated_sharpe(r, n, variance),
+            trials: n,
+        })
+        .collect()
+}
+
+/// Probability of backtest overfitting via CSCV (Bailey et al.). `returns[trial][t]` must be
+/// aligned in time. Rows are cut into `blocks` contiguous groups (rounded down to even); each
+/// half/half combination picks the best in-sample trial by Sharpe and records its relative
+/// rank out of sample. Returns `None` with fewer than two trials or too little data.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn pbo_cscv(returns: &[Vec<f64>], blocks: usize) -> Option<Pbo> {
+    let n_trials = returns.len();
+    let t = returns.iter().map(Vec::len).min()?;
+    let blocks = blocks - blocks % 2;
+    if n_trials < 2 || blocks < 2 || t < blocks * 2 {
+        return None;
+    }
+    let size = t / blocks;
+    // Per (trial, block) sum and sum of squares so each split's Sharpe is O(blocks)
+    let stats: Vec<Vec<(f64, f64)>> = returns
+        .iter()
+        .map(|r| {
+            (0..blocks)
+                .map(|b| {
+                    let chunk = &r[b * size..(b + 1) * size];
+                    (chunk.iter().sum(), chunk.iter().map(|x| x * x).sum())
+                })
+                .collect()
+        })
+        .collect();
+    let sharpe_of = |trial: usize, idx: &[usize]| -> f64 {
+        let n = (idx.len() * size) as f64;
+        let (sum, sq) = idx.iter().fold((0.0, 0.0), |(s, q), &b| {
+            (s + stats[trial][b].0, q + stats[trial][b].1)
+        });
+        let mean = sum / n;
+        let var = (sq - n * mean * mean) / (n - 1.0);
+        if var > 0.0 { mean / var.sqrt() } else { 0.0 }
+    };
+
+    let logits: Vec<f64> = (0..blocks)
+        .combinations(blocks / 2)
+        .map(|train| {
+            let test: Vec<usize> = (0..blocks).filter(|b| !train.contains(b)).collect();
+            let is: Vec<f64> = (0..n_trials).map(|k| sharpe_of(k, &train)).collect();
+            let oos: Vec<f64> = (0..n_trials).map(|k| sharpe_of(k, &test)).collect();
+            let best = is
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map_or(0, |(k, _)| k);
+            // Rank 1 = worst out of sample; relative rank in (0, 1)
+            let rank = oos.iter().filter(|v| **v < oos[best]).count() + 1;
+            let w = rank as f64 / (n_trials as f64 + 1.0);
+            (w / (1.0 - w)).ln()
+        })
+        .collect();
+
+    let splits = logits.len();
+    let pbo = logits.iter().filter(|l| **l <= 0.0).count() as f64 / splits as f64;
+    Some(Pbo {
+        pbo,
+        logits,
+        splits,
+    })
+}
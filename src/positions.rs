@@ -0,0 +1,381 @@
+//! The daemon's position book: what it has entered, at what initial risk, the current trailing
+//! stop and whether the profit target has been scaled out. Persisted as JSON between cycles;
+//! each cycle ratchets stops on the latest close and emits the resulting actions.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::TradeArgs;
+use crate::account::{Account, Holding};
+use crate::analyzer::StrategyAnalysis;
+use crate::error::{Context, Error, Required, Result};
+use crate::instruments::Instrument;
+
+/// Latest bar of an asset the book is updated on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mark {
+    pub date: NaiveDate,
+    pub close: f64,
+    /// The strategy's stop on this bar: close − `atr_mult` × ATR of the true range, or the
+    /// volatility fallback before the ATR has a full window
+    pub stop: Option<f64>,
+}
+
+/// Latest close and strategy stop level of every analyzed asset.
+#[must_use]
+pub fn latest_marks(analyses: &[StrategyAnalysis]) -> BTreeMap<String, Mark> {
+    analyses
+        .iter()
+        .filter_map(|a| {
+            a.signals().last().map(|s| {
+                let mark = Mark {
+                    date: s.date(),
+                    close: s.close(),
+                    stop: s.stop_level(),
+                };
+                (a.asset().clone(), mark)
+            })
+        })
+        .collect()
+}
+
+/// A new position to open at the cycle's close, e.g. from the portfolio playbook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub asset: String,
+    pub quantity: f64,
+    pub stop: f64,
+    pub instrument: Instrument,
+}
+
+/// Target rules the book is managed by; stops trail the strategy's own stop level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookRules {
+    /// Scale out half at this multiple of the initial risk R
+    pub profit_target_r: f64,
+}
+
+impl BookRules {
+    /// Rules of resolved trade arguments.
+    ///
+    /// # Errors
+    /// Returns a configuration error if the profit target is not set.
+    pub fn from_args(args: &TradeArgs) -> Result<Self> {
+        Ok(Self {
+            profit_target_r: args.profit_target_r.required("profit_target_r")?,
+        })
+    }
+}
+
+/// An open position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub asset: String,
+    pub entry_date: NaiveDate,
+    pub entry_price: f64,
+    pub initial_quantity: f64,
+    pub quantity: f64,
+    pub initial_stop: f64,
+    /// Initial risk per unit: entry price − initial stop
+    pub initial_r: f64,
+    pub trailing_stop: f64,
+    pub scaled_out: bool,
+    /// Profit or loss booked by the scale-out
+    pub realized: f64,
+    /// Date of the last bar the position was updated on
+    pub updated: NaiveDate,
+    pub last_close: f64,
+    #[serde(default)]
+    pub instrument: Instrument,
+}
+
+impl Position {
+    /// Current open profit in multiples of the initial risk.
+    #[must_use]
+    pub fn open_r(&self) -> f64 {
+        (self.last_close - self.entry_price) / self.initial_r
+    }
+
+    /// Price at which half the position is scaled out.
+    #[must_use]
+    pub fn target(&self, rules: &BookRules) -> f64 {
+        self.instrument.price_up(
+            rules
+                .profit_target_r
+                .mul_add(self.initial_r, self.entry_price),
+        )
+    }
+}
+
+/// A position that was stopped out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Closed {
+    pub asset: String,
+    pub entry_date: NaiveDate,
+    pub entry_price: f64,
+    pub exit_date: NaiveDate,
+    pub exit_price: f64,
+    pub quantity: f64,
+    /// Total profit including the scale-out
+    pub pnl: f64,
+    /// `pnl` in multiples of the initial risk of the whole position
+    pub r_multiple: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    /// Buy a new position
+    Enter,
+    /// Move the stop order up
+    RaiseStop,
+    /// Sell half at the profit target
+    ScaleOut,
+    /// The close breached the stop: sell the rest
+    StopOut,
+}
+
+impl ActionKind {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Enter => "ENTER",
+            Self::RaiseStop => "RAISE STOP",
+            Self::ScaleOut => "SCALE OUT",
+            Self::StopOut => "STOP OUT",
+        }
+    }
+}
+
+/// What to do about a position after a cycle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    pub date: NaiveDate,
+    pub asset: String,
+    pub kind: ActionKind,
+    pub quantity: f64,
+    /// Fill price for entries and exits, new stop for stop raises
+    pub price: f64,
+    pub note: String,
+    /// Rules of the position, so quantities print in its lot size
+    #[serde(default)]
+    pub instrument: Instrument,
+}
+
+impl Action {
+    /// One-line description for console output.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} {} @ {:.2} ({})",
+            self.kind.name(),
+            self.asset,
+            self.instrument.format_quantity(self.quantity),
+            self.price,
+            self.note
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PositionBook {
+    #[serde(default)]
+    pub positions: Vec<Position>,
+    #[serde(default)]
+    pub closed: Vec<Closed>,
+    /// Every action emitted, oldest first
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl PositionBook {
+    /// Read the book, or start an empty one if `path` does not exist yet.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        serde_json::from_str(&text)
+            .map_err(|e| Error::config(format!("position book {}: {e}", path.display())))
+    }
+
+    /// Write the book atomically, so a crash mid-write keeps the previous one.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(dir)?;
+        let tmp = tempfile::NamedTempFile::new_in(dir)?;
+        serde_json::to_writer_pretty(&tmp, self)?;
+        tmp.persist(path)?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn position(&self, asset: &str) -> Option<&Position> {
+        self.positions.iter().find(|p| p.asset == asset)
+    }
+
+    /// Net cash of every fill in the action log: entries pay, scale-outs and stop-outs receive.
+    #[must_use]
+    pub fn cash_flow(&self) -> f64 {
+        self.actions
+            .iter()
+            .map(|a| match a.kind {
+                ActionKind::Enter => -a.quantity * a.price,
+                ActionKind::ScaleOut | ActionKind::StopOut => a.quantity * a.price,
+                ActionKind::RaiseStop => 0.0,
+            })
+            .sum()
+    }
+
+    /// Book the fills into `account`, the capital the book trades from: cash moves by
+    /// [`Self::cash_flow`] and open positions become holdings at their entry price, marked at
+    /// their last close.
+    pub fn apply_to(&self, account: &mut Account) {
+        account.cash += self.cash_flow();
+        account
+            .positions
+            .extend(self.positions.iter().map(|p| Holding {
+                asset: p.asset.clone(),
+                quantity: p.quantity,
+                avg_price: p.entry_price,
+                mark: Some(p.last_close),
+            }));
+    }
+
+    /// Apply one cycle: every position with a newer bar in `marks` is stopped out if the close
+    /// is at or below its stop, else scales out half at the profit target (moving the stop to
+    /// break-even) and ratchets its stop to max(prior, strategy stop level). Then `entries` not held
+    /// (nor stopped out on the same bar) open at their close. Bars already applied are skipped,
+    /// so repeated cycles on the same data change nothing. Returns the cycle's actions.
+    pub fn update(
+        &mut self,
+        marks: &BTreeMap<String, Mark>,
+        entries: &[Entry],
+        rules: &BookRules,
+    ) -> &[Action] {
+        let mut actions = Vec::new();
+        let mut open = Vec::with_capacity(self.positions.len());
+        for mut p in std::mem::take(&mut self.positions) {
+            let Some(mark) = marks.get(&p.asset).filter(|m| m.date > p.updated) else {
+                open.push(p);
+                continue;
+            };
+            let (asset, instrument) = (p.asset.clone(), p.instrument);
+            let action = |kind, quantity, price, note: String| Action {
+                date: mark.date,
+                asset: asset.clone(),
+                kind,
+                quantity,
+                price,
+                note,
+                instrument,
+            };
+            p.updated = mark.date;
+            p.last_close = mark.close;
+
+            if mark.close <= p.trailing_stop {
+                actions.push(action(
+                    ActionKind::StopOut,
+                    p.quantity,
+                    mark.close,
+                    format!("close at or below stop {:.2}", p.trailing_stop),
+                ));
+                let pnl = p.quantity.mul_add(mark.close - p.entry_price, p.realized);
+                self.closed.push(Closed {
+                    asset: p.asset.clone(),
+                    entry_date: p.entry_date,
+                    entry_price: p.entry_price,
+                    exit_date: mark.date,
+                    exit_price: mark.close,
+                    quantity: p.initial_quantity,
+                    pnl,
+                    r_multiple: pnl / (p.initial_quantity * p.initial_r),
+                });
+                continue;
+            }
+
+            // A position too small to split into whole lots is not scaled out; all of it rides on
+            // the trailing stop
+            let target = p.target(rules);
+            let half = p.instrument.quantity(p.quantity * 0.5, mark.close);
+            if !p.scaled_out && mark.close >= target && half > 0.0 {
+                actions.push(action(
+                    ActionKind::ScaleOut,
+                    half,
+                    mark.close,
+                    format!("{:.1}R target {target:.2} reached", rules.profit_target_r),
+                ));
+                p.realized = half.mul_add(mark.close - p.entry_price, p.realized);
+                p.quantity = p.instrument.round_quantity(p.quantity - half);
+                p.scaled_out = true;
+                p.trailing_stop = p.trailing_stop.max(p.entry_price);
+            }
+
+            if let Some(ratchet) = mark.stop.map(|s| p.instrument.price_down(s))
+                && ratchet > p.trailing_stop
+            {
+                actions.push(action(
+                    ActionKind::RaiseStop,
+                    p.quantity,
+                    ratchet,
+                    format!("from {:.2}: strategy stop level", p.trailing_stop),
+                ));
+                p.trailing_stop = ratchet;
+            }
+            open.push(p);
+        }
+
+        for entry in entries {
+            let Some(mark) = marks.get(&entry.asset) else {
+                continue;
+            };
+            let held = open.iter().any(|p| p.asset == entry.asset);
+            let stopped_today = actions
+                .iter()
+                .any(|a| a.asset == entry.asset && a.kind == ActionKind::StopOut);
+            if held || stopped_today || entry.quantity <= 0.0 || entry.stop >= mark.close {
+                continue;
+            }
+            let initial_r = mark.close - entry.stop;
+            actions.push(Action {
+                date: mark.date,
+                asset: entry.asset.clone(),
+                kind: ActionKind::Enter,
+                quantity: entry.quantity,
+                price: mark.close,
+                note: format!("stop {:.2}, R {initial_r:.2}", entry.stop),
+                instrument: entry.instrument,
+            });
+            open.push(Position {
+                asset: entry.asset.clone(),
+                entry_date: mark.date,
+                entry_price: mark.close,
+                initial_quantity: entry.quantity,
+                quantity: entry.quantity,
+                initial_stop: entry.stop,
+                initial_r,
+                trailing_stop: entry.stop,
+                scaled_out: false,
+                realized: 0.0,
+                updated: mark.date,
+                last_close: mark.close,
+                instrument: entry.instrument,
+            });
+        }
+
+        self.positions = open;
+        let start = self.actions.len();
+        self.actions.extend(actions);
+        &self.actions[start..]
+    }
+}
@@ -0,0 +1,287 @@
+use crate::StrategyArgs;
+use crate::error::{Required, Result};
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+use crate::strategy::{rolling_ma, rolling_ma_opt, rolling_std};
+
+/// Market regime label for a single date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Regime {
+    RiskOn,
+    Neutral,
+    RiskOff,
+    Crisis,
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::RiskOn => "risk-on",
+            Self::Neutral => "neutral",
+            Self::RiskOff => "risk-off",
+            Self::Crisis => "crisis",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl Regime {
+    /// Gross exposure multiplier applied to long weights in this regime.
+    #[must_use]
+    pub fn exposure(self, config: &RegimeConfig) -> f64 {
+        match self {
+            Self::RiskOn => 1.0,
+            Self::Neutral => config.neutral_exposure,
+            Self::RiskOff => config.risk_off_exposure,
+            Self::Crisis => config.crisis_exposure,
+        }
+    }
+}
+
+/// Thresholds for the regime classifier.
+#[derive(Debug, Clone)]
+pub struct RegimeConfig {
+    pub ma_short: usize,
+    pub ma_long: usize,
+    /// Window (days) for realized volatility and dispersion
+    pub vol_lookback: usize,
+    /// Annualized BTC realized volatility above which the market is "stressed"
+    pub high_vol: f64,
+    /// Share of the universe above its long MA for a bullish breadth reading
+    pub breadth_bull: f64,
+    /// Share of the universe above its long MA below which breadth is bearish
+    pub breadth_bear: f64,
+    pub neutral_exposure: f64,
+    pub risk_off_exposure: f64,
+    pub crisis_exposure: f64,
+}
+
+impl RegimeConfig {
+    /// Classifier settings of resolved strategy arguments; the trend MAs are the strategy's.
+    ///
+    /// # Errors
+    /// Returns a configuration error if a lookback, threshold or exposure is not set.
+    pub fn from_args(args: &StrategyArgs) -> Result<Self> {
+        Ok(Self {
+            ma_short: args.ma_short.required("ma_short")?,
+            ma_long: args.ma_long.required("ma_long")?,
+            vol_lookback: args.regime_vol_lookback.required("regime_vol_lookback")?,
+            high_vol: args.regime_high_vol.required("regime_high_vol")?,
+            breadth_bull: args.regime_breadth_bull.required("regime_breadth_bull")?,
+            breadth_bear: args.regime_breadth_bear.required("regime_breadth_bear")?,
+            neutral_exposure: args
+                .regime_neutral_exposure
+                .required("regime_neutral_exposure")?,
+            risk_off_exposure: args
+                .regime_risk_off_exposure
+                .required("regime_risk_off_exposure")?,
+            crisis_exposure: args.regime_crisis_exposure.required("regime_crisis_exposure")?,
+        })
+    }
+}
+
+/// Regime inputs and classification for one date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeState {
+    pub date: NaiveDate,
+    pub regime: Regime,
+    /// +1 BTC uptrend (price > MA_long, MA_short > MA_long), -1 downtrend, 0 mixed
+    pub btc_trend: i8,
+    /// Fraction of assets closing above their long MA; `None` until some asset has one
+    pub breadth: Option<f64>,
+    /// Annualized BTC realized volatility
+    pub realized_vol: Option<f64>,
+    /// Trailing average cross-sectional std of daily asset returns
+    pub dispersion: Option<f64>,
+    pub exposure: f64,
+}
+
+impl RegimeState {
+    /// One-line description for playbooks and AI prompts.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "{} (BTC trend {}, breadth {}, realized vol {}, dispersion {})",
+            self.regime,
+            match self.btc_trend {
+                1 => "up",
+                -1 => "down",
+                _ => "mixed",
+            },
+            self.breadth
+                .map_or_else(|| "n/a".to_string(), |b| format!("{:.0}%", b * 100.0)),
+            self.realized_vol
+                .map_or_else(|| "n/a".to_string(), |v| format!("{:.0}%", v * 100.0)),
+            self.dispersion
+                .map_or_else(|| "n/a".to_string(), |v| format!("{:.2}%", v * 100.0)),
+        )
+    }
+}
+
+/// Classify each date from BTC trend, universe breadth, BTC realized volatility and
+/// cross-sectional return dispersion. `asset_closes` are aligned to `btc_close`; `None`
+/// marks a date where the asset is not tradeable.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn classify(
+    dates: &[NaiveDate],
+    btc_close: &[f64],
+    asset_closes: &[Vec<Option<f64>>],
+    config: &RegimeConfig,
+) -> Vec<RegimeState> {
+    let n = dates.len();
+    let btc_ma_s = rolling_ma(btc_close, config.ma_short);
+    let btc_ma_l = rolling_ma(btc_close, config.ma_long);
+
+    let btc_ret: Vec<f64> = (0..n)
+        .map(|i| {
+            if i == 0 {
+                0.0
+            } else {
+                btc_close[i] / btc_close[i - 1] - 1.0
+            }
+        })
+        .collect();
+    let btc_vol = rolling_std(&btc_ret, config.vol_lookback);
+
+    // Breadth: share of available assets above their own long MA
+    let asset_ma_l: Vec<Vec<Option<f64>>> = asset_closes
+        .iter()
+        .map(|c| rolling_ma_opt(c, config.ma_long))
+        .collect();
+
+    // Cross-sectional dispersion of daily returns
+    let daily_dispersion: Vec<f64> = (0..n)
+        .map(|i| {
+            if i == 0 {
+                return 0.0;
+            }
+            let rets: Vec<f64> = asset_closes
+                .iter()
+                .filter_map(|c| match (c[i - 1], c[i]) {
+                    (Some(p), Some(x)) if p > 0.0 => Some(x / p - 1.0),
+                    _ => None,
+                })
+                .collect();
+            if rets.len() < 2 {
+                return 0.0;
+            }
+            let m = rets.iter().sum::<f64>() / rets.len() as f64;
+            (rets.iter().map(|r| (r - m).powi(2)).sum::<f64>() / rets.len() as f64).sqrt()
+        })
+        .collect();
+    let dispersion = rolling_ma(&daily_dispersion, config.vol_lookback);
+    let dispersion_baseline = rolling_ma(&daily_dispersion, config.vol_lookback * 3);
+
+    (0..n)
+        .map(|i| {
+            let btc_trend = match (btc_ma_s[i], btc_ma_l[i]) {
+                (Some(s), Some(l)) if btc_close[i] > l && s > l => 1,
+                (Some(s), Some(l)) if btc_close[i] < l && s < l => -1,
+                _ => 0,
+            };
+
+            let (above, total) = asset_closes.iter().zip(&asset_ma_l).fold(
+                (0usize, 0usize),
+                |(above, total), (c, ma)| match (c[i], ma[i]) {
+                    (Some(px), Some(m)) => (above + usize::from(px > m), total + 1),
+                    _ => (above, total),
+                },
+            );
+            // Before any asset has a long MA breadth is unknown and casts no vote
+            let breadth = (total > 0).then(|| above as f64 / total as f64);
+            let breadth_bear = breadth.is_some_and(|b| b < config.breadth_bear);
+
+            let realized_vol = btc_vol[i].map(|sd| sd * 365.0_f64.sqrt());
+            let high_vol = realized_vol.is_some_and(|v| v > config.high_vol);
+
+            // Score: trend and breadth vote, stress subtracts, rising dispersion favours RS selection
+            let mut score = i32::from(btc_trend);
+            if breadth.is_some_and(|b| b >= config.breadth_bull) {
+                score += 1;
+            } else if breadth_bear {
+                score -= 1;
+            }
+            if high_vol {
+                score -= 1;
+            }
+            if btc_trend >= 0
+                && let (Some(d), Some(b)) = (dispersion[i], dispersion_baseline[i])
+                && d > b
+            {
+                score += 1;
+            }
+
+            let regime = if btc_trend < 0 && high_vol && breadth_bear {
+                Regime::Crisis
+            } else if score >= 2 {
+                Regime::RiskOn
+            } else if score <= -1 {
+                Regime::RiskOff
+            } else {
+                Regime::Neutral
+            };
+
+            RegimeState {
+                date: dates[i],
+                regime,
+                btc_trend,
+                breadth,
+                realized_vol,
+                dispersion: dispersion[i],
+                exposure: regime.exposure(config),
+            }
+        })
+        .collect()
+}
+
+/// Write regime states to `regime.csv`.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_regimes(path: &Path, states: &[RegimeState]) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    for s in states {
+        wtr.serialize(s)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Append regime states to an existing `regime.csv`, creating it with a header if missing.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn append_regimes(path: &Path, states: &[RegimeState]) -> Result<()> {
+    if !path.exists() {
+        return write_regimes(path, states);
+    }
+    let file = std::fs::OpenOptions::new().append(true).open(path)?;
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+    for s in states {
+        wtr.serialize(s)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Read the most recent regime from a `regime.csv` written by the strategy.
+///
+/// # Errors
+/// Returns an error if the file exists but cannot be parsed.
+pub fn read_latest_regime(path: &Path) -> Result<Option<RegimeState>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut rdr = ReaderBuilder::new().trim(csv::Trim::All).from_path(path)?;
+    let mut last = None;
+    for rec in rdr.deserialize::<RegimeState>() {
+        last = Some(rec?);
+    }
+    Ok(last)
+}
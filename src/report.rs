@@ -0,0 +1,514 @@
+use crate::error::{Context, Error, Result};
+use chrono::{Datelike, NaiveDate};
+use csv::ReaderBuilder;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::StrategyArgs;
+use crate::attribution::Attribution;
+use crate::metrics;
+use crate::strategy::Backtest;
+
+/// Bars in the rolling Sharpe window
+const ROLLING_WINDOW: usize = 90;
+const WIDTH: f64 = 960.0;
+const PALETTE: [&str; 6] = [
+    "#2563eb", "#f59e0b", "#10b981", "#8b5cf6", "#ef4444", "#6b7280",
+];
+
+/// Everything the HTML report shows, from a fresh backtest or a stored run directory.
+#[derive(Debug, Clone, Default)]
+pub struct ReportData {
+    pub title: String,
+    pub dates: Vec<NaiveDate>,
+    pub equity: Vec<f64>,
+    pub daily_ret: Vec<f64>,
+    pub exposure: Vec<f64>,
+    pub num_positions: Vec<f64>,
+    /// (name, equity curve on `dates`)
+    pub benchmarks: Vec<(String, Vec<f64>)>,
+    /// (asset, summed contribution), best first
+    pub attribution: Vec<(String, f64)>,
+    pub params: Vec<(String, String)>,
+    /// Contents of `metrics.txt`
+    pub summary: String,
+}
+
+/// Flatten serialized strategy arguments into sorted (name, value) rows.
+#[must_use]
+pub fn param_rows(args: &StrategyArgs) -> Vec<(String, String)> {
+    let Ok(serde_json::Value::Object(map)) = serde_json::to_value(args) else {
+        return vec![];
+    };
+    map.into_iter()
+        .filter(|(_, v)| !v.is_null())
+        .map(|(k, v)| {
+            let v = match v {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Array(a) => format!("{} items", a.len()),
+                other => other.to_string(),
+            };
+            (k, v)
+        })
+        .collect()
+}
+
+impl ReportData {
+    /// Report data for an in-memory backtest.
+    #[must_use]
+    pub fn from_backtest(
+        bt: &Backtest,
+        attribution: &Attribution,
+        args: &StrategyArgs,
+        summary: &str,
+    ) -> Self {
+        let benchmarks = bt
+            .benchmarks
+            .iter()
+            .map(|b| {
+                let eq = b
+                    .daily_ret
+                    .iter()
+                    .scan(1.0, |e, r| {
+                        *e *= 1.0 + r;
+                        Some(*e)
+                    })
+                    .collect();
+                (b.name.clone(), eq)
+            })
+            .collect();
+        #[allow(clippy::cast_precision_loss)]
+        let num_positions = bt.num_positions.iter().map(|n| *n as f64).collect();
+        Self {
+            title: "Strategy Backtest".to_string(),
+            dates: bt.dates.clone(),
+            equity: bt.equity.clone(),
+            daily_ret: bt.daily_ret.clone(),
+            exposure: bt.exposure.clone(),
+            num_positions,
+            benchmarks,
+            attribution: attribution
+                .by_asset
+                .iter()
+                .map(|g| (g.group.clone(), g.contribution))
+                .collect(),
+            params: param_rows(args),
+            summary: summary.to_string(),
+        }
+    }
+
+    /// Rebuild report data from the files `strategy` wrote to `dir`.
+    ///
+    /// # Errors
+    /// Returns an error if `equity_curve.csv` is missing or malformed; the other files are
+    /// optional.
+    pub fn load_run(dir: &Path) -> Result<Self> {
+        let mut data = Self {
+            title: format!("Strategy Backtest — {}", dir.display()),
+            ..Self::default()
+        };
+        let mut rdr = ReaderBuilder::new()
+            .from_path(dir.join("equity_curve.csv"))
+            .with_context(|| format!("read equity_curve.csv in {}", dir.display()))?;
+        let headers = rdr.headers()?.clone();
+        let col = |name: &str| headers.iter().position(|h| h == name);
+        let (Some(date), Some(equity), Some(ret)) = (col("date"), col("equity"), col("port_ret"))
+        else {
+            return Err(Error::data("equity_curve.csv lacks date/equity/port_ret columns"));
+        };
+        let (positions, exposure) = (col("num_positions"), col("gross_exposure"));
+        for rec in rdr.records() {
+            let rec = rec?;
+            let num = |i: Option<usize>| {
+                i.and_then(|i| rec.get(i))
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .unwrap_or(0.0)
+            };
+            data.dates
+                .push(NaiveDate::parse_from_str(&rec[date], "%Y-%m-%d")?);
+            data.equity.push(num(Some(equity)));
+            data.daily_ret.push(num(Some(ret)));
+            data.num_positions.push(num(positions));
+            data.exposure.push(num(exposure));
+        }
+
+        if let Ok(mut rdr) = ReaderBuilder::new().from_path(dir.join("benchmarks.csv")) {
+            let names: Vec<String> = rdr.headers()?.iter().skip(2).map(String::from).collect();
+            let mut curves = vec![Vec::new(); names.len()];
+            for rec in rdr.records() {
+                let rec = rec?;
+                for (k, curve) in curves.iter_mut().enumerate() {
+                    curve.push(rec.get(k + 2).and_then(|v| v.parse().ok()).unwrap_or(1.0));
+                }
+            }
+            data.benchmarks = names.into_iter().zip(curves).collect();
+        }
+        if let Ok(mut rdr) = ReaderBuilder::new().from_path(dir.join("attribution_summary.csv")) {
+            for rec in rdr.records() {
+                let rec = rec?;
+                if &rec[0] == "asset" {
+                    data.attribution
+                        .push((rec[1].to_string(), rec[3].parse().unwrap_or(0.0)));
+                }
+            }
+        }
+        if let Ok(text) = fs::read_to_string(dir.join("params.json"))
+            && let Ok(args) = serde_json::from_str::<StrategyArgs>(&text)
+        {
+            data.params = param_rows(&args);
+        }
+        data.summary = fs::read_to_string(dir.join("metrics.txt")).unwrap_or_default();
+        Ok(data)
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Map `v` in `[lo, hi]` onto `[top, bottom]` pixels (higher values draw higher).
+fn scale_y(v: f64, lo: f64, hi: f64, top: f64, bottom: f64) -> f64 {
+    if hi > lo {
+        bottom - (v - lo) / (hi - lo) * (bottom - top)
+    } else {
+        f64::midpoint(top, bottom)
+    }
+}
+
+/// Multi-series line chart over `dates`. `fill` shades the area between the first series
+/// and zero (used for the underwater chart).
+#[allow(clippy::cast_precision_loss)]
+fn line_chart(
+    dates: &[NaiveDate],
+    series: &[(&str, &[f64])],
+    height: f64,
+    fmt: fn(f64) -> String,
+    fill: bool,
+) -> String {
+    let (left, right, top, bottom) = (70.0, WIDTH - 10.0, 10.0, height - 25.0);
+    let values = || {
+        series
+            .iter()
+            .flat_map(|(_, v)| v.iter().copied())
+            .filter(|v| v.is_finite())
+    };
+    let mut lo = values().fold(f64::INFINITY, f64::min);
+    let mut hi = values().fold(f64::NEG_INFINITY, f64::max);
+    if fill {
+        hi = hi.max(0.0);
+        lo = lo.min(0.0);
+    }
+    if !lo.is_finite() || !hi.is_finite() {
+        return String::from("<p class=\"muted\">No data</p>");
+    }
+    let n = dates.len().max(2);
+    let x = |i: usize| left + (right - left) * i as f64 / (n - 1) as f64;
+
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {WIDTH} {height}\" width=\"100%\" role=\"img\">\
+         <line x1=\"{left}\" y1=\"{bottom}\" x2=\"{right}\" y2=\"{bottom}\" class=\"axis\"/>\
+         <line x1=\"{left}\" y1=\"{top}\" x2=\"{left}\" y2=\"{bottom}\" class=\"axis\"/>"
+    );
+    for v in [lo, f64::midpoint(lo, hi), hi] {
+        let y = scale_y(v, lo, hi, top, bottom);
+        let _ = write!(
+            svg,
+            "<line x1=\"{left}\" y1=\"{y:.1}\" x2=\"{right}\" y2=\"{y:.1}\" class=\"grid\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" class=\"tick\" text-anchor=\"end\">{}</text>",
+            left - 6.0,
+            y + 4.0,
+            fmt(v)
+        );
+    }
+    if let (Some(first), Some(last)) = (dates.first(), dates.last()) {
+        let _ = write!(
+            svg,
+            "<text x=\"{left}\" y=\"{:.1}\" class=\"tick\">{first}</text>\
+             <text x=\"{right}\" y=\"{:.1}\" class=\"tick\" text-anchor=\"end\">{last}</text>",
+            height - 6.0,
+            height - 6.0
+        );
+    }
+    for (k, (name, values)) in series.iter().enumerate() {
+        let color = PALETTE[k % PALETTE.len()];
+        let points: Vec<String> = values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(i, v)| format!("{:.1},{:.1}", x(i), scale_y(*v, lo, hi, top, bottom)))
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+        if fill && k == 0 {
+            let zero = scale_y(0.0, lo, hi, top, bottom);
+            let _ = write!(
+                svg,
+                "<polygon points=\"{left:.1},{zero:.1} {} {:.1},{zero:.1}\" fill=\"{color}\" fill-opacity=\"0.25\"/>",
+                points.join(" "),
+                x(values.len().saturating_sub(1))
+            );
+        }
+        let _ = write!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"{}\"><title>{}</title></polyline>",
+            points.join(" "),
+            if k == 0 { 2.0 } else { 1.2 },
+            escape(name)
+        );
+    }
+    svg.push_str("</svg>");
+    if series.len() > 1 {
+        svg.push_str("<div class=\"legend\">");
+        for (k, (name, _)) in series.iter().enumerate() {
+            let _ = write!(
+                svg,
+                "<span><i style=\"background:{}\"></i>{}</span>",
+                PALETTE[k % PALETTE.len()],
+                escape(name)
+            );
+        }
+        svg.push_str("</div>");
+    }
+    svg
+}
+
+/// Horizontal bar chart of (label, value) pairs around a zero line.
+#[allow(clippy::cast_precision_loss)]
+fn bar_chart(rows: &[(String, f64)], fmt: fn(f64) -> String) -> String {
+    if rows.is_empty() {
+        return String::from("<p class=\"muted\">No data</p>");
+    }
+    let row_h = 22.0;
+    let height = row_h * rows.len() as f64 + 10.0;
+    let (left, right) = (170.0, WIDTH - 80.0);
+    let max = rows.iter().map(|(_, v)| v.abs()).fold(1e-12, f64::max);
+    let lo = if rows.iter().any(|(_, v)| *v < 0.0) { -max } else { 0.0 };
+    let x = |v: f64| left + (v - lo) / (max - lo) * (right - left);
+    let mut svg = format!("<svg viewBox=\"0 0 {WIDTH} {height}\" width=\"100%\" role=\"img\">");
+    for (k, (label, v)) in rows.iter().enumerate() {
+        let y = 5.0 + row_h * k as f64;
+        let (x0, x1) = (x(0.0).min(x(*v)), x(0.0).max(x(*v)));
+        let color = if *v >= 0.0 { "#10b981" } else { "#ef4444" };
+        let _ = write!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" class=\"tick\" text-anchor=\"end\">{}</text>\
+             <rect x=\"{x0:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{color}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" class=\"tick\">{}</text>",
+            left - 6.0,
+            y + 15.0,
+            escape(label),
+            y + 3.0,
+            (x1 - x0).max(1.0),
+            row_h - 6.0,
+            x1 + 4.0,
+            y + 15.0,
+            fmt(*v)
+        );
+    }
+    let zero = x(0.0);
+    let _ = write!(
+        svg,
+        "<line x1=\"{zero:.1}\" y1=\"0\" x2=\"{zero:.1}\" y2=\"{height}\" class=\"axis\"/></svg>"
+    );
+    svg
+}
+
+/// Year x month table of compounded returns, cells shaded by sign and size.
+fn monthly_heatmap(dates: &[NaiveDate], rets: &[f64]) -> String {
+    let mut months: BTreeMap<i32, [Option<f64>; 12]> = BTreeMap::new();
+    for (d, r) in dates.iter().zip(rets) {
+        let cell = &mut months.entry(d.year()).or_insert([None; 12])[d.month0() as usize];
+        *cell = Some(cell.unwrap_or(1.0) * (1.0 + r));
+    }
+    let mut html = String::from("<table class=\"heatmap\"><tr><th>Year</th>");
+    for m in [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ] {
+        let _ = write!(html, "<th>{m}</th>");
+    }
+    html.push_str("<th>Year</th></tr>");
+    for (year, cells) in &months {
+        let _ = write!(html, "<tr><th>{year}</th>");
+        let mut total = 1.0;
+        for cell in cells {
+            match cell {
+                Some(g) => {
+                    let r = g - 1.0;
+                    total *= g;
+                    let _ = write!(html, "<td style=\"{}\">{:.1}%</td>", heat(r), r * 100.0);
+                }
+                None => html.push_str("<td></td>"),
+            }
+        }
+        let _ = write!(
+            html,
+            "<td style=\"{}\"><b>{:.1}%</b></td></tr>",
+            heat(total - 1.0),
+            (total - 1.0) * 100.0
+        );
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn heat(r: f64) -> String {
+    let a = (r.abs() / 0.25).min(1.0) * 0.8 + 0.05;
+    if r >= 0.0 {
+        format!("background:rgba(16,185,129,{a:.2})")
+    } else {
+        format!("background:rgba(239,68,68,{a:.2})")
+    }
+}
+
+/// Annualized Sharpe over a trailing window; `NaN` until the window fills.
+#[allow(clippy::cast_precision_loss)]
+fn rolling_sharpe(rets: &[f64], window: usize, periods_per_year: f64) -> Vec<f64> {
+    (0..rets.len())
+        .map(|i| {
+            if i + 1 < window {
+                return f64::NAN;
+            }
+            let w = &rets[i + 1 - window..=i];
+            let mean = w.iter().sum::<f64>() / window as f64;
+            let var = w.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (window - 1) as f64;
+            if var > 0.0 {
+                mean / var.sqrt() * periods_per_year.sqrt()
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn pct(v: f64) -> String {
+    format!("{:.1}%", v * 100.0)
+}
+
+fn num(v: f64) -> String {
+    format!("{v:.2}")
+}
+
+/// Render `data` as one self-contained HTML page (inline CSS and SVG, no external assets).
+#[must_use]
+pub fn render(data: &ReportData) -> String {
+    let mut equity_series: Vec<(&str, &[f64])> = vec![("strategy", &data.equity)];
+    equity_series.extend(
+        data.benchmarks
+            .iter()
+            .filter(|(_, eq)| eq.len() == data.equity.len())
+            .map(|(n, eq)| (n.as_str(), eq.as_slice())),
+    );
+    let mut peak = f64::MIN;
+    let underwater: Vec<f64> = data
+        .equity
+        .iter()
+        .map(|e| {
+            peak = peak.max(*e);
+            e / peak - 1.0
+        })
+        .collect();
+    let ppy = metrics::periods_per_year(&data.dates);
+    let sharpe = rolling_sharpe(&data.daily_ret, ROLLING_WINDOW, ppy);
+
+    let mut params = String::from("<table class=\"params\">");
+    for (k, v) in &data.params {
+        let _ = write!(
+            params,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(k),
+            escape(v)
+        );
+    }
+    params.push_str("</table>");
+
+    let sections = [
+        (
+            "Equity vs Benchmarks".to_string(),
+            line_chart(&data.dates, &equity_series, 320.0, num, false),
+        ),
+        (
+            "Underwater Drawdown".to_string(),
+            line_chart(&data.dates, &[("drawdown", &underwater)], 200.0, pct, true),
+        ),
+        (
+            "Monthly Returns".to_string(),
+            monthly_heatmap(&data.dates, &data.daily_ret),
+        ),
+        (
+            format!("Rolling Sharpe ({ROLLING_WINDOW} bars, annualized)"),
+            line_chart(&data.dates, &[("rolling sharpe", &sharpe)], 200.0, num, false),
+        ),
+        (
+            "Gross Exposure".to_string(),
+            line_chart(&data.dates, &[("exposure", &data.exposure)], 160.0, num, false),
+        ),
+        (
+            "Open Positions".to_string(),
+            line_chart(
+                &data.dates,
+                &[("positions", &data.num_positions)],
+                160.0,
+                |v| format!("{v:.0}"),
+                false,
+            ),
+        ),
+        (
+            "Attribution by Asset (sum of weight × return)".to_string(),
+            bar_chart(&data.attribution, pct),
+        ),
+        ("Parameters".to_string(), params),
+        (
+            "Summary".to_string(),
+            format!("<pre>{}</pre>", escape(&data.summary)),
+        ),
+    ];
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>\
+         body{{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;margin:24px auto;max-width:1000px;color:#111827}}\
+         h1{{font-size:22px}}h2{{font-size:16px;margin-top:28px;border-bottom:1px solid #e5e7eb;padding-bottom:4px}}\
+         .axis{{stroke:#9ca3af}}.grid{{stroke:#f3f4f6}}.tick{{font-size:11px;fill:#6b7280}}\
+         .legend span{{margin-right:14px;font-size:12px}}.legend i{{display:inline-block;width:10px;height:10px;margin-right:4px}}\
+         table{{border-collapse:collapse;font-size:12px}}th,td{{padding:3px 6px;text-align:right}}\
+         .params th{{text-align:left;color:#6b7280}}.params td{{text-align:left}}\
+         pre{{background:#f9fafb;padding:12px;font-size:12px}}.muted{{color:#9ca3af}}\
+         </style></head><body><h1>{title}</h1><p class=\"muted\">{} → {} · {} bars</p>",
+        data.dates.first().map(ToString::to_string).unwrap_or_default(),
+        data.dates.last().map(ToString::to_string).unwrap_or_default(),
+        data.dates.len(),
+        title = escape(&data.title),
+    );
+    for (heading, body) in sections {
+        let _ = write!(html, "<h2>{}</h2>{body}", escape(&heading));
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Write the report for `data` to `path`.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write(path: &Path, data: &ReportData) -> Result<()> {
+    fs::write(path, render(data)).with_context(|| format!("write {}", path.display()))
+}
+
+/// Regenerate `report.html` in a stored run directory.
+///
+/// # Errors
+/// Returns an error if the run cannot be read or the report cannot be written.
+pub fn execute(run_dir: &str) -> Result<()> {
+    let dir = Path::new(run_dir);
+    let data = ReportData::load_run(dir)?;
+    let path = dir.join("report.html");
+    write(&path, &data)?;
+    println!("📄 Report written to {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use crate::error::Result;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::analyzer::read_signals_file;
+
+/// Daily return series for a set of assets, aligned on their common dates.
+#[derive(Debug, Clone)]
+pub struct AlignedReturns {
+    pub assets: Vec<String>,
+    pub dates: Vec<NaiveDate>,
+    /// `returns[asset][t]`, same order as `assets`
+    pub returns: Vec<Vec<f64>>,
+}
+
+/// Ex-ante risk of a set of dollar positions under a covariance matrix.
+#[derive(Debug, Clone)]
+pub struct PortfolioRisk {
+    /// Daily portfolio volatility in currency units
+    pub daily_volatility: f64,
+    /// Daily portfolio volatility as a fraction of portfolio value
+    pub daily_volatility_percent: f64,
+    /// Annualized volatility as a fraction of portfolio value (sqrt(365))
+    pub annual_volatility_percent: f64,
+    /// Marginal risk contribution per position (d sigma / d value)
+    pub marginal_risk: Vec<f64>,
+    /// Share of total portfolio variance contributed by each position (sums to 1)
+    pub risk_contribution: Vec<f64>,
+    /// Loss if every position moves `stress_sigma` daily standard deviations against us at once,
+    /// i.e. assuming all correlations are 1 regardless of the estimated matrix
+    pub perfect_correlation_stress_loss: f64,
+    /// Same shock, but aggregated with the estimated correlations
+    pub diversified_stress_loss: f64,
+}
+
+/// Load close-to-close returns for `assets` from `signals_{asset}.csv` files and keep the
+/// last `lookback` dates that every asset shares.
+///
+/// # Errors
+/// Returns an error if a signals file cannot be read.
+pub fn load_aligned_returns(
+    signals_dir: &Path,
+    assets: &[String],
+    lookback: usize,
+) -> Result<AlignedReturns> {
+    let mut per_asset: Vec<BTreeMap<NaiveDate, f64>> = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let signals = read_signals_file(&signals_dir.join(format!("signals_{asset}.csv")))?;
+        let rets = signals
+            .windows(2)
+            .filter(|w| w[0].close() > 0.0)
+            .map(|w| (w[1].date(), w[1].close() / w[0].close() - 1.0))
+            .collect();
+        per_asset.push(rets);
+    }
+
+    let mut common: Option<BTreeSet<NaiveDate>> = None;
+    for rets in &per_asset {
+        let keys: BTreeSet<NaiveDate> = rets.keys().copied().collect();
+        common = Some(match common {
+            Some(c) => c.intersection(&keys).copied().collect(),
+            None => keys,
+        });
+    }
+    let mut dates: Vec<NaiveDate> = common.unwrap_or_default().into_iter().collect();
+    if dates.len() > lookback {
+        dates.drain(..dates.len() - lookback);
+    }
+
+    let returns = per_asset
+        .iter()
+        .map(|rets| dates.iter().map(|d| rets[d]).collect())
+        .collect();
+
+    Ok(AlignedReturns {
+        assets: assets.to_vec(),
+        dates,
+        returns,
+    })
+}
+
+/// Sample covariance matrix of aligned return series (n-1 denominator).
+#[must_use]
+#[allow(clippy::cast_precision_loss, clippy::needless_range_loop)]
+pub fn covariance_matrix(returns: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = returns.len();
+    let t = returns.first().map_or(0, Vec::len);
+    let mut cov = vec![vec![0.0; n]; n];
+    if t < 2 {
+        return cov;
+    }
+    let means: Vec<f64> = returns
+        .iter()
+        .map(|r| r.iter().sum::<f64>() / t as f64)
+        .collect();
+    for i in 0..n {
+        for j in i..n {
+            let c = returns[i]
+                .iter()
+                .zip(&returns[j])
+                .map(|(a, b)| (a - means[i]) * (b - means[j]))
+                .sum::<f64>()
+                / (t as f64 - 1.0);
+            cov[i][j] = c;
+            cov[j][i] = c;
+        }
+    }
+    cov
+}
+
+/// Correlation matrix from a covariance matrix; zero-variance rows get 0 off-diagonal.
+#[must_use]
+#[allow(clippy::needless_range_loop)]
+pub fn correlation_matrix(cov: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = cov.len();
+    let sd: Vec<f64> = (0..n).map(|i| cov[i][i].max(0.0).sqrt()).collect();
+    let mut corr = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            corr[i][j] = if i == j {
+                1.0
+            } else if sd[i] > 0.0 && sd[j] > 0.0 {
+                cov[i][j] / (sd[i] * sd[j])
+            } else {
+                0.0
+            };
+        }
+    }
+    corr
+}
+
+/// Ex-ante risk of dollar `positions` (same order as the covariance matrix).
+#[must_use]
+#[allow(clippy::needless_range_loop)]
+pub fn portfolio_risk(
+    positions: &[f64],
+    cov: &[Vec<f64>],
+    portfolio_value: f64,
+    stress_sigma: f64,
+) -> PortfolioRisk {
+    let n = positions.len();
+    let sigma_v: Vec<f64> = (0..n)
+        .map(|i| (0..n).map(|j| cov[i][j] * positions[j]).sum())
+        .collect();
+    let variance: f64 = (0..n).map(|i| positions[i] * sigma_v[i]).sum::<f64>().max(0.0);
+    let daily_volatility = variance.sqrt();
+
+    let marginal_risk = if daily_volatility > 0.0 {
+        sigma_v.iter().map(|x| x / daily_volatility).collect()
+    } else {
+        vec![0.0; n]
+    };
+    let risk_contribution = if variance > 0.0 {
+        (0..n).map(|i| positions[i] * sigma_v[i] / variance).collect()
+    } else {
+        vec![0.0; n]
+    };
+
+    let perfect_correlation_stress_loss = (0..n)
+        .map(|i| positions[i].abs() * cov[i][i].max(0.0).sqrt() * stress_sigma)
+        .sum();
+    let diversified_stress_loss = daily_volatility * stress_sigma;
+
+    let daily_volatility_percent = if portfolio_value > 0.0 {
+        daily_volatility / portfolio_value
+    } else {
+        0.0
+    };
+
+    PortfolioRisk {
+        daily_volatility,
+        daily_volatility_percent,
+        annual_volatility_percent: daily_volatility_percent * 365.0_f64.sqrt(),
+        marginal_risk,
+        risk_contribution,
+        perfect_correlation_stress_loss,
+        diversified_stress_loss,
+    }
+}
+
+/// Stop-out losses aggregated with the estimated correlations, sqrt(sᵀ·ρ·s): their plain sum
+/// when every correlation is 1, and less the more the positions diversify.
+#[must_use]
+#[allow(clippy::needless_range_loop)]
+pub fn correlated_stop_risk(stop_risks: &[f64], corr: &[Vec<f64>]) -> f64 {
+    let n = stop_risks.len();
+    (0..n)
+        .map(|i| (0..n).map(|j| stop_risks[i] * corr[i][j] * stop_risks[j]).sum::<f64>())
+        .sum::<f64>()
+        .max(0.0)
+        .sqrt()
+}
+
+/// Portfolio heat in percent of `portfolio_value`: the larger of the correlated stop risk and
+/// the diversified stress loss of `risk`, so the cap follows the estimated correlations.
+#[must_use]
+pub fn portfolio_heat(
+    stop_risks: &[f64],
+    corr: &[Vec<f64>],
+    risk: &PortfolioRisk,
+    portfolio_value: f64,
+) -> f64 {
+    if portfolio_value <= 0.0 {
+        return 0.0;
+    }
+    correlated_stop_risk(stop_risks, corr).max(risk.diversified_stress_loss) / portfolio_value
+        * 100.0
+}
+
+/// Scale factor (<= 1) that brings portfolio heat back under `max_heat`.
+#[must_use]
+pub fn heat_scale(heat: f64, max_heat: f64) -> f64 {
+    if max_heat > 0.0 && heat > max_heat {
+        max_heat / heat
+    } else {
+        1.0
+    }
+}
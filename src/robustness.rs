@@ -0,0 +1,318 @@
+use crate::error::{Context, Error, Required, Result};
+use csv::WriterBuilder;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::fs;
+
+use crate::RobustnessArgs;
+use crate::metrics::{self, Metrics, percentile};
+use crate::strategy::{self, Trade};
+
+/// Resampling scheme used to generate alternative histories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Politis-Romano stationary block bootstrap of daily portfolio returns
+    BlockBootstrap,
+    /// Random permutation of the trade ledger
+    TradeShuffle,
+    /// Backtest returns from a random start date to the end
+    StartDate,
+}
+
+impl Method {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BlockBootstrap => "block_bootstrap",
+            Self::TradeShuffle => "trade_shuffle",
+            Self::StartDate => "random_start",
+        }
+    }
+
+    /// Reordering trades leaves compounded return unchanged; only path metrics (drawdown) vary.
+    #[must_use]
+    pub const fn path_only(self) -> bool {
+        matches!(self, Self::TradeShuffle)
+    }
+}
+
+/// Percentile summary of one metric across simulations.
+#[derive(Debug, Clone, Copy)]
+pub struct Interval {
+    pub lower: f64,
+    pub median: f64,
+    pub upper: f64,
+    pub mean: f64,
+}
+
+/// Distribution summary for one resampling method.
+#[derive(Debug, Clone)]
+pub struct MethodSummary {
+    pub method: Method,
+    pub samples: Vec<Metrics>,
+    pub cagr: Interval,
+    pub sharpe: Interval,
+    pub max_drawdown: Interval,
+    /// Share of simulations whose total return is at or below `-loss_threshold`
+    pub prob_loss: f64,
+    /// Share of simulations whose max drawdown reaches `loss_threshold`
+    pub prob_drawdown: f64,
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn interval(values: impl Iterator<Item = f64>, confidence: f64) -> Interval {
+    let mut v: Vec<f64> = values.filter(|x| x.is_finite()).collect();
+    v.sort_by(f64::total_cmp);
+    let tail = (1.0 - confidence) / 2.0;
+    Interval {
+        lower: percentile(&v, tail),
+        median: percentile(&v, 0.5),
+        upper: percentile(&v, 1.0 - tail),
+        mean: if v.is_empty() {
+            0.0
+        } else {
+            v.iter().sum::<f64>() / v.len() as f64
+        },
+    }
+}
+
+/// Stationary block bootstrap: blocks start at random offsets and have geometric length with
+/// mean `mean_block`; the series wraps around at the end.
+#[must_use]
+pub fn stationary_bootstrap(rets: &[f64], mean_block: f64, rng: &mut impl Rng) -> Vec<f64> {
+    let n = rets.len();
+    if n == 0 {
+        return vec![];
+    }
+    let p_new = 1.0 / mean_block.max(1.0);
+    let mut out = Vec::with_capacity(n);
+    let mut idx = rng.random_range(0..n);
+    for _ in 0..n {
+        out.push(rets[idx]);
+        idx = if rng.random::<f64>() < p_new {
+            rng.random_range(0..n)
+        } else {
+            (idx + 1) % n
+        };
+    }
+    out
+}
+
+/// Trade contributions replayed one after another in a random order, padded with flat days
+/// to `days`. Overlapping trades are treated as sequential, so only drawdown is meaningful.
+#[must_use]
+pub fn shuffled_trade_path(trades: &[Trade], days: usize, rng: &mut impl Rng) -> Vec<f64> {
+    let mut contributions: Vec<f64> = trades.iter().map(|t| t.contribution).collect();
+    contributions.shuffle(rng);
+    let mut rets = contributions;
+    rets.resize(days.max(rets.len()), 0.0);
+    rets
+}
+
+fn simulate(
+    method: Method,
+    daily_ret: &[f64],
+    trades: &[Trade],
+    periods_per_year: f64,
+    args: &RobustnessArgs,
+    seed: u64,
+) -> Metrics {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let rets = match method {
+        Method::BlockBootstrap => {
+            stationary_bootstrap(daily_ret, args.block_length.unwrap_or(10.0), &mut rng)
+        }
+        Method::TradeShuffle => shuffled_trade_path(trades, daily_ret.len(), &mut rng),
+        Method::StartDate => {
+            let min_days = args.min_days.unwrap_or(90).min(daily_ret.len());
+            let start = rng.random_range(0..=daily_ret.len() - min_days);
+            daily_ret[start..].to_vec()
+        }
+    };
+    metrics::compute(&rets, &[], periods_per_year, &[], &[])
+}
+
+/// Run `simulations` resamples for every method.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn analyze(
+    daily_ret: &[f64],
+    trades: &[Trade],
+    periods_per_year: f64,
+    args: &RobustnessArgs,
+) -> Vec<MethodSummary> {
+    let simulations = args.simulations.unwrap_or(1000);
+    let seed = args.seed.unwrap_or(42);
+    let confidence = args.confidence.unwrap_or(0.90);
+    let loss = args.loss_threshold.unwrap_or(0.20);
+
+    [Method::BlockBootstrap, Method::TradeShuffle, Method::StartDate]
+        .into_iter()
+        .enumerate()
+        .map(|(m, method)| {
+            // Independent, reproducible stream per (method, simulation)
+            let samples: Vec<Metrics> = (0..simulations)
+                .into_par_iter()
+                .map(|k| {
+                    let s = seed
+                        .wrapping_mul(1_000_003)
+                        .wrapping_add((m * simulations + k) as u64);
+                    simulate(method, daily_ret, trades, periods_per_year, args, s)
+                })
+                .collect();
+            let n = samples.len().max(1) as f64;
+            MethodSummary {
+                method,
+                cagr: interval(samples.iter().map(|s| s.cagr), confidence),
+                sharpe: interval(samples.iter().map(|s| s.sharpe), confidence),
+                max_drawdown: interval(samples.iter().map(|s| s.max_drawdown), confidence),
+                prob_loss: samples.iter().filter(|s| s.total_return <= -loss).count() as f64 / n,
+                prob_drawdown: samples.iter().filter(|s| s.max_drawdown >= loss).count() as f64 / n,
+                samples,
+            }
+        })
+        .collect()
+}
+
+/// Execute robustness analysis of the configured strategy.
+///
+/// # Errors
+/// Returns an error if the backtest fails or results cannot be written.
+#[allow(clippy::too_many_lines)]
+pub fn execute(args: &RobustnessArgs) -> Result<()> {
+    let out_dir = args.base.out.as_ref().required("out")?;
+    fs::create_dir_all(out_dir).context("create out dir")?;
+
+    let data = strategy::load_market_data(&args.base)?;
+    let bt = strategy::run_backtest(&data, &args.base)?;
+    if bt.daily_ret.len() < 2 {
+        return Err(Error::data("not enough backtest days for resampling"));
+    }
+    // Day 0 has no return by construction
+    let daily_ret = &bt.daily_ret[1..];
+    let confidence = args.confidence.unwrap_or(0.90);
+    let loss = args.loss_threshold.unwrap_or(0.20);
+
+    println!(
+        "🎲 Robustness: {} simulations per method, {} days, {} trades, seed {}",
+        args.simulations.unwrap_or(1000),
+        daily_ret.len(),
+        bt.trades.len(),
+        args.seed.unwrap_or(42)
+    );
+    let periods_per_year = metrics::periods_per_year(&bt.dates);
+    let summaries = analyze(daily_ret, &bt.trades, periods_per_year, args);
+
+    let mut wtr = WriterBuilder::new().from_path(out_dir.join("robustness_samples.csv"))?;
+    wtr.write_record([
+        "method",
+        "simulation",
+        "total_return",
+        "cagr",
+        "sharpe",
+        "max_drawdown",
+    ])?;
+    for s in &summaries {
+        for (k, m) in s.samples.iter().enumerate() {
+            wtr.write_record(&[
+                s.method.name().to_string(),
+                (k + 1).to_string(),
+                format!("{:.6}", m.total_return),
+                format!("{:.6}", m.cagr),
+                format!("{:.4}", m.sharpe),
+                format!("{:.6}", m.max_drawdown),
+            ])?;
+        }
+    }
+    wtr.flush()?;
+
+    let mut wtr = WriterBuilder::new().from_path(out_dir.join("robustness.csv"))?;
+    wtr.write_record([
+        "method", "metric", "lower", "median", "upper", "mean",
+    ])?;
+    for s in &summaries {
+        let intervals = [
+            ("cagr", s.cagr),
+            ("sharpe", s.sharpe),
+            ("max_drawdown", s.max_drawdown),
+        ];
+        let probabilities = [
+            ("prob_total_loss", s.prob_loss),
+            ("prob_drawdown", s.prob_drawdown),
+        ];
+        let skip = usize::from(s.method.path_only()) * 2;
+        for (metric, iv) in intervals.into_iter().skip(skip) {
+            wtr.write_record(&[
+                s.method.name().to_string(),
+                metric.to_string(),
+                format!("{:.6}", iv.lower),
+                format!("{:.6}", iv.median),
+                format!("{:.6}", iv.upper),
+                format!("{:.6}", iv.mean),
+            ])?;
+        }
+        for (metric, p) in probabilities.into_iter().skip(skip / 2) {
+            wtr.write_record(&[
+                s.method.name().to_string(),
+                metric.to_string(),
+                String::new(),
+                format!("{p:.6}"),
+                String::new(),
+                String::new(),
+            ])?;
+        }
+    }
+    wtr.flush()?;
+
+    let pct = confidence * 100.0;
+    let mut report = format!(
+        "Backtest: CAGR {:.2}%, Sharpe {:.2}, Max DD {:.2}%\nConfidence: {:.0}%\nLoss Threshold: {:.0}%\n",
+        bt.metrics.cagr * 100.0,
+        bt.metrics.sharpe,
+        bt.metrics.max_drawdown * 100.0,
+        pct,
+        loss * 100.0
+    );
+    for s in &summaries {
+        report.push_str(&format!("\n[{}]\n", s.method.name()));
+        if s.method.path_only() {
+            report.push_str("CAGR / Sharpe: unchanged by trade order\n");
+        } else {
+            report.push_str(&format!(
+                "CAGR: {:.2}% .. {:.2}% (median {:.2}%)\nSharpe: {:.2} .. {:.2} (median {:.2})\n",
+                s.cagr.lower * 100.0,
+                s.cagr.upper * 100.0,
+                s.cagr.median * 100.0,
+                s.sharpe.lower,
+                s.sharpe.upper,
+                s.sharpe.median
+            ));
+        }
+        report.push_str(&format!(
+            "Max Drawdown: {:.2}% .. {:.2}% (median {:.2}%)\n",
+            s.max_drawdown.lower * 100.0,
+            s.max_drawdown.upper * 100.0,
+            s.max_drawdown.median * 100.0
+        ));
+        if !s.method.path_only() {
+            report.push_str(&format!(
+                "P(total return <= -{:.0}%): {:.1}%\n",
+                loss * 100.0,
+                s.prob_loss * 100.0
+            ));
+        }
+        report.push_str(&format!(
+            "P(max drawdown >= {:.0}%): {:.1}%\n",
+            loss * 100.0,
+            s.prob_drawdown * 100.0
+        ));
+    }
+    fs::write(out_dir.join("robustness.txt"), &report)?;
+    println!();
+    println!("{report}");
+    println!("💾 Intervals and samples written to {}", out_dir.display());
+
+    Ok(())
+}
@@ -0,0 +1,398 @@
+use crate::error::{Context, Error, Result};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::StrategyArgs;
+use crate::metrics::Metrics;
+use crate::report;
+
+/// Manifest file written into every run directory
+pub const MANIFEST: &str = "run.json";
+
+/// One input CSV as it was read by the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputFile {
+    pub path: PathBuf,
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+/// Everything needed to identify and reproduce one strategy run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// `YYYYMMDD-HHMMSS-<config hash prefix>`, also the run directory name
+    pub id: String,
+    /// RFC 3339 creation time (UTC)
+    pub created_at: String,
+    pub crate_version: String,
+    /// Commit of the source tree the binary was built from, if it was built in a git checkout
+    pub git_commit: Option<String>,
+    /// Tracked files had uncommitted changes at build time
+    pub git_dirty: bool,
+    /// SHA-256 over the resolved args (less output and registry settings) and input hashes;
+    /// equal hashes mean equal experiments
+    pub config_hash: String,
+    /// Fully resolved strategy arguments
+    pub args: StrategyArgs,
+    pub inputs: Vec<InputFile>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Headline metrics (non-finite values stored as null)
+    pub metrics: Value,
+    /// Output files archived next to the manifest
+    pub outputs: Vec<String>,
+}
+
+/// SHA-256 (hex) and size of a file.
+///
+/// # Errors
+/// Returns an error if the file cannot be read.
+pub fn hash_file(path: &Path) -> Result<(String, u64)> {
+    let mut file = fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let bytes = std::io::copy(&mut file, &mut hasher)?;
+    Ok((format!("{:x}", hasher.finalize()), bytes))
+}
+
+/// Every CSV the strategy reads: BTC, the assets and the optional extra benchmark.
+fn input_paths(args: &StrategyArgs) -> Vec<PathBuf> {
+    args.btc
+        .iter()
+        .chain(args.assets.iter().flatten())
+        .chain(args.benchmark.iter())
+        .cloned()
+        .collect()
+}
+
+/// Config hash of resolved `args` and their input files. Where outputs go and whether the
+/// run is recorded do not change the experiment, so `out`, `runs_dir` and `record` are left
+/// out.
+fn config_hash(args: &StrategyArgs, inputs: &[InputFile]) -> Result<String> {
+    let experiment = StrategyArgs {
+        out: None,
+        runs_dir: None,
+        record: None,
+        ..args.clone()
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&experiment)?);
+    for input in inputs {
+        hasher.update(input.sha256.as_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Archive one finished run: copy `outputs` into a new directory under `runs_dir` and write
+/// its manifest.
+///
+/// # Errors
+/// Returns an error if an input cannot be hashed or the run directory cannot be written.
+pub fn record(
+    runs_dir: &Path,
+    args: &StrategyArgs,
+    dates: &[NaiveDate],
+    metrics: &Metrics,
+    outputs: &[PathBuf],
+) -> Result<RunManifest> {
+    let inputs = input_paths(args)
+        .into_iter()
+        .map(|path| {
+            let (sha256, bytes) = hash_file(&path)?;
+            Ok(InputFile {
+                path,
+                sha256,
+                bytes,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let config_hash = config_hash(args, &inputs)?;
+    let now = Utc::now();
+
+    let base = format!("{}-{}", now.format("%Y%m%d-%H%M%S"), &config_hash[..8]);
+    let mut id = base.clone();
+    let mut n = 1;
+    while runs_dir.join(&id).exists() {
+        n += 1;
+        id = format!("{base}-{n}");
+    }
+    let dir = runs_dir.join(&id);
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+
+    let mut archived = Vec::new();
+    for path in outputs.iter().filter(|p| p.is_file()) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        fs::copy(path, dir.join(&name)).with_context(|| format!("archive {}", path.display()))?;
+        archived.push(name);
+    }
+    archived.sort();
+
+    let manifest = RunManifest {
+        id,
+        created_at: now.to_rfc3339(),
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        // Captured by build.rs when the binary was compiled
+        git_commit: option_env!("GIT_COMMIT").map(str::to_string),
+        git_dirty: option_env!("GIT_DIRTY") == Some("true"),
+        config_hash,
+        args: args.clone(),
+        inputs,
+        start_date: dates.first().copied(),
+        end_date: dates.last().copied(),
+        metrics: serde_json::to_value(metrics)?,
+        outputs: archived,
+    };
+    fs::write(dir.join(MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+    Ok(manifest)
+}
+
+/// Every run in `runs_dir`, oldest first. Directories without a readable manifest are skipped.
+///
+/// # Errors
+/// Returns an error if `runs_dir` exists but cannot be listed.
+pub fn load_all(runs_dir: &Path) -> Result<Vec<RunManifest>> {
+    if !runs_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut runs: Vec<RunManifest> = fs::read_dir(runs_dir)?
+        .flatten()
+        .filter_map(|e| fs::read_to_string(e.path().join(MANIFEST)).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect();
+    runs.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(runs)
+}
+
+/// The run whose id is `id` or uniquely starts with it.
+///
+/// # Errors
+/// Returns an error if no run or more than one run matches.
+pub fn find(runs_dir: &Path, id: &str) -> Result<RunManifest> {
+    let mut matches: Vec<RunManifest> = load_all(runs_dir)?
+        .into_iter()
+        .filter(|r| r.id.starts_with(id))
+        .collect();
+    match matches.len() {
+        0 => Err(Error::config(format!(
+            "no run matching '{id}' in {}",
+            runs_dir.display()
+        ))),
+        1 => Ok(matches.remove(0)),
+        n => Err(Error::config(format!(
+            "'{id}' matches {n} runs; use a longer prefix"
+        ))),
+    }
+}
+
+fn metric(run: &RunManifest, name: &str) -> Option<f64> {
+    run.metrics.get(name).and_then(Value::as_f64)
+}
+
+fn fmt_pct(v: Option<f64>) -> String {
+    v.map_or_else(|| "-".to_string(), |v| format!("{:.2}%", v * 100.0))
+}
+
+fn short_commit(run: &RunManifest) -> String {
+    let commit = run.git_commit.as_deref().map_or("unknown", |c| &c[..c.len().min(8)]);
+    format!("{commit}{}", if run.git_dirty { "+dirty" } else { "" })
+}
+
+fn period(run: &RunManifest) -> String {
+    match (run.start_date, run.end_date) {
+        (Some(s), Some(e)) => format!("{s} → {e}"),
+        _ => "-".to_string(),
+    }
+}
+
+/// Command line that re-runs `args` (flags in alphabetical order).
+#[must_use]
+pub fn command_line(args: &StrategyArgs) -> String {
+    let mut cmd = format!("{} strategy", env!("CARGO_PKG_NAME"));
+    let Ok(Value::Object(map)) = serde_json::to_value(args) else {
+        return cmd;
+    };
+    for (key, value) in map {
+        let values = match value {
+            Value::Null => continue,
+            Value::Array(items) => items.iter().map(flag_value).collect::<Vec<_>>().join(" "),
+            other => flag_value(&other),
+        };
+        cmd.push_str(&format!(" --{} {values}", key.replace('_', "-")));
+    }
+    cmd
+}
+
+fn flag_value(v: &Value) -> String {
+    match v {
+        Value::String(s) if s.contains(char::is_whitespace) => format!("'{s}'"),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Print every recorded run.
+///
+/// # Errors
+/// Returns an error if the registry cannot be read.
+pub fn list(runs_dir: &Path) -> Result<()> {
+    let runs = load_all(runs_dir)?;
+    if runs.is_empty() {
+        println!("No runs recorded in {}", runs_dir.display());
+        return Ok(());
+    }
+    println!("\n🗂️  RECORDED RUNS ({})", runs_dir.display());
+    println!("{}", "=".repeat(118));
+    println!(
+        "{:<26} {:<16} {:<25} {:>6} {:>10} {:>7} {:>9}  Mode",
+        "Run", "Commit", "Period", "Assets", "Return", "Sharpe", "Max DD"
+    );
+    println!("{}", "-".repeat(118));
+    for run in &runs {
+        println!(
+            "{:<26} {:<16} {:<25} {:>6} {:>10} {:>7.2} {:>9}  {}",
+            run.id,
+            short_commit(run),
+            period(run),
+            run.args.assets.as_ref().map_or(0, Vec::len),
+            fmt_pct(metric(run, "total_return")),
+            metric(run, "sharpe").unwrap_or(0.0),
+            fmt_pct(metric(run, "max_drawdown")),
+            flag_value(&serde_json::to_value(run.args.mode).unwrap_or_default())
+        );
+    }
+    Ok(())
+}
+
+/// Print one run's metadata, input integrity, parameters and metrics.
+///
+/// # Errors
+/// Returns an error if the run cannot be found.
+pub fn show(runs_dir: &Path, id: &str) -> Result<()> {
+    let run = find(runs_dir, id)?;
+    println!("\n🗂️  RUN {}", run.id);
+    println!("{}", "=".repeat(80));
+    println!("Created:        {}", run.created_at);
+    println!("Version:        {} ({})", run.crate_version, short_commit(&run));
+    println!("Period:         {}", period(&run));
+    println!("Config hash:    {}", run.config_hash);
+    println!("Directory:      {}", runs_dir.join(&run.id).display());
+
+    println!("\n📥 Inputs:");
+    for input in &run.inputs {
+        let status = match hash_file(&input.path) {
+            Ok((sha, _)) if sha == input.sha256 => "✅ unchanged",
+            Ok(_) => "⚠️  changed",
+            Err(_) => "❌ missing",
+        };
+        println!(
+            "   {:<12} {}  {}",
+            status,
+            &input.sha256[..12],
+            input.path.display()
+        );
+    }
+
+    println!("\n⚙️  Parameters:");
+    for (k, v) in report::param_rows(&run.args) {
+        println!("   {k:<18} {v}");
+    }
+
+    if let Ok(metrics) = fs::read_to_string(runs_dir.join(&run.id).join("metrics.txt")) {
+        println!("\n📊 Metrics:\n{metrics}");
+    }
+    println!("🔁 Reproduce with:\n   {}", command_line(&run.args));
+    Ok(())
+}
+
+/// Rows of keys present in either map, with "-" for a missing side.
+fn side_by_side(
+    a: &BTreeMap<String, String>,
+    b: &BTreeMap<String, String>,
+) -> Vec<(String, String, String)> {
+    let missing = || "-".to_string();
+    a.keys()
+        .chain(b.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|k| {
+            (
+                k.clone(),
+                a.get(k).cloned().unwrap_or_else(missing),
+                b.get(k).cloned().unwrap_or_else(missing),
+            )
+        })
+        .collect()
+}
+
+/// Print the rows whose two values differ, or note that all match.
+fn print_changes(rows: &[(String, String, String)]) {
+    let changed: Vec<_> = rows.iter().filter(|(_, x, y)| x != y).collect();
+    if changed.is_empty() {
+        println!("   (identical)");
+    }
+    for (label, x, y) in changed {
+        println!("   {label:<22} {x:<28} {y}");
+    }
+}
+
+/// Print what differs between two runs: code, period, inputs, parameters and metrics.
+///
+/// # Errors
+/// Returns an error if either run cannot be found.
+pub fn diff(runs_dir: &Path, a: &str, b: &str) -> Result<()> {
+    let (a, b) = (find(runs_dir, a)?, find(runs_dir, b)?);
+    println!("\n🔀 {} ⇄ {}", a.id, b.id);
+    println!("{}", "=".repeat(80));
+    if a.config_hash == b.config_hash {
+        println!("Same config hash: identical parameters and input data");
+    }
+    println!("\n🧬 Code and period:");
+    print_changes(&[
+        (
+            "version".to_string(),
+            format!("{} {}", a.crate_version, short_commit(&a)),
+            format!("{} {}", b.crate_version, short_commit(&b)),
+        ),
+        ("period".to_string(), period(&a), period(&b)),
+    ]);
+
+    println!("\n📥 Inputs:");
+    let hashes = |r: &RunManifest| -> BTreeMap<String, String> {
+        r.inputs
+            .iter()
+            .map(|i| (i.path.display().to_string(), i.sha256[..12].to_string()))
+            .collect()
+    };
+    print_changes(&side_by_side(&hashes(&a), &hashes(&b)));
+
+    println!("\n⚙️  Parameters:");
+    let params = |r: &RunManifest| -> BTreeMap<String, String> {
+        report::param_rows(&r.args).into_iter().collect()
+    };
+    print_changes(&side_by_side(&params(&a), &params(&b)));
+
+    println!("\n📊 Metrics:");
+    let empty = serde_json::Map::new();
+    let (ma, mb) = (
+        a.metrics.as_object().unwrap_or(&empty),
+        b.metrics.as_object().unwrap_or(&empty),
+    );
+    let changed: Vec<(&String, f64, f64)> = ma
+        .iter()
+        .filter_map(|(key, va)| Some((key, va.as_f64()?, mb.get(key)?.as_f64()?)))
+        .filter(|(_, x, y)| (x - y).abs() > 1e-12)
+        .collect();
+    if changed.is_empty() {
+        println!("   (identical)");
+    } else {
+        println!("   {:<22} {:>14} {:>14} {:>14}", "", "A", "B", "B - A");
+    }
+    for (key, x, y) in changed {
+        println!("   {key:<22} {x:>14.6} {y:>14.6} {:>+14.6}", y - x);
+    }
+    Ok(())
+}
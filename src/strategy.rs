@@ -1,12 +1,24 @@
-use anyhow::{Context, Result, bail};
+use crate::error::{Context, Error, Required, Result};
 use chrono::NaiveDate;
 use csv::{ReaderBuilder, WriterBuilder};
 use itertools::Itertools;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use statrs::statistics::Statistics;
-use std::{collections::BTreeMap, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::StrategyArgs;
+use crate::attribution::{self, Attribution, Contribution, SignalState};
+use crate::benchmark::{self, Benchmark};
+use crate::metrics::{self, Metrics};
+use crate::{RankBy, SignalMode, StrategyArgs};
+use crate::regime::{self, Regime, RegimeConfig, RegimeState};
+use crate::report::{self, ReportData};
+use crate::runs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Row {
@@ -18,14 +30,38 @@ pub struct Row {
     #[serde(default)]
     low: Option<f64>,
     close: f64,
+    #[serde(default)]
+    volume: Option<f64>,
 }
 
 #[derive(Clone)]
 pub struct Series {
     dates: Vec<NaiveDate>,
+    open: Vec<Option<f64>>,
     close: Vec<f64>,
     high: Vec<Option<f64>>,
     low: Vec<Option<f64>>,
+    volume: Vec<Option<f64>>,
+}
+
+impl Series {
+    #[must_use]
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    /// The observed bar at row `i`.
+    #[must_use]
+    pub fn bar(&self, i: usize) -> AlignedBar {
+        AlignedBar {
+            open: self.open[i],
+            close: self.close[i],
+            high: self.high[i],
+            low: self.low[i],
+            volume: self.volume[i],
+            filled: false,
+        }
+    }
 }
 
 /// Read a time series from a CSV file.
@@ -33,24 +69,41 @@ pub struct Series {
 /// # Errors
 /// Returns an error if the file cannot be read or parsed.
 pub fn read_series(path: &PathBuf) -> Result<Series> {
-    let mut rdr = ReaderBuilder::new().trim(csv::Trim::All).from_path(path)?;
+    series_from_csv(ReaderBuilder::new().trim(csv::Trim::All).from_path(path)?)
+}
+
+/// Parse a time series from CSV text (header line included).
+///
+/// # Errors
+/// Returns an error if a row cannot be parsed.
+pub fn parse_series(csv: &[u8]) -> Result<Series> {
+    series_from_csv(ReaderBuilder::new().trim(csv::Trim::All).from_reader(csv))
+}
+
+fn series_from_csv<R: std::io::Read>(mut rdr: csv::Reader<R>) -> Result<Series> {
     let mut dates = Vec::new();
+    let mut open = Vec::new();
     let mut close = Vec::new();
     let mut high = Vec::new();
     let mut low = Vec::new();
+    let mut volume = Vec::new();
 
     for rec in rdr.deserialize::<Row>() {
         let r = rec?;
         dates.push(r.date);
+        open.push(r.open);
         close.push(r.close);
         high.push(r.high);
         low.push(r.low);
+        volume.push(r.volume);
     }
     Ok(Series {
         dates,
+        open,
         close,
         high,
         low,
+        volume,
     })
 }
 
@@ -74,6 +127,23 @@ pub fn rolling_ma(x: &[f64], w: usize) -> Vec<Option<f64>> {
     out
 }
 
+/// Rolling mean over optional values; a window with any missing value yields `None`.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn rolling_ma_opt(x: &[Option<f64>], w: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; x.len()];
+    if w == 0 {
+        return out;
+    }
+    for i in (w - 1)..x.len() {
+        let window = &x[i + 1 - w..=i];
+        if window.iter().all(Option::is_some) {
+            out[i] = Some(window.iter().flatten().sum::<f64>() / w as f64);
+        }
+    }
+    out
+}
+
 #[must_use]
 pub fn true_range(high: f64, low: f64, prev_close: f64) -> f64 {
     (high - low)
@@ -114,23 +184,27 @@ pub fn rolling_atr(
 }
 
 #[must_use]
-#[allow(clippy::cast_precision_loss)]
 pub fn rolling_std(returns: &[f64], w: usize) -> Vec<Option<f64>> {
     let mut out = vec![None; returns.len()];
     for i in 0..returns.len() {
         if i + 1 >= w {
-            let s = &returns[i + 1 - w..=i];
-            let mean = s.mean();
-            let var = s.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (s.len() as f64).max(1.0);
-            out[i] = Some(var.sqrt());
+            out[i] = Some(population_std(&returns[i + 1 - w..=i]));
         }
     }
     out
 }
 
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[allow(clippy::cast_precision_loss)]
+fn population_std(s: &[f64]) -> f64 {
+    let mean = s.mean();
+    let var = s.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (s.len() as f64).max(1.0);
+    var.sqrt()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DailySignal {
     date: NaiveDate,
+    #[serde(deserialize_with = "null_as_nan")]
     price: f64,
     ma_short: Option<f64>,
     ma_long: Option<f64>,
@@ -143,78 +217,928 @@ pub struct DailySignal {
     score: usize,
     raw_weight: f64,
     stop_level: Option<f64>,
+    adv: Option<f64>,
+    /// False before listing and inside gaps longer than the forward-fill limit
+    available: bool,
+}
+
+impl DailySignal {
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+    pub fn price(&self) -> f64 {
+        self.price
+    }
+    pub fn ma_short(&self) -> Option<f64> {
+        self.ma_short
+    }
+    pub fn ma_long(&self) -> Option<f64> {
+        self.ma_long
+    }
+    pub fn rs(&self) -> Option<f64> {
+        self.rs
+    }
+    pub fn rs_ma_short(&self) -> Option<f64> {
+        self.rs_ma_short
+    }
+    pub fn rs_ma_long(&self) -> Option<f64> {
+        self.rs_ma_long
+    }
+    pub fn trend_bull(&self) -> bool {
+        self.trend_bull
+    }
+    pub fn mom_bull(&self) -> bool {
+        self.mom_bull
+    }
+    pub fn rs_bull(&self) -> bool {
+        self.rs_bull
+    }
+    pub fn score(&self) -> usize {
+        self.score
+    }
+    pub fn raw_weight(&self) -> f64 {
+        self.raw_weight
+    }
+    pub fn stop_level(&self) -> Option<f64> {
+        self.stop_level
+    }
+    pub fn adv(&self) -> Option<f64> {
+        self.adv
+    }
+    pub fn available(&self) -> bool {
+        self.available
+    }
+
+    /// Placeholder for a date on which the asset cannot be traded.
+    #[must_use]
+    pub fn unavailable(date: NaiveDate) -> Self {
+        Self {
+            date,
+            price: f64::NAN,
+            ..Self::default()
+        }
+    }
 }
 
+/// `serde_json` writes NaN as `null`; read it back as NaN.
+fn null_as_nan<'de, D: serde::Deserializer<'de>>(d: D) -> std::result::Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(d)?.unwrap_or(f64::NAN))
+}
+
+/// Union of every date in `series`, starting no earlier than the first series (BTC).
 #[must_use]
-pub fn intersect_dates(series: &[Series]) -> Vec<NaiveDate> {
+pub fn union_dates(series: &[Series]) -> Vec<NaiveDate> {
     use std::collections::BTreeSet;
-    if series.is_empty() {
+    let Some(start) = series.first().and_then(|s| s.dates.first().copied()) else {
         return vec![];
+    };
+    let all: BTreeSet<NaiveDate> = series
+        .iter()
+        .flat_map(|s| s.dates.iter().copied())
+        .filter(|d| *d >= start)
+        .collect();
+    all.into_iter().collect()
+}
+
+/// One bar mapped onto the union calendar.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AlignedBar {
+    #[serde(default)]
+    pub open: Option<f64>,
+    pub close: f64,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub volume: Option<f64>,
+    /// True if carried forward from an earlier date
+    pub filled: bool,
+}
+
+/// Forward-fill position of one series on the union calendar: the last observed bar and
+/// the number of calendar dates since it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForwardFill {
+    last: Option<(AlignedBar, usize)>,
+}
+
+impl ForwardFill {
+    /// Advance one calendar date given the series' own bar on that date, if any.
+    pub fn step(&mut self, bar: Option<AlignedBar>, max_fill: usize) -> Option<AlignedBar> {
+        if let Some(bar) = bar {
+            self.last = Some((bar, 0));
+            return Some(bar);
+        }
+        let (bar, run) = self.last.as_mut()?;
+        *run += 1;
+        (*run <= max_fill).then_some(AlignedBar {
+            open: None,
+            high: None,
+            low: None,
+            filled: true,
+            ..*bar
+        })
     }
-    let mut iter = series
+}
+
+/// Map `ser` onto `calendar`: `None` before the first observation (listing) and once a gap
+/// runs longer than `max_fill` calendar dates; shorter gaps repeat the last close and volume
+/// with no open or intraday range.
+#[must_use]
+pub fn align_forward_fill(
+    ser: &Series,
+    calendar: &[NaiveDate],
+    max_fill: usize,
+) -> Vec<Option<AlignedBar>> {
+    let idx: BTreeMap<NaiveDate, usize> =
+        ser.dates.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+    let mut fill = ForwardFill::default();
+    calendar
         .iter()
-        .map(|s| s.dates.iter().copied().collect::<BTreeSet<_>>());
-    let Some(mut base) = iter.next() else {
-        return vec![];
-    };
-    for s in iter {
-        base = base.intersection(&s).copied().collect();
+        .map(|d| fill.step(idx.get(d).map(|&j| ser.bar(j)), max_fill))
+        .collect()
+}
+
+/// Write the per-asset tradeable mask (1 = tradeable) on the calendar to `availability.csv`.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_availability(
+    path: &Path,
+    dates: &[NaiveDate],
+    per_asset_signals: &BTreeMap<String, Vec<DailySignal>>,
+) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    let mut header = vec!["date".to_string()];
+    header.extend(per_asset_signals.keys().cloned());
+    wtr.write_record(&header)?;
+    for (i, d) in dates.iter().enumerate() {
+        let mut rec = vec![d.to_string()];
+        rec.extend(
+            per_asset_signals
+                .values()
+                .map(|sigs| u8::from(sigs[i].available).to_string()),
+        );
+        wtr.write_record(&rec)?;
     }
-    base.into_iter().collect()
+    wtr.flush()?;
+    Ok(())
 }
 
-/// Execute the momentum strategy analysis.
+/// Columns of `signals_<asset>.csv`.
+const SIGNAL_COLUMNS: [&str; 14] = [
+    "date",
+    "close",
+    "ma_short",
+    "ma_long",
+    "rs",
+    "rs_ma_short",
+    "rs_ma_long",
+    "trend_bull",
+    "mom_bull",
+    "rs_bull",
+    "score",
+    "raw_weight",
+    "stop_level",
+    "adv",
+];
+
+fn signal_record(s: &DailySignal) -> [String; 14] {
+    [
+        s.date.to_string(),
+        format!("{:.8}", s.price),
+        s.ma_short.map(|v| format!("{v:.8}")).unwrap_or_default(),
+        s.ma_long.map(|v| format!("{v:.8}")).unwrap_or_default(),
+        s.rs.map(|v| format!("{v:.8}")).unwrap_or_default(),
+        s.rs_ma_short
+            .map(|v| format!("{v:.8}"))
+            .unwrap_or_default(),
+        s.rs_ma_long
+            .map(|v| format!("{v:.8}"))
+            .unwrap_or_default(),
+        s.trend_bull.to_string(),
+        s.mom_bull.to_string(),
+        s.rs_bull.to_string(),
+        s.score.to_string(),
+        format!("{:.4}", s.raw_weight),
+        s.stop_level
+            .map(|v| format!("{:.8}", v))
+            .unwrap_or_default(),
+        s.adv.map(|v| format!("{v:.2}")).unwrap_or_default(),
+    ]
+}
+
+/// Write one asset's daily signals to CSV.
 ///
 /// # Errors
-/// Returns an error if file operations fail or if data cannot be processed.
+/// Returns an error if the file cannot be written.
+pub fn write_signals_csv(path: &Path, signals: &[DailySignal]) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    wtr.write_record(SIGNAL_COLUMNS)?;
+    for s in signals.iter().filter(|s| s.available) {
+        wtr.write_record(signal_record(s))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Append tradeable signal rows to an existing `signals_<asset>.csv`, creating it with a
+/// header if missing.
 ///
-/// # Panics
-/// Panics if the output directory is not specified in the arguments.
-#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::too_many_lines)]
-pub fn execute(args: &StrategyArgs) -> Result<()> {
-    let out_dir = args.out.as_ref().unwrap();
-    fs::create_dir_all(out_dir).context("create out dir")?;
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn append_signals_csv(path: &Path, signals: &[DailySignal]) -> Result<()> {
+    if !path.exists() {
+        return write_signals_csv(path, signals);
+    }
+    let file = fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+    for s in signals.iter().filter(|s| s.available) {
+        wtr.write_record(signal_record(s))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write the trade ledger to CSV.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_trades(path: &Path, trades: &[Trade]) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    for t in trades {
+        wtr.serialize(t)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write the stop exits to CSV.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_stop_fills(path: &Path, fills: &[StopFill]) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    for f in fills {
+        wtr.serialize(f)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write strategy and benchmark equity curves (1.0 before the first bar) to CSV.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_benchmarks(
+    path: &Path,
+    dates: &[NaiveDate],
+    equity: &[f64],
+    benchmarks: &[Benchmark],
+) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    let mut header = vec!["date".to_string(), "strategy".to_string()];
+    header.extend(benchmarks.iter().map(|b| b.name.clone()));
+    wtr.write_record(&header)?;
+    let mut bench_equity = vec![1.0; benchmarks.len()];
+    for (i, d) in dates.iter().enumerate() {
+        let mut rec = vec![d.to_string(), format!("{:.8}", equity[i])];
+        for (eq, b) in bench_equity.iter_mut().zip(benchmarks) {
+            *eq *= 1.0 + b.daily_ret[i];
+            rec.push(format!("{eq:.8}"));
+        }
+        wtr.write_record(&rec)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// OLS slope of `ln(y)` against time over `y`.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn log_slope(y: &[f64]) -> Option<f64> {
+    let n = y.len();
+    if n < 2 || y.iter().any(|v| *v <= 0.0) {
+        return None;
+    }
+    let x_mean = (n - 1) as f64 / 2.0;
+    let ln: Vec<f64> = y.iter().map(|v| v.ln()).collect();
+    let y_mean = ln.iter().sum::<f64>() / n as f64;
+    let (mut num, mut den) = (0.0, 0.0);
+    for (t, v) in ln.iter().enumerate() {
+        let dx = t as f64 - x_mean;
+        num += dx * (v - y_mean);
+        den += dx * dx;
+    }
+    Some(num / den)
+}
+
+/// Replace threshold weights with cross-sectional ranks: every `rebalance_days` rank the
+/// universe by the chosen metric over `rank_lookback` days ending `rank_skip` days ago,
+/// hold the top K at +1 (and the bottom K at -1 if `short_bottom`) until the next rebalance.
+pub fn apply_cross_sectional_ranks(
+    per_asset_signals: &mut BTreeMap<String, Vec<DailySignal>>,
+    rules: &RankRules,
+) {
+    let n_dates = per_asset_signals.values().next().map_or(0, Vec::len);
+    let mut ranks = RankState::default();
+    for i in 0..n_dates {
+        let mut universe: Vec<(&str, &mut [DailySignal])> = per_asset_signals
+            .iter_mut()
+            .map(|(name, sigs)| (name.as_str(), &mut sigs[..=i]))
+            .collect();
+        ranks.step(&mut universe, rules);
+    }
+}
+
+/// Cross-sectional ranking parameters of resolved strategy arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RankRules {
+    pub rank_by: RankBy,
+    /// Days the ranking metric covers (at least 1)
+    pub lookback: usize,
+    /// Most recent days left out of the ranking window
+    pub skip: usize,
+    pub top_k: usize,
+    pub short_bottom: bool,
+    /// Days between rebalances (at least 1)
+    pub rebalance: usize,
+}
+
+impl RankRules {
+    /// # Errors
+    /// Returns [`Error::Config`] if a ranking parameter is not set.
+    pub fn from_args(args: &StrategyArgs) -> Result<Self> {
+        Ok(Self {
+            rank_by: args.rank_by.required("rank_by")?,
+            lookback: args.rank_lookback.required("rank_lookback")?.max(1),
+            skip: args.rank_skip.required("rank_skip")?,
+            top_k: args.top_k.required("top_k")?,
+            short_bottom: args.short_bottom.required("short_bottom")?,
+            rebalance: args.rebalance_days.required("rebalance_days")?.max(1),
+        })
+    }
+
+    /// Dates of history a ranking needs: the window, the skipped days and today.
+    #[must_use]
+    pub const fn history(&self) -> usize {
+        self.lookback + self.skip + 1
+    }
+}
+
+/// Cross-sectional holdings carried between rebalances, advanced one date at a time; see
+/// [`apply_cross_sectional_ranks`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RankState {
+    /// Index of the next calendar date
+    day: usize,
+    held: BTreeMap<String, f64>,
+}
+
+impl RankState {
+    /// Rebalance if one is due today, then set today's raw weights. Each history in
+    /// `universe` ends today and holds at least `rank_lookback + rank_skip + 1` dates once
+    /// ranking has started.
+    pub fn step(&mut self, universe: &mut [(&str, &mut [DailySignal])], rules: &RankRules) {
+        let RankRules {
+            rank_by,
+            lookback,
+            skip,
+            top_k,
+            short_bottom,
+            rebalance,
+        } = *rules;
+
+        let i = self.day;
+        self.day += 1;
+        if i >= lookback + skip && (i - lookback - skip).is_multiple_of(rebalance) {
+            let mut scores: Vec<(String, f64)> = universe
+                .iter()
+                .filter_map(|(name, sigs)| {
+                    let today = sigs.len() - 1;
+                    let end = today - skip;
+                    let window = &sigs[end - lookback..=end];
+                    if !(sigs[today].available && window.iter().all(|s| s.available)) {
+                        return None;
+                    }
+                    let score = match rank_by {
+                        RankBy::Return => {
+                            let (first, last) = (window[0].price, window[lookback].price);
+                            (first > 0.0).then(|| last / first - 1.0)
+                        }
+                        RankBy::RsSlope => {
+                            let rs: Option<Vec<f64>> = window.iter().map(|s| s.rs).collect();
+                            rs.and_then(|rs| log_slope(&rs))
+                        }
+                    };
+                    score
+                        .filter(|v| v.is_finite())
+                        .map(|v| ((*name).to_string(), v))
+                })
+                .collect();
+            scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            self.held.clear();
+            let k = top_k.min(scores.len());
+            for (name, _) in scores.iter().take(k) {
+                self.held.insert(name.clone(), 1.0);
+            }
+            if short_bottom {
+                let k_short = top_k.min(scores.len() - k);
+                for (name, _) in scores.iter().rev().take(k_short) {
+                    self.held.insert(name.clone(), -1.0);
+                }
+            }
+        }
+        for (name, sigs) in universe.iter_mut() {
+            if let Some(s) = sigs.last_mut() {
+                s.raw_weight = if s.available {
+                    self.held.get(*name).copied().unwrap_or(0.0)
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+}
+
+/// Compute daily signals for one contiguous tradeable run of an asset.
+///
+/// # Errors
+/// Returns [`Error::Config`] if the strategy arguments have not had their defaults applied.
+pub fn compute_signals(
+    dates: &[NaiveDate],
+    bars: &[AlignedBar],
+    btc_close: &[f64],
+    args: &StrategyArgs,
+) -> Result<Vec<DailySignal>> {
+    let mut state = IndicatorState::new(args)?;
+    Ok(dates
+        .iter()
+        .zip(bars)
+        .zip(btc_close)
+        .map(|((date, bar), btc)| state.step(*date, bar, *btc))
+        .collect())
+}
+
+/// The last `len` values pushed, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Window<T> {
+    len: usize,
+    values: VecDeque<T>,
+}
 
-    let btc_path = args.btc.as_ref().unwrap();
-    let btc = read_series(btc_path).context("read BTC")?;
-    let assets_paths = args.assets.as_ref().unwrap();
-    let min_required_days = args.ma_long.unwrap() + 10;
-    let mut assets: Vec<(String, Series)> = Vec::new();
-
-    for p in assets_paths {
-        let name = p.file_stem().unwrap().to_string_lossy().to_string();
-        let series = read_series(p)?;
-        if series.dates.len() >= min_required_days {
-            assets.push((name, series));
+impl<T> Window<T> {
+    fn new(len: usize) -> Self {
+        Self {
+            len,
+            values: VecDeque::with_capacity(len + 1),
+        }
+    }
+
+    /// Push `v`, returning the value that fell out of the window.
+    fn push(&mut self, v: T) -> Option<T> {
+        self.values.push_back(v);
+        if self.values.len() > self.len {
+            self.values.pop_front()
         } else {
-            println!(
-                "Skipping {} (only {} days, need {})",
-                name,
-                series.dates.len(),
-                min_required_days
-            );
+            None
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.values.len() == self.len
+    }
+}
+
+/// Running mean with the same summation order as [`rolling_ma`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RollingMean {
+    window: Window<f64>,
+    sum: f64,
+}
+
+impl RollingMean {
+    fn new(len: usize) -> Self {
+        Self {
+            window: Window::new(len),
+            sum: 0.0,
         }
     }
 
-    println!("Using {} assets with sufficient data", assets.len());
+    #[allow(clippy::cast_precision_loss)]
+    fn push(&mut self, x: f64) -> Option<f64> {
+        self.sum += x;
+        if let Some(old) = self.window.push(x) {
+            self.sum -= old;
+        }
+        (self.window.len > 0 && self.window.is_full()).then(|| self.sum / self.window.len as f64)
+    }
+}
 
-    // Build common date index across BTC + all assets
-    let mut all = vec![btc.clone()];
-    all.extend(assets.iter().map(|(_, s)| s.clone()));
-    let dates = intersect_dates(&all);
-    let ma_long = args.ma_long.unwrap();
-    if dates.len() < ma_long + 10 {
-        bail!("Not enough overlapping data after alignment.");
+/// Rolling indicator state for one contiguous tradeable run of an asset, advanced one bar at
+/// a time. [`compute_signals`] folds it over a run; the incremental engine persists it
+/// between daemon cycles, and both produce identical signals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorState {
+    ma_short: RollingMean,
+    ma_long: RollingMean,
+    rs_ma_short: RollingMean,
+    rs_ma_long: RollingMean,
+    true_ranges: Window<f64>,
+    returns: Window<f64>,
+    volumes: Window<Option<f64>>,
+    prev_close: Option<f64>,
+    min_signals: usize,
+    short_alts: bool,
+    atr_mult: f64,
+    vol_mult: f64,
+}
+
+impl IndicatorState {
+    /// Empty state at the start of a run.
+    ///
+    /// # Errors
+    /// Returns [`Error::Config`] if the strategy arguments have not had their defaults applied.
+    pub fn new(args: &StrategyArgs) -> Result<Self> {
+        let ma_short = args.ma_short.required("ma_short")?;
+        let ma_long = args.ma_long.required("ma_long")?;
+        let stop_lookback = args.stop_lookback.required("stop_lookback")?;
+        Ok(Self {
+            ma_short: RollingMean::new(ma_short),
+            ma_long: RollingMean::new(ma_long),
+            rs_ma_short: RollingMean::new(ma_short),
+            rs_ma_long: RollingMean::new(ma_long),
+            true_ranges: Window::new(stop_lookback),
+            returns: Window::new(stop_lookback),
+            volumes: Window::new(args.adv_lookback.required("adv_lookback")?),
+            prev_close: None,
+            min_signals: args.min_signals.required("min_signals")?,
+            short_alts: args.short_alts.required("short_alts")?,
+            atr_mult: args.atr_mult.required("atr_mult")?,
+            vol_mult: args.vol_mult.required("vol_mult")?,
+        })
+    }
+
+    /// Advance one bar and return that day's signal.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn step(&mut self, date: NaiveDate, bar: &AlignedBar, btc_close: f64) -> DailySignal {
+        let close = bar.close;
+
+        // MAs
+        let ma_s = self.ma_short.push(close);
+        let ma_l = self.ma_long.push(close);
+
+        // Relative strength line and its MAs
+        let rs = close / btc_close;
+        let rs_ma_s = self.rs_ma_short.push(rs);
+        let rs_ma_l = self.rs_ma_long.push(rs);
+
+        // Stops (ATR if possible else vol of returns)
+        let tr = match (bar.high, bar.low, self.prev_close) {
+            (Some(h), Some(l), None) => (h - l).abs(),
+            (_, _, None) => 0.0,
+            (Some(h), Some(l), Some(prev)) => true_range(h, l, prev),
+            (_, _, Some(prev)) => (close - prev).abs(),
+        };
+        self.true_ranges.push(tr);
+        let atr = self.true_ranges.is_full().then(|| {
+            self.true_ranges.values.iter().sum::<f64>() / self.true_ranges.len as f64
+        });
+        let ret_std = self.prev_close.and_then(|prev| {
+            self.returns.push((close - prev) / prev);
+            self.returns
+                .is_full()
+                .then(|| population_std(self.returns.values.make_contiguous()))
+        });
+        self.prev_close = Some(close);
+
+        // Average daily dollar volume (CSV volume is already quoted in the vs currency)
+        self.volumes.push(bar.volume);
+        let adv = (self.volumes.len > 0
+            && self.volumes.is_full()
+            && self.volumes.values.iter().all(Option::is_some))
+        .then(|| self.volumes.values.iter().flatten().sum::<f64>() / self.volumes.len as f64);
+
+        let trend_bull = ma_l.is_some_and(|l| close > l);
+        let mom_bull = match (ma_s, ma_l) {
+            (Some(s), Some(l)) => s > l,
+            _ => false,
+        };
+        let rs_bull = match (rs_ma_s, rs_ma_l) {
+            (Some(s), Some(l)) => s > l,
+            _ => false,
+        };
+        let score = [trend_bull, mom_bull, rs_bull]
+            .iter()
+            .filter(|x| **x)
+            .count();
+
+        // raw weight: +1 for 3/3, +0.5 for >= min_signals with rs_bull, else 0 (or -1 on 3/3 bear if short_alts)
+        let mut raw = 0.0;
+        if score == 3 {
+            raw = 1.0;
+        } else if score >= self.min_signals && rs_bull {
+            raw = 0.5;
+        } else if self.short_alts {
+            // full-bear: 3/3 bearish
+            let trend_bear = ma_l.is_some_and(|l| close < l);
+            let mom_bear = match (ma_s, ma_l) {
+                (Some(s), Some(l)) => s < l,
+                _ => false,
+            };
+            let rs_bear = match (rs_ma_s, rs_ma_l) {
+                (Some(s), Some(l)) => s < l,
+                _ => false,
+            };
+            if trend_bear && mom_bear && rs_bear {
+                raw = -1.0;
+            }
+        }
+
+        // Stop level
+        let stop = atr
+            .filter(|&atrv| atrv > 0.0)
+            .map(|atrv| self.atr_mult.mul_add(-atrv, close))
+            .or_else(|| ret_std.map(|sd| close * self.vol_mult.mul_add(-sd, 1.0)));
+
+        DailySignal {
+            date,
+            price: close,
+            ma_short: ma_s,
+            ma_long: ma_l,
+            rs: Some(rs),
+            rs_ma_short: rs_ma_s,
+            rs_ma_long: rs_ma_l,
+            trend_bull,
+            mom_bull,
+            rs_bull,
+            score,
+            raw_weight: raw,
+            stop_level: stop,
+            adv,
+            available: true,
+        }
+    }
+}
+
+/// Raw price series loaded once and shared across backtest runs.
+#[derive(Clone)]
+pub struct MarketData {
+    pub btc: Series,
+    pub assets: Vec<(String, Series)>,
+    /// Optional user benchmark: (file stem, series)
+    pub benchmark: Option<(String, Series)>,
+}
+
+/// Series name of a CSV path: its file stem.
+fn series_name(path: &Path) -> Result<String> {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or_else(|| Error::config(format!("{} is not a file path", path.display())))
+}
+
+/// Load BTC and every asset series named in `args`.
+///
+/// # Errors
+/// Returns an error if the BTC or asset paths are not set, or a CSV file cannot be read or
+/// parsed.
+pub fn load_market_data(args: &StrategyArgs) -> Result<MarketData> {
+    let btc = read_series(args.btc.as_ref().required("btc")?).context("read BTC")?;
+    let assets = args
+        .assets
+        .as_ref()
+        .required("assets")?
+        .par_iter()
+        .map(|p| {
+            let series = read_series(p).with_context(|| format!("read {}", p.display()))?;
+            Ok((series_name(p)?, series))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let benchmark = match &args.benchmark {
+        Some(p) => Some((series_name(p)?, read_series(p).context("read benchmark")?)),
+        None => None,
+    };
+    Ok(MarketData {
+        btc,
+        assets,
+        benchmark,
+    })
+}
+
+/// One round trip: consecutive days held on the same side of an asset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub asset: String,
+    /// +1 long, -1 short
+    pub side: i8,
+    /// Close at which the position was entered (day before the first held return)
+    pub entry_date: NaiveDate,
+    pub exit_date: NaiveDate,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub days: usize,
+    /// Signed price return from entry to exit
+    pub trade_return: f64,
+    /// Sum of daily weight x return, i.e. contribution to portfolio return
+    pub contribution: f64,
+    /// Closed by its stop rather than the signal
+    #[serde(default)]
+    pub stopped: bool,
+}
+
+/// How a stop was reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopTrigger {
+    /// The bar opened through the stop; filled at the open
+    Gap,
+    /// The low (high for shorts) touched the stop; filled at the stop
+    Intrabar,
+    /// No intraday range in the data; the close was through the stop and filled there
+    Close,
+}
+
+/// A stop exit, kept to calibrate `atr_mult` against realistic fills.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopFill {
+    pub asset: String,
+    pub date: NaiveDate,
+    /// +1 long, -1 short
+    pub side: i8,
+    /// Prior close, from which the stop distance is measured
+    pub reference: f64,
+    pub stop: f64,
+    pub price: f64,
+    pub trigger: StopTrigger,
+}
+
+impl StopFill {
+    /// Fill relative to the stop price; negative is worse than the stop.
+    #[must_use]
+    pub fn slippage(&self) -> f64 {
+        f64::from(self.side) * (self.price / self.stop - 1.0)
+    }
+
+    /// Fill relative to the stop in units of the stop distance from the prior close.
+    #[must_use]
+    pub fn slippage_r(&self) -> f64 {
+        let distance = (self.reference - self.stop).abs();
+        if distance > 0.0 {
+            f64::from(self.side) * (self.price - self.stop) / distance
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Exit price and trigger if `bar` reaches `stop` for a position on `side`. With OHLC the
+/// stop triggers on the low (high for shorts) and fills at the worse of open and stop; bars
+/// without an intraday range (close-only data, forward-filled days) trigger on the close.
+#[must_use]
+pub fn stop_exit(side: i8, stop: f64, bar: &AlignedBar) -> Option<(f64, StopTrigger)> {
+    let through = |px: f64| if side > 0 { px <= stop } else { px >= stop };
+    let extreme = if side > 0 { bar.low } else { bar.high };
+    match extreme {
+        Some(_) if bar.open.is_some_and(through) => bar.open.map(|o| (o, StopTrigger::Gap)),
+        Some(x) if through(x) => Some((stop, StopTrigger::Intrabar)),
+        Some(_) => None,
+        None => {
+            let beyond = if side > 0 { bar.close < stop } else { bar.close > stop };
+            beyond.then_some((bar.close, StopTrigger::Close))
+        }
+    }
+}
+
+/// Stop count by trigger and slippage of the fills versus their stops, for `metrics.txt`.
+#[allow(clippy::cast_precision_loss)]
+#[must_use]
+pub fn stop_report(fills: &[StopFill]) -> String {
+    let count = |t: StopTrigger| fills.iter().filter(|f| f.trigger == t).count();
+    let mut out = format!(
+        "Stops Hit (gap/intrabar/close): {} ({}/{}/{})\n",
+        fills.len(),
+        count(StopTrigger::Gap),
+        count(StopTrigger::Intrabar),
+        count(StopTrigger::Close)
+    );
+    if !fills.is_empty() {
+        let n = fills.len() as f64;
+        let worst = |f: fn(&StopFill) -> f64| fills.iter().map(f).fold(f64::INFINITY, f64::min);
+        let mean = |f: fn(&StopFill) -> f64| fills.iter().map(f).sum::<f64>() / n;
+        let _ = writeln!(
+            out,
+            "Stop Slippage (mean/worst): {:.2}%/{:.2}% of stop, {:.2}R/{:.2}R of stop distance",
+            mean(StopFill::slippage) * 100.0,
+            worst(StopFill::slippage) * 100.0,
+            mean(StopFill::slippage_r),
+            worst(StopFill::slippage_r)
+        );
+    }
+    out
+}
+
+/// Output of a single in-memory backtest run.
+pub struct Backtest {
+    pub dates: Vec<NaiveDate>,
+    pub btc_close: Vec<f64>,
+    pub equity: Vec<f64>,
+    pub daily_ret: Vec<f64>,
+    pub num_positions: Vec<usize>,
+    /// Sum of absolute weight changes per day
+    pub turnover: Vec<f64>,
+    /// Gross exposure per day
+    pub exposure: Vec<f64>,
+    pub signals: BTreeMap<String, Vec<DailySignal>>,
+    /// Each asset's bars on `dates`; `None` where it cannot be traded
+    pub bars: BTreeMap<String, Vec<Option<AlignedBar>>>,
+    pub regimes: Vec<RegimeState>,
+    /// Trade ledger in exit order
+    pub trades: Vec<Trade>,
+    /// Assets dropped for insufficient history: (name, observed days)
+    pub skipped: Vec<(String, usize)>,
+    pub liquidity_capped_days: usize,
+    pub filled_days: usize,
+    /// Stop exits in date order
+    pub stop_fills: Vec<StopFill>,
+    /// BTC buy-and-hold, equal-weight universe and the optional user benchmark
+    pub benchmarks: Vec<Benchmark>,
+    /// Per-day position P&L (weight x return), hedge included; sums to `daily_ret`
+    pub contributions: Vec<Vec<Contribution>>,
+    pub metrics: Metrics,
+}
+
+impl Backtest {
+    /// Portfolio weights held over each day (index 0 is empty), keyed by asset; the BTC
+    /// hedge appears under [`attribution::HEDGE`].
+    #[must_use]
+    pub fn weights(&self) -> Vec<BTreeMap<String, f64>> {
+        self.contributions
+            .iter()
+            .map(|day| day.iter().map(|c| (c.asset.clone(), c.weight)).collect())
+            .collect()
     }
+}
+
+/// Run the strategy over pre-loaded data without touching the filesystem.
+///
+/// # Errors
+/// Returns [`Error::Data`] if there is not enough data after alignment and
+/// [`Error::Config`] if the strategy arguments have not had their defaults applied.
+#[allow(clippy::too_many_lines)]
+pub fn run_backtest(data: &MarketData, args: &StrategyArgs) -> Result<Backtest> {
+    let ma_long = args.ma_long.required("ma_long")?;
+    let ma_short = args.ma_short.required("ma_short")?;
+    let btc_hedge = args.btc_hedge.required("btc_hedge")?;
+    let min_required_days = ma_long + 10;
+    let mut skipped = Vec::new();
+    let assets: Vec<&(String, Series)> = data
+        .assets
+        .iter()
+        .filter(|(name, series)| {
+            let ok = series.dates.len() >= min_required_days;
+            if !ok {
+                skipped.push((name.clone(), series.dates.len()));
+            }
+            ok
+        })
+        .collect();
 
-    // Index maps
-    let btc_idx: BTreeMap<NaiveDate, usize> =
-        btc.dates.iter().enumerate().map(|(i, d)| (*d, i)).collect();
-    let btc_close: Vec<f64> = dates
+    // Union calendar from BTC's first date; assets trade from listing, short gaps forward-filled
+    let max_fill = args.max_fill_days.required("max_fill_days")?;
+    let mut all = vec![data.btc.clone()];
+    all.extend(assets.iter().map(|(_, s)| s.clone()));
+    let calendar = union_dates(&all);
+    let btc_bars = align_forward_fill(&data.btc, &calendar, max_fill);
+    // BTC is the numeraire: dates it cannot cover are dropped from the calendar
+    let keep: Vec<bool> = btc_bars.iter().map(Option::is_some).collect();
+    let dates: Vec<NaiveDate> = calendar
         .iter()
-        .map(|d| btc.close[*btc_idx.get(d).unwrap()])
+        .zip(&keep)
+        .filter_map(|(d, k)| k.then_some(*d))
         .collect();
-    let ma_short = args.ma_short.unwrap();
+    if dates.len() < min_required_days {
+        return Err(Error::data("Not enough overlapping data after alignment."));
+    }
+    let btc_close: Vec<f64> = btc_bars.iter().flatten().map(|b| b.close).collect();
+    let asset_bars: Vec<Vec<Option<AlignedBar>>> = assets
+        .par_iter()
+        .map(|(_, ser)| {
+            align_forward_fill(ser, &calendar, max_fill)
+                .into_iter()
+                .zip(&keep)
+                .filter_map(|(b, k)| k.then_some(b))
+                .collect()
+        })
+        .collect();
+    let filled_days: usize = asset_bars
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|b| b.filled)
+        .count();
+
     let btc_ma_s = rolling_ma(&btc_close, ma_short);
     let btc_ma_l = rolling_ma(&btc_close, ma_long);
     let btc_mkt_bear: Vec<bool> = dates
@@ -226,294 +1150,569 @@ pub fn execute(args: &StrategyArgs) -> Result<()> {
         })
         .collect();
 
+    // Market regime per date (BTC trend, breadth, realized vol, dispersion)
+    let regime_config = RegimeConfig::from_args(args)?;
+    let aligned_closes: Vec<Vec<Option<f64>>> = asset_bars
+        .iter()
+        .map(|bars| bars.iter().map(|b| b.map(|b| b.close)).collect())
+        .collect();
+    let regimes = regime::classify(&dates, &btc_close, &aligned_closes, &regime_config);
+    let regime_scaling = args.regime_scaling.required("regime_scaling")?;
+
     // For portfolio aggregation
     let mut daily_port_ret: Vec<f64> = vec![0.0; dates.len()];
     let mut daily_port_poscount: Vec<usize> = vec![0; dates.len()];
-    let mut per_asset_signals: BTreeMap<String, Vec<DailySignal>> = BTreeMap::new();
+    let mut daily_turnover: Vec<f64> = vec![0.0; dates.len()];
+    let mut daily_exposure: Vec<f64> = vec![0.0; dates.len()];
+    let mut prev_weights: BTreeMap<String, f64> = BTreeMap::new();
+    let mut open_trades: BTreeMap<String, Trade> = BTreeMap::new();
+    let mut trades: Vec<Trade> = Vec::new();
+    let mut contributions: Vec<Vec<Contribution>> = vec![Vec::new(); dates.len()];
+    let max_adv_fraction = args.max_adv_fraction.required("max_adv_fraction")?;
+    let capital = args.capital.required("capital")?;
+    let mut liquidity_capped_days = 0usize;
+    let mut stop_fills: Vec<StopFill> = Vec::new();
 
-    for (name, ser) in &assets {
-        // Map to aligned series
-        let idx: BTreeMap<NaiveDate, usize> =
-            ser.dates.iter().enumerate().map(|(i, d)| (*d, i)).collect();
-        let a_close: Vec<f64> = dates
-            .iter()
-            .map(|d| ser.close[*idx.get(d).unwrap()])
-            .collect();
-        let a_high: Vec<Option<f64>> = dates
-            .iter()
-            .map(|d| ser.high[*idx.get(d).unwrap()])
-            .collect();
-        let a_low: Vec<Option<f64>> = dates
-            .iter()
-            .map(|d| ser.low[*idx.get(d).unwrap()])
-            .collect();
-
-        // MAs
-        let a_ma_s = rolling_ma(&a_close, ma_short);
-        let a_ma_l = rolling_ma(&a_close, ma_long);
-
-        // Relative strength line and its MAs
-        let rs: Vec<f64> = a_close
-            .iter()
-            .zip(btc_close.iter())
-            .map(|(a, b)| a / b)
-            .collect();
-        let rs_ma_s = rolling_ma(&rs, ma_short);
-        let rs_ma_l = rolling_ma(&rs, ma_long);
-
-        // Stops (ATR if possible else vol of returns)
-        let stop_lookback = args.stop_lookback.unwrap();
-        let atr = rolling_atr(&a_high, &a_low, &a_close, stop_lookback);
-        let daily_ret: Vec<f64> = std::iter::once(&a_close[0])
-            .chain(a_close.iter().skip(1))
-            .tuple_windows()
-            .map(|(prev, next)| (next - prev) / prev)
-            .collect::<Vec<_>>();
-        let ret_std = rolling_std(&daily_ret, stop_lookback);
-
-        let mut signals = Vec::with_capacity(dates.len());
-        for i in 0..dates.len() {
-            let trend_bull = a_ma_l[i].is_some_and(|l| a_close[i] > l);
-            let mom_bull = match (a_ma_s[i], a_ma_l[i]) {
-                (Some(s), Some(l)) => s > l,
-                _ => false,
-            };
-            let rs_bull = match (rs_ma_s[i], rs_ma_l[i]) {
-                (Some(s), Some(l)) => s > l,
-                _ => false,
-            };
-            let score = [trend_bull, mom_bull, rs_bull]
-                .iter()
-                .filter(|x| **x)
-                .count();
-
-            // raw weight: +1 for 3/3, +0.5 for >= min_signals with rs_bull, else 0 (or -1 on 3/3 bear if short_alts)
-            let mut raw = 0.0;
-            if score == 3 {
-                raw = 1.0;
-            } else if score >= args.min_signals.unwrap() && rs_bull {
-                raw = 0.5;
-            } else if args.short_alts.unwrap_or(false) {
-                // full-bear: 3/3 bearish
-                let trend_bear = a_ma_l[i].is_some_and(|l| a_close[i] < l);
-                let mom_bear = match (a_ma_s[i], a_ma_l[i]) {
-                    (Some(s), Some(l)) => s < l,
-                    _ => false,
-                };
-                let rs_bear = match (rs_ma_s[i], rs_ma_l[i]) {
-                    (Some(s), Some(l)) => s < l,
-                    _ => false,
-                };
-                if trend_bear && mom_bear && rs_bear {
-                    raw = -1.0;
+    // Per-asset indicators are independent; collecting keeps the universe order
+    let mut per_asset_signals: BTreeMap<String, Vec<DailySignal>> = assets
+        .par_iter()
+        .zip(&asset_bars)
+        .map(|((name, _), bars)| {
+            // Indicators restart on each contiguous tradeable run (listing, or after a long gap)
+            let mut signals = Vec::with_capacity(dates.len());
+            let mut i = 0;
+            while i < dates.len() {
+                if bars[i].is_none() {
+                    signals.push(DailySignal::unavailable(dates[i]));
+                    i += 1;
+                    continue;
+                }
+                let start = i;
+                while i < dates.len() && bars[i].is_some() {
+                    i += 1;
                 }
+                let run: Vec<AlignedBar> = bars[start..i].iter().flatten().copied().collect();
+                signals.extend(compute_signals(
+                    &dates[start..i],
+                    &run,
+                    &btc_close[start..i],
+                    args,
+                )?);
             }
+            Ok((name.clone(), signals))
+        })
+        .collect::<Result<_>>()?;
 
-            // Stop level
-            let stop = atr[i]
-                .filter(|&atrv| atrv > 0.0)
-                .map(|atrv| args.atr_mult.unwrap().mul_add(-atrv, a_close[i]))
-                .or_else(|| {
-                    if i > 0 {
-                        ret_std[i - 1].map(|sd| a_close[i] * args.vol_mult.unwrap().mul_add(-sd, 1.0))
-                    } else {
-                        None
-                    }
-                });
-
-            signals.push(DailySignal {
-                date: dates[i],
-                price: a_close[i],
-                ma_short: a_ma_s[i],
-                ma_long: a_ma_l[i],
-                rs: Some(rs[i]),
-                rs_ma_short: rs_ma_s[i],
-                rs_ma_long: rs_ma_l[i],
-                trend_bull,
-                mom_bull,
-                rs_bull,
-                score,
-                raw_weight: raw,
-                stop_level: stop,
-            });
-        }
-
-        // Export signals CSV
-        fs::create_dir_all(out_dir)?;
-        let mut wtr =
-            WriterBuilder::new().from_path(out_dir.join(format!("signals_{name}.csv")))?;
-        wtr.write_record([
-            "date",
-            "close",
-            "ma_short",
-            "ma_long",
-            "rs",
-            "rs_ma_short",
-            "rs_ma_long",
-            "trend_bull",
-            "mom_bull",
-            "rs_bull",
-            "score",
-            "raw_weight",
-            "stop_level",
-        ])?;
-        for s in &signals {
-            wtr.write_record(&[
-                s.date.to_string(),
-                format!("{:.8}", s.price),
-                s.ma_short.map(|v| format!("{v:.8}")).unwrap_or_default(),
-                s.ma_long.map(|v| format!("{v:.8}")).unwrap_or_default(),
-                s.rs.map(|v| format!("{v:.8}")).unwrap_or_default(),
-                s.rs_ma_short
-                    .map(|v| format!("{v:.8}"))
-                    .unwrap_or_default(),
-                s.rs_ma_long
-                    .map(|v| format!("{v:.8}"))
-                    .unwrap_or_default(),
-                s.trend_bull.to_string(),
-                s.mom_bull.to_string(),
-                s.rs_bull.to_string(),
-                s.score.to_string(),
-                format!("{:.4}", s.raw_weight),
-                s.stop_level
-                    .map(|v| format!("{:.8}", v))
-                    .unwrap_or_default(),
-            ])?;
-        }
-        wtr.flush()?;
-        per_asset_signals.insert(name.clone(), signals);
+    // Cross-sectional mode replaces threshold weights with periodic top/bottom-K ranks
+    let cross_sectional = args.mode.required("mode")? == SignalMode::CrossSectional;
+    let allow_shorts = cross_sectional && args.short_bottom.required("short_bottom")?;
+    if cross_sectional {
+        apply_cross_sectional_ranks(&mut per_asset_signals, &RankRules::from_args(args)?);
     }
 
     // Portfolio construction: normalize long weights daily, optional BTC hedge on market-bear
     // If all raw weights <=0 (no longs), portfolio goes to cash unless btc_hedge triggers a short BTC hedge.
     // Daily portfolio return is sum_i(weight_i * asset_return_i) + hedge
-    // We also enforce stop: if close < stop on the day, set that asset's weight to 0 for that day.
+    // We also enforce stops: a position whose bar reaches the prior day's stop is held to the
+    // stop fill (see `stop_exit`) and closed that day.
+    let bars_of: BTreeMap<&str, &Vec<Option<AlignedBar>>> = assets
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .zip(&asset_bars)
+        .collect();
     let mut equity: Vec<f64> = vec![1.0; dates.len()];
     for i in 1..dates.len() {
-        // Gather candidate longs
+        // Gather candidate longs (and shorts in cross-sectional long/short mode)
         let mut longs: Vec<(String, f64)> = Vec::new();
+        let mut shorts: Vec<(String, f64)> = Vec::new();
+        let mut states: BTreeMap<&str, SignalState> = BTreeMap::new();
+        let mut stopped: BTreeMap<String, StopFill> = BTreeMap::new();
         for (name, sigs) in &per_asset_signals {
             let s_prev = &sigs[i - 1]; // enter based on prev day’s signal
             let s_now = &sigs[i];
-            // stop trigger
-            let stopped =
-                matches!((s_prev.stop_level, Some(s_now.price)), (Some(stp), Some(px)) if px < stp);
-            let w = if stopped {
-                0.0
+            if !(s_prev.available && s_now.available) {
+                // delisted or data gap beyond the fill limit: flat at last known price
+                continue;
+            }
+            let side: i8 = if s_prev.raw_weight > 0.0 {
+                longs.push((name.clone(), s_prev.raw_weight));
+                states.insert(
+                    name,
+                    SignalState::of(cross_sectional, s_prev.score, s_prev.raw_weight),
+                );
+                1
+            } else if allow_shorts && s_prev.raw_weight < 0.0 {
+                shorts.push((name.clone(), -s_prev.raw_weight));
+                states.insert(name, SignalState::CrossSectionalShort);
+                -1
             } else {
-                s_prev.raw_weight.max(0.0)
+                continue;
             };
-            if w > 0.0 {
-                longs.push((name.clone(), w));
+            // The short stop mirrors the long stop distance above the prior close
+            let stop = s_prev
+                .stop_level
+                .map(|stp| if side > 0 { stp } else { 2.0f64.mul_add(s_prev.price, -stp) });
+            let fill = stop.zip(bars_of[name.as_str()][i]).and_then(|(stp, bar)| {
+                stop_exit(side, stp, &bar).map(|(price, trigger)| StopFill {
+                    asset: name.clone(),
+                    date: dates[i],
+                    side,
+                    reference: s_prev.price,
+                    stop: stp,
+                    price,
+                    trigger,
+                })
+            });
+            if let Some(fill) = fill {
+                stopped.insert(name.clone(), fill);
             }
         }
         let long_sum: f64 = longs.iter().map(|(_, w)| *w).sum();
+        let short_sum: f64 = shorts.iter().map(|(_, w)| *w).sum();
+        // Each side gets full gross when alone, half when long/short
+        let side_gross = if long_sum > 0.0 && short_sum > 0.0 {
+            0.5
+        } else {
+            1.0
+        };
         let mut weights: BTreeMap<String, f64> = BTreeMap::new();
         if long_sum > 0.0 {
             for (name, w) in longs {
-                weights.insert(name, w / long_sum);
+                weights.insert(name, side_gross * w / long_sum);
+            }
+        }
+        if short_sum > 0.0 {
+            for (name, w) in shorts {
+                weights.insert(name, -side_gross * w / short_sum);
+            }
+        }
+
+        // Liquidity cap: weight * book <= max_adv_fraction * ADV (known at entry); excess stays in cash
+        if max_adv_fraction > 0.0 {
+            let book = capital * equity[i - 1];
+            for (name, w) in &mut weights {
+                if let Some(adv) = per_asset_signals[name][i - 1].adv {
+                    let cap = max_adv_fraction * adv / book;
+                    if w.abs() > cap {
+                        *w = cap.copysign(*w);
+                        liquidity_capped_days += 1;
+                    }
+                }
+            }
+        }
+
+        // Regime gross-exposure scaler (decided on prior day's regime)
+        if regime_scaling {
+            let exposure = regimes[i - 1].exposure;
+            for w in weights.values_mut() {
+                *w *= exposure;
             }
         }
 
         // BTC hedge
         let mut hedge_ret = 0.0;
-        if args.btc_hedge.unwrap() > 0.0 && btc_mkt_bear[i - 1] {
+        if btc_hedge > 0.0 && btc_mkt_bear[i - 1] {
             // short BTC @ weight = btc_hedge, P&L = -hedge * btc_return
             let r_btc = (btc_close[i] - btc_close[i - 1]) / btc_close[i - 1];
-            hedge_ret += -args.btc_hedge.unwrap() * r_btc;
+            hedge_ret += -btc_hedge * r_btc;
+            weights.insert("BTC_HEDGE".to_string(), -btc_hedge);
+            contributions[i].push(Contribution {
+                asset: attribution::HEDGE.to_string(),
+                state: SignalState::Hedge,
+                weight: -btc_hedge,
+                asset_return: r_btc,
+            });
+        }
+
+        // Turnover and gross exposure (hedge included)
+        daily_turnover[i] = weights
+            .keys()
+            .chain(prev_weights.keys())
+            .unique()
+            .map(|k| {
+                (weights.get(k).copied().unwrap_or(0.0) - prev_weights.get(k).copied().unwrap_or(0.0))
+                    .abs()
+            })
+            .sum();
+        daily_exposure[i] = weights.values().map(|w| w.abs()).sum();
+        let hedge = weights.remove("BTC_HEDGE");
+
+        // Close trades that are no longer held on the same side
+        let closed: Vec<String> = open_trades
+            .iter()
+            .filter(|(name, t)| {
+                weights
+                    .get(*name)
+                    .is_none_or(|w| (*w > 0.0) != (t.side > 0))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in closed {
+            trades.extend(open_trades.remove(&name));
         }
 
         // Compute daily return
         let mut port_ret = hedge_ret;
         for (name, w) in &weights {
-            let sigs = per_asset_signals.get(name).unwrap();
-            let r = (sigs[i].price - sigs[i - 1].price) / sigs[i - 1].price;
+            let sigs = &per_asset_signals[name];
+            let exit = stopped.get(name).map_or(sigs[i].price, |f| f.price);
+            let r = (exit - sigs[i - 1].price) / sigs[i - 1].price;
             port_ret += w * r;
+            contributions[i].push(Contribution {
+                asset: name.clone(),
+                state: states[name.as_str()],
+                weight: *w,
+                asset_return: r,
+            });
+
+            let trade = open_trades.entry(name.clone()).or_insert_with(|| Trade {
+                asset: name.clone(),
+                side: if *w > 0.0 { 1 } else { -1 },
+                entry_date: dates[i - 1],
+                exit_date: dates[i - 1],
+                entry_price: sigs[i - 1].price,
+                exit_price: sigs[i - 1].price,
+                days: 0,
+                trade_return: 0.0,
+                contribution: 0.0,
+                stopped: false,
+            });
+            trade.exit_date = dates[i];
+            trade.exit_price = exit;
+            trade.days += 1;
+            trade.trade_return = f64::from(trade.side) * (trade.exit_price / trade.entry_price - 1.0);
+            trade.contribution += w * r;
         }
 
         equity[i] = equity[i - 1] * (1.0 + port_ret);
         daily_port_ret[i] = port_ret;
         daily_port_poscount[i] = weights.len();
+        if let Some(h) = hedge {
+            weights.insert("BTC_HEDGE".to_string(), h);
+        }
+        // Stopped positions end the day flat; holding the signal again is a new trade
+        for (name, fill) in stopped {
+            if let Some(mut trade) = open_trades.remove(&name) {
+                trade.stopped = true;
+                trades.push(trade);
+                weights.remove(&name);
+                stop_fills.push(fill);
+            }
+        }
+        prev_weights = weights;
     }
+    // Positions still open at the end are marked to the last close
+    trades.extend(open_trades.into_values());
 
-    // Write equity curve
-    let mut wtr_eq = WriterBuilder::new().from_path(out_dir.join("equity_curve.csv"))?;
-    wtr_eq.write_record(["date", "equity", "port_ret", "num_positions", "btc_close"])?;
-    for i in 0..dates.len() {
-        wtr_eq.write_record(&[
-            dates[i].to_string(),
-            format!("{:.8}", equity[i]),
-            format!("{:.8}", daily_port_ret[i]),
-            daily_port_poscount[i].to_string(),
-            format!("{:.2}", btc_close[i]),
-        ])?;
+    let btc_closes: Vec<Option<f64>> = btc_close.iter().copied().map(Some).collect();
+    let mut benchmarks = vec![
+        Benchmark {
+            name: benchmark::BTC.to_string(),
+            daily_ret: benchmark::buy_and_hold(&btc_closes),
+        },
+        Benchmark {
+            name: benchmark::EQUAL_WEIGHT.to_string(),
+            daily_ret: benchmark::equal_weight(&aligned_closes),
+        },
+    ];
+    if let Some((name, ser)) = &data.benchmark {
+        // Non-crypto benchmarks skip weekends: carry their last close across any gap
+        let closes: Vec<Option<f64>> = align_forward_fill(ser, &dates, usize::MAX)
+            .iter()
+            .map(|b| b.map(|b| b.close))
+            .collect();
+        benchmarks.push(Benchmark {
+            name: name.clone(),
+            daily_ret: benchmark::buy_and_hold(&closes),
+        });
     }
-    wtr_eq.flush()?;
 
-    // Metrics
-    let total_ret = equity.last().unwrap() - 1.0;
-    let n_days = dates.len().max(1);
-    let years = (n_days as f64) / 365.25;
-    let cagr = if years > 0.0 {
-        equity.last().unwrap().powf(1.0 / years) - 1.0
-    } else {
-        0.0
-    };
-    // Sharpe (daily, then annualize sqrt(365))
-    let rets: Vec<f64> = daily_port_ret
-        .iter()
-        .copied()
-        .filter(|x| x.is_finite() && *x != 0.0)
-        .collect();
-    let mean = if rets.is_empty() {
-        0.0
-    } else {
-        rets.clone().mean()
-    };
-    let sd = if rets.len() > 1 {
-        let m = mean;
-        (rets.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (rets.len() as f64 - 1.0)).sqrt()
-    } else {
-        0.0
-    };
-    let sharpe = if sd > 0.0 {
-        (mean / sd) * 365.25_f64.sqrt()
-    } else {
-        0.0
-    };
+    let metrics = metrics::compute(
+        &daily_port_ret,
+        &dates,
+        metrics::periods_per_year(&dates),
+        &daily_turnover,
+        &daily_exposure,
+    );
 
-    // Max drawdown
-    let mut peak = f64::MIN;
-    let mut mdd = 0.0;
-    for &e in &equity {
-        if e > peak {
-            peak = e;
+    Ok(Backtest {
+        dates,
+        btc_close,
+        equity,
+        daily_ret: daily_port_ret,
+        num_positions: daily_port_poscount,
+        turnover: daily_turnover,
+        exposure: daily_exposure,
+        signals: per_asset_signals,
+        bars: assets
+            .iter()
+            .map(|(name, _)| name.clone())
+            .zip(asset_bars)
+            .collect(),
+        regimes,
+        trades,
+        skipped,
+        liquidity_capped_days,
+        filled_days,
+        stop_fills,
+        benchmarks,
+        contributions,
+        metrics,
+    })
+}
+
+/// Asset CSVs in `dir`, skipping the files a strategy run writes.
+///
+/// # Errors
+/// Returns an error if `dir` cannot be read.
+pub fn asset_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().unwrap_or_default() == "csv" {
+            // Filter out strategy output files
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            if !filename.starts_with("signals_")
+                && !filename.starts_with("attribution_")
+                && !OUTPUT_FILES.contains(&filename.as_ref())
+            {
+                files.push(path);
+            }
         }
-        let dd = 1.0 - (e / peak);
-        if dd > mdd {
-            mdd = dd;
+    }
+    Ok(files)
+}
+
+/// Fixed-name files written by [`write_outputs`] besides the per-asset signal CSVs
+const OUTPUT_FILES: [&str; 12] = [
+    "regime.csv",
+    "availability.csv",
+    "trades.csv",
+    "stops.csv",
+    "benchmarks.csv",
+    "attribution_daily.csv",
+    "attribution_summary.csv",
+    "attribution_monthly.csv",
+    "equity_curve.csv",
+    "metrics.txt",
+    "params.json",
+    "report.html",
+];
+
+/// Everything a strategy run produces, returned by [`backtest`] without printing or
+/// writing files.
+pub struct BacktestResult {
+    /// Arguments the run used, with defaults applied
+    pub args: StrategyArgs,
+    pub backtest: Backtest,
+    pub attribution: Attribution,
+    /// Human-readable metrics and benchmark summary (the contents of `metrics.txt`)
+    pub summary: String,
+}
+
+impl BacktestResult {
+    /// Market regime on the last backtest date.
+    #[must_use]
+    pub fn latest_regime(&self) -> Option<&RegimeState> {
+        self.backtest.regimes.last()
+    }
+}
+
+/// Load the inputs named in `args` and run the strategy, returning typed results.
+///
+/// # Errors
+/// Returns an error if an input CSV cannot be read, there is not enough data, or the
+/// strategy arguments have not had their defaults applied.
+pub fn backtest(args: &StrategyArgs) -> Result<BacktestResult> {
+    let data = load_market_data(args)?;
+    let bt = run_backtest(&data, args)?;
+    let attribution = attribution::summarize(&bt.dates, &bt.equity, &bt.contributions);
+
+    let m = &bt.metrics;
+    let cross_sectional = args.mode.required("mode")? == SignalMode::CrossSectional;
+    let allow_shorts = cross_sectional && args.short_bottom.required("short_bottom")?;
+    let max_fill = args.max_fill_days.required("max_fill_days")?;
+    let regime_days = |r: Regime| bt.regimes.iter().filter(|s| s.regime == r).count();
+    let mut summary = format!(
+        "Days: {}\n{}Turnover (ann.): {:.1}x\nAvg Gross Exposure: {:.2}\nLiquidity-Capped Position Days: {}\nRegime Days (risk-on/neutral/risk-off/crisis): {}/{}/{}/{}\nRegime Scaling: {}\nSignal Mode: {}\nForward-Filled Asset Days (max {} per gap): {}\n",
+        m.days,
+        metrics::report(m),
+        m.turnover,
+        m.exposure,
+        bt.liquidity_capped_days,
+        regime_days(Regime::RiskOn),
+        regime_days(Regime::Neutral),
+        regime_days(Regime::RiskOff),
+        regime_days(Regime::Crisis),
+        args.regime_scaling.required("regime_scaling")?,
+        if cross_sectional {
+            let rules = RankRules::from_args(args)?;
+            format!(
+                "cross-sectional (top {}{}, every {} days)",
+                rules.top_k,
+                if allow_shorts { ", short bottom" } else { "" },
+                rules.rebalance
+            )
+        } else {
+            "threshold".to_string()
+        },
+        max_fill,
+        bt.filled_days
+    );
+    summary.push_str(&stop_report(&bt.stop_fills));
+    for b in &bt.benchmarks {
+        let (bench, rel) = benchmark::compare(&bt.daily_ret, b, m.periods_per_year);
+        summary.push_str(&benchmark::report(&b.name, &bench, &rel));
+    }
+
+    Ok(BacktestResult {
+        args: args.clone(),
+        backtest: bt,
+        attribution,
+        summary,
+    })
+}
+
+/// Write the equity curve to CSV.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_equity_curve(path: &Path, bt: &Backtest) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    wtr.write_record([
+        "date",
+        "equity",
+        "port_ret",
+        "num_positions",
+        "btc_close",
+        "gross_exposure",
+    ])?;
+    for i in 0..bt.dates.len() {
+        wtr.write_record(&[
+            bt.dates[i].to_string(),
+            format!("{:.8}", bt.equity[i]),
+            format!("{:.8}", bt.daily_ret[i]),
+            bt.num_positions[i].to_string(),
+            format!("{:.2}", bt.btc_close[i]),
+            format!("{:.4}", bt.exposure[i]),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// File sink for a run: write every output file into `out_dir` and return their paths.
+///
+/// # Errors
+/// Returns an error if the directory or any file cannot be written.
+pub fn write_outputs(result: &BacktestResult, out_dir: &Path) -> Result<Vec<PathBuf>> {
+    let bt = &result.backtest;
+    fs::create_dir_all(out_dir).context("create out dir")?;
+
+    let mut written: Vec<PathBuf> = bt
+        .signals
+        .par_iter()
+        .map(|(name, signals)| {
+            let path = out_dir.join(format!("signals_{name}.csv"));
+            write_signals_csv(&path, signals)?;
+            Ok(path)
+        })
+        .collect::<Result<_>>()?;
+    let path = |f: &str| out_dir.join(f);
+    regime::write_regimes(&path("regime.csv"), &bt.regimes)?;
+    write_availability(&path("availability.csv"), &bt.dates, &bt.signals)?;
+    write_trades(&path("trades.csv"), &bt.trades)?;
+    write_stop_fills(&path("stops.csv"), &bt.stop_fills)?;
+    write_benchmarks(
+        &path("benchmarks.csv"),
+        &bt.dates,
+        &bt.equity,
+        &bt.benchmarks,
+    )?;
+    attribution::write_daily(&path("attribution_daily.csv"), &bt.dates, &bt.contributions)?;
+    attribution::write_summary(&path("attribution_summary.csv"), &result.attribution)?;
+    attribution::write_monthly(
+        &path("attribution_monthly.csv"),
+        &result.attribution,
+        &bt.contributions,
+    )?;
+    write_equity_curve(&path("equity_curve.csv"), bt)?;
+    fs::write(path("metrics.txt"), &result.summary)?;
+    fs::write(
+        path("params.json"),
+        serde_json::to_string_pretty(&result.args)?,
+    )?;
+    report::write(
+        &path("report.html"),
+        &ReportData::from_backtest(bt, &result.attribution, &result.args, &result.summary),
+    )?;
+    written.extend(OUTPUT_FILES.iter().map(|f| path(f)));
+    Ok(written)
+}
+
+/// Print the attribution by signal state and the largest drawdown detractor.
+fn print_attribution(attr: &Attribution) {
+    println!("🧩 Attribution by signal state:");
+    for g in &attr.by_state {
+        println!(
+            "   {:<14} {:>5} position-days  contribution {:>7.2}%  avg asset return {:>6.3}%  hit rate {:>5.1}%",
+            g.group,
+            g.days,
+            g.contribution * 100.0,
+            g.avg_asset_return * 100.0,
+            g.hit_rate * 100.0
+        );
+    }
+    if let Some((peak, trough)) = attr.drawdown {
+        let worst = attr
+            .by_asset
+            .iter()
+            .min_by(|a, b| a.drawdown_contribution.total_cmp(&b.drawdown_contribution));
+        if let Some(g) = worst {
+            println!(
+                "   Max drawdown {peak} → {trough}: largest detractor {} ({:.2}%)",
+                g.group,
+                g.drawdown_contribution * 100.0
+            );
         }
     }
+}
 
-    // Win rate
-    let wins = rets.iter().filter(|r| **r > 0.0).count() as f64;
-    let wr = if rets.is_empty() {
-        0.0
-    } else {
-        wins / (rets.len() as f64)
+/// Execute the momentum strategy: run [`backtest`], print the summary, write the output
+/// files when `--out` is set and record the run.
+///
+/// # Errors
+/// Returns an error if file operations fail or if data cannot be processed.
+pub fn execute(args: &StrategyArgs) -> Result<()> {
+    let result = backtest(args)?;
+    let bt = &result.backtest;
+    let min_required_days = args.ma_long.required("ma_long")? + 10;
+    for (name, days) in &bt.skipped {
+        println!("Skipping {name} (only {days} days, need {min_required_days})");
+    }
+    println!("Using {} assets with sufficient data", bt.signals.len());
+
+    let outputs = match &args.out {
+        Some(out_dir) => write_outputs(&result, out_dir)?,
+        None => Vec::new(),
     };
 
-    let metrics = format!(
-        "Days: {}\nTotal Return: {:.2}%\nCAGR: {:.2}%\nSharpe (ann.): {:.2}\nMax Drawdown: {:.2}%\nWin Rate: {:.2}%\n",
-        n_days,
-        total_ret * 100.0,
-        cagr * 100.0,
-        sharpe,
-        mdd * 100.0,
-        wr * 100.0
-    );
-    fs::write(out_dir.join("metrics.txt"), metrics.clone())?;
-    println!("{metrics}");
+    print_attribution(&result.attribution);
+    println!("{}", result.summary);
+    if let Some(out_dir) = &args.out {
+        println!("📄 Report written to {}", out_dir.join("report.html").display());
+    }
+
+    if args.record.required("record")?
+        && let Some(runs_dir) = &args.runs_dir
+    {
+        let run = runs::record(runs_dir, args, &bt.dates, &bt.metrics, &outputs)?;
+        println!(
+            "🗂️  Run recorded as {} ({})",
+            run.id,
+            runs_dir.join(&run.id).display()
+        );
+    }
 
     Ok(())
 }
@@ -0,0 +1,418 @@
+use crate::error::{Context, Error, Required, Result};
+use clap::ValueEnum;
+use csv::WriterBuilder;
+use itertools::Itertools;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::metrics::Metrics;
+use crate::overfit::{self, Pbo};
+use crate::strategy::{self, MarketData};
+use crate::{RankBy, SignalMode, StrategyArgs, SweepArgs};
+
+/// Values to try for one `StrategyArgs` field.
+#[derive(Debug, Clone)]
+pub struct ParamGrid {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// One grid point and its backtest outcome.
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    /// Parameter values in grid order
+    pub params: Vec<String>,
+    pub metrics: Option<Metrics>,
+    pub error: Option<String>,
+    /// Daily portfolio returns (empty on error)
+    pub daily_ret: Vec<f64>,
+    /// Probabilistic Sharpe ratio
+    pub psr: f64,
+    /// Sharpe deflated by the number of combinations tried
+    pub dsr: f64,
+}
+
+/// Parse `name=start:end:step` (inclusive) or `name=v1,v2,...`.
+///
+/// # Errors
+/// Returns an error if the spec is malformed or names an unknown field.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn parse_grid(spec: &str) -> Result<ParamGrid> {
+    let (name, values) = spec
+        .split_once('=')
+        .ok_or_else(|| {
+            Error::config(format!("grid spec '{spec}' must look like name=start:end:step"))
+        })?;
+    let name = name.trim().replace('-', "_");
+
+    let values: Vec<String> = if values.contains(':') {
+        let parts: Vec<&str> = values.split(':').map(str::trim).collect();
+        let [start, end, step] = parts[..] else {
+            return Err(Error::config(format!(
+                "range '{values}' must be start:end:step"
+            )));
+        };
+        if let (Ok(a), Ok(b), Ok(st)) = (
+            start.parse::<i64>(),
+            end.parse::<i64>(),
+            step.parse::<i64>(),
+        ) {
+            if st <= 0 || b < a {
+                return Err(Error::config(format!(
+                    "range '{values}' needs start <= end and a positive step"
+                )));
+            }
+            (a..=b).step_by(st as usize).map(|v| v.to_string()).collect()
+        } else {
+            let bound = |what: &str, v: &str| {
+                v.parse::<f64>()
+                    .map_err(|e| Error::config(format!("range {what} '{v}': {e}")))
+            };
+            let (a, b, st) = (bound("start", start)?, bound("end", end)?, bound("step", step)?);
+            if st <= 0.0 || b < a {
+                return Err(Error::config(format!(
+                    "range '{values}' needs start <= end and a positive step"
+                )));
+            }
+            let n = ((b - a) / st + 1e-9).floor() as usize;
+            (0..=n)
+                .map(|k| {
+                    let v = (k as f64).mul_add(st, a);
+                    ((v * 1e10).round() / 1e10).to_string()
+                })
+                .collect()
+        }
+    } else {
+        values
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    };
+    if values.is_empty() {
+        return Err(Error::config(format!("grid for '{name}' has no values")));
+    }
+
+    // Validate every value up front so a typo fails before any backtest runs
+    let mut probe = StrategyArgs::default();
+    for v in &values {
+        set_param(&mut probe, &name, v)?;
+    }
+    Ok(ParamGrid { name, values })
+}
+
+/// Set a single `StrategyArgs` field by name from its string value.
+///
+/// # Errors
+/// Returns an error for unknown or non-sweepable fields and unparsable values.
+pub fn set_param(args: &mut StrategyArgs, name: &str, value: &str) -> Result<()> {
+    fn num<T: std::str::FromStr>(name: &str, value: &str) -> Result<Option<T>>
+    where
+        T::Err: std::fmt::Display,
+    {
+        value
+            .parse()
+            .map(Some)
+            .map_err(|e| Error::config(format!("invalid value '{value}' for {name}: {e}")))
+    }
+    fn choice<T: ValueEnum>(name: &str, value: &str) -> Result<Option<T>> {
+        T::from_str(value, true)
+            .map(Some)
+            .map_err(|e| Error::config(format!("invalid value '{value}' for {name}: {e}")))
+    }
+
+    match name {
+        "ma_short" => args.ma_short = num(name, value)?,
+        "ma_long" => args.ma_long = num(name, value)?,
+        "min_signals" => args.min_signals = num(name, value)?,
+        "short_alts" => args.short_alts = num(name, value)?,
+        "btc_hedge" => args.btc_hedge = num(name, value)?,
+        "stop_lookback" => args.stop_lookback = num(name, value)?,
+        "atr_mult" => args.atr_mult = num(name, value)?,
+        "vol_mult" => args.vol_mult = num(name, value)?,
+        "max_adv_fraction" => args.max_adv_fraction = num(name, value)?,
+        "adv_lookback" => args.adv_lookback = num(name, value)?,
+        "capital" => args.capital = num(name, value)?,
+        "regime_scaling" => args.regime_scaling = num(name, value)?,
+        "mode" => args.mode = choice::<SignalMode>(name, value)?,
+        "rank_by" => args.rank_by = choice::<RankBy>(name, value)?,
+        "rank_lookback" => args.rank_lookback = num(name, value)?,
+        "rank_skip" => args.rank_skip = num(name, value)?,
+        "top_k" => args.top_k = num(name, value)?,
+        "short_bottom" => args.short_bottom = num(name, value)?,
+        "rebalance_days" => args.rebalance_days = num(name, value)?,
+        "max_fill_days" => args.max_fill_days = num(name, value)?,
+        "btc" | "assets" | "out" | "benchmark" | "runs_dir" | "record" => {
+            return Err(Error::config(format!(
+                "'{name}' selects data or output files and cannot be swept"
+            )));
+        },
+        _ => return Err(Error::config(format!("unknown strategy parameter '{name}'"))),
+    }
+    Ok(())
+}
+
+/// Every combination of grid values, in grid order.
+#[must_use]
+pub fn combinations(grids: &[ParamGrid]) -> Vec<Vec<String>> {
+    grids
+        .iter()
+        .map(|g| g.values.clone())
+        .multi_cartesian_product()
+        .collect()
+}
+
+/// `base` with one grid point applied.
+#[must_use]
+pub fn args_for(base: &StrategyArgs, grids: &[ParamGrid], params: &[String]) -> StrategyArgs {
+    let mut args = base.clone();
+    for (grid, value) in grids.iter().zip(params) {
+        // values were validated in parse_grid
+        let _ = set_param(&mut args, &grid.name, value);
+    }
+    args
+}
+
+/// Parse and de-duplicate `--grid` specs.
+///
+/// # Errors
+/// Returns an error if no grid is given, a spec is invalid or a parameter repeats.
+pub fn parse_grids(specs: &[String]) -> Result<Vec<ParamGrid>> {
+    if specs.is_empty() {
+        return Err(Error::config(
+            "no --grid given; e.g. --grid ma_short=2:6:1 --grid atr_mult=2,2.5,3",
+        ));
+    }
+    let grids: Vec<ParamGrid> = specs.iter().map(|g| parse_grid(g)).collect::<Result<_>>()?;
+    if let Some(dup) = grids.iter().map(|g| &g.name).duplicates().next() {
+        return Err(Error::config(format!(
+            "parameter '{dup}' appears in more than one --grid"
+        )));
+    }
+    Ok(grids)
+}
+
+/// Run every combination of `grids` over `data` in parallel.
+#[must_use]
+pub fn run_sweep(data: &MarketData, base: &StrategyArgs, grids: &[ParamGrid]) -> Vec<SweepResult> {
+    combinations(grids)
+        .into_par_iter()
+        .map(|params| {
+            let args = args_for(base, grids, &params);
+            match strategy::run_backtest(data, &args) {
+                Ok(bt) => SweepResult {
+                    params,
+                    metrics: Some(bt.metrics),
+                    error: None,
+                    // Day 0 has no return by construction
+                    daily_ret: bt.daily_ret.get(1..).unwrap_or_default().to_vec(),
+                    psr: 0.0,
+                    dsr: 0.0,
+                },
+                Err(e) => SweepResult {
+                    params,
+                    metrics: None,
+                    error: Some(e.to_string()),
+                    daily_ret: Vec::new(),
+                    psr: 0.0,
+                    dsr: 0.0,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Fill in PSR/DSR for every successful grid point (trials = successful combinations) and
+/// return the CSCV probability of backtest overfitting for picking the best one.
+pub fn apply_overfitting_diagnostics(results: &mut [SweepResult]) -> Option<Pbo> {
+    let ok: Vec<usize> = (0..results.len())
+        .filter(|&k| results[k].metrics.is_some())
+        .collect();
+    let trials: Vec<&[f64]> = ok.iter().map(|&k| results[k].daily_ret.as_slice()).collect();
+    let diagnostics = overfit::sharpe_diagnostics(&trials);
+    let matrix: Vec<Vec<f64>> = ok.iter().map(|&k| results[k].daily_ret.clone()).collect();
+    for (&k, d) in ok.iter().zip(diagnostics) {
+        results[k].psr = d.psr;
+        results[k].dsr = d.dsr;
+    }
+    overfit::pbo_cscv(&matrix, 16)
+}
+
+/// Write all grid points, best Sharpe first.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write_results(path: &Path, grids: &[ParamGrid], results: &[SweepResult]) -> Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    let mut header: Vec<&str> = grids.iter().map(|g| g.name.as_str()).collect();
+    header.extend([
+        "days",
+        "total_return",
+        "cagr",
+        "sharpe",
+        "psr",
+        "dsr",
+        "max_drawdown",
+        "win_rate",
+        "turnover",
+        "exposure",
+        "error",
+    ]);
+    wtr.write_record(&header)?;
+    for r in results {
+        let mut rec = r.params.clone();
+        match &r.metrics {
+            Some(m) => rec.extend([
+                m.days.to_string(),
+                format!("{:.6}", m.total_return),
+                format!("{:.6}", m.cagr),
+                format!("{:.4}", m.sharpe),
+                format!("{:.4}", r.psr),
+                format!("{:.4}", r.dsr),
+                format!("{:.6}", m.max_drawdown),
+                format!("{:.4}", m.win_rate),
+                format!("{:.2}", m.turnover),
+                format!("{:.4}", m.exposure),
+                String::new(),
+            ]),
+            None => {
+                rec.extend(std::iter::repeat_n(String::new(), 10));
+                rec.push(r.error.clone().unwrap_or_default());
+            }
+        }
+        wtr.write_record(&rec)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// For every pair of swept parameters write a pivot table (rows = first parameter, columns =
+/// second) of the best metric over all remaining parameters, one file per metric.
+///
+/// # Errors
+/// Returns an error if a file cannot be written.
+pub fn write_heatmaps(out_dir: &Path, grids: &[ParamGrid], results: &[SweepResult]) -> Result<()> {
+    // Lower drawdown is better: rank on the negated value and flip it back on output
+    let get = |metric: &str, m: &Metrics| match metric {
+        "sharpe" => m.sharpe,
+        "cagr" => m.cagr,
+        _ => -m.max_drawdown,
+    };
+    for (a, b) in (0..grids.len()).tuple_combinations() {
+        if grids[a].values.len() < 2 || grids[b].values.len() < 2 {
+            continue;
+        }
+        for metric in ["sharpe", "cagr", "max_drawdown"] {
+            let mut best: BTreeMap<(&str, &str), f64> = BTreeMap::new();
+            for r in results {
+                let Some(m) = &r.metrics else { continue };
+                let v = get(metric, m);
+                let cell = best
+                    .entry((r.params[a].as_str(), r.params[b].as_str()))
+                    .or_insert(f64::NEG_INFINITY);
+                *cell = cell.max(v);
+            }
+            let sign = if metric == "max_drawdown" { -1.0 } else { 1.0 };
+
+            let path = out_dir.join(format!(
+                "heatmap_{metric}_{}_x_{}.csv",
+                grids[a].name, grids[b].name
+            ));
+            let mut wtr = WriterBuilder::new().from_path(path)?;
+            let mut header = vec![format!("{}\\{}", grids[a].name, grids[b].name)];
+            header.extend(grids[b].values.iter().cloned());
+            wtr.write_record(&header)?;
+            for va in &grids[a].values {
+                let mut rec = vec![va.clone()];
+                rec.extend(grids[b].values.iter().map(|vb| {
+                    best.get(&(va.as_str(), vb.as_str()))
+                        .filter(|v| v.is_finite())
+                        .map(|v| format!("{:.4}", sign * v))
+                        .unwrap_or_default()
+                }));
+                wtr.write_record(&rec)?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Execute a parameter sweep.
+///
+/// # Errors
+/// Returns an error if the grid is invalid, data cannot be loaded or results cannot be written.
+pub fn execute(args: &SweepArgs) -> Result<()> {
+    let grids = parse_grids(&args.grid)?;
+
+    let out_dir = args.base.out.as_ref().required("out")?;
+    fs::create_dir_all(out_dir).context("create out dir")?;
+
+    let data = strategy::load_market_data(&args.base)?;
+    let n_combos: usize = grids.iter().map(|g| g.values.len()).product();
+    println!(
+        "🔬 Sweeping {} combinations over {} assets using {} threads",
+        n_combos,
+        data.assets.len(),
+        rayon::current_num_threads()
+    );
+    for g in &grids {
+        println!("   • {} = [{}]", g.name, g.values.join(", "));
+    }
+
+    let mut results = run_sweep(&data, &args.base, &grids);
+    let pbo = apply_overfitting_diagnostics(&mut results);
+    results.sort_by(|a, b| {
+        let key = |r: &SweepResult| r.metrics.as_ref().map_or(f64::NEG_INFINITY, |m| m.sharpe);
+        key(b).total_cmp(&key(a))
+    });
+
+    write_results(&out_dir.join("sweep_results.csv"), &grids, &results)?;
+    write_heatmaps(out_dir, &grids, &results)?;
+
+    let failed = results.iter().filter(|r| r.metrics.is_none()).count();
+    let top = args.top.unwrap_or(10);
+    println!();
+    println!("📈 TOP {} PARAMETER SETS (by Sharpe)", top.min(results.len() - failed));
+    let param_cols: String = grids.iter().map(|g| format!("{:<16}", g.name)).collect();
+    println!(
+        "{param_cols}{:<10} {:<10} {:<8} {:<8} {:<10} {:<10} {:<10} {:<10}",
+        "CAGR%", "Sharpe", "PSR%", "DSR%", "Max DD%", "Win Rate%", "Turnover", "Exposure"
+    );
+    println!("{}", "-".repeat(16 * grids.len() + 84));
+    for r in results.iter().filter(|r| r.metrics.is_some()).take(top) {
+        let Some(m) = &r.metrics else { continue };
+        let params: String = r.params.iter().map(|v| format!("{v:<16}")).collect();
+        println!(
+            "{params}{:<10.2} {:<10.2} {:<8.1} {:<8.1} {:<10.2} {:<10.1} {:<10.1} {:<10.2}",
+            m.cagr * 100.0,
+            m.sharpe,
+            r.psr * 100.0,
+            r.dsr * 100.0,
+            m.max_drawdown * 100.0,
+            m.win_rate * 100.0,
+            m.turnover,
+            m.exposure
+        );
+    }
+    if failed > 0 {
+        println!("⚠️  {failed} combinations failed (see the error column)");
+    }
+    if let Some(pbo) = &pbo {
+        println!(
+            "🎯 Probability of backtest overfitting (CSCV, {} splits): {:.1}%",
+            pbo.splits,
+            pbo.pbo * 100.0
+        );
+    }
+    println!();
+    println!("💾 Results written to {}", out_dir.join("sweep_results.csv").display());
+
+    Ok(())
+}
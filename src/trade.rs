@@ -1,9 +1,20 @@
-use anyhow::Result;
+use crate::error::{Required, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::Path;
 
-use crate::ai_insights::{generate_asset_insights, generate_fallback_insights, AssetMetrics};
+use crate::TradeArgs;
+use crate::account::Account;
+use crate::instruments::{Instrument, Instruments};
+use crate::positions::{BookRules, Position, PositionBook};
+use crate::ai_insights::{generate_asset_insights, generate_fallback_insights, AiSettings, AssetMetrics};
 use crate::analyzer::{StrategyAnalysis, analyze_signals_directory};
+use crate::regime::{RegimeState, read_latest_regime};
+
+/// Minimum deflated Sharpe (probability) for an asset to be promoted to a playbook; below 0.5
+/// the Sharpe is no better than the best of that many coin flips.
+const MIN_DEFLATED_SHARPE: f64 = 0.5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradePlan {
@@ -14,6 +25,7 @@ pub struct TradePlan {
     pub conviction: Conviction,
     pub backtest_stats: BacktestStats,
     pub computed_values: ComputedValues,
+    pub regime: Option<String>,
     pub notes: String,
 }
 
@@ -60,6 +72,10 @@ pub struct Conviction {
 pub struct BacktestStats {
     pub total_return_percent: f64,
     pub sharpe_ratio: f64,
+    /// Probabilistic Sharpe ratio (%): P(true Sharpe > 0)
+    pub probabilistic_sharpe_percent: f64,
+    /// Sharpe deflated by the number of assets screened (%)
+    pub deflated_sharpe_percent: f64,
     pub win_rate_percent: f64,
     pub max_drawdown_percent: f64,
     pub trading_days: usize,
@@ -98,68 +114,108 @@ pub struct ComputedValues {
     pub risk_per_share: f64,         // Risk per share (entry - stop)
     pub max_shares_by_risk: f64,     // Max shares based on risk cap
     pub max_shares_by_position: f64, // Max shares based on position cap
-    pub recommended_shares: u64,     // Final recommended position size
+    pub recommended_shares: f64,     // Final recommended position size, in valid lots
     pub position_value: f64,         // Total position value
     pub position_percent: f64,       // Position as % of portfolio
 
     // Profit taking calculations
-    pub profit_target: f64,         // 2R profit target price
+    pub profit_target: f64,         // Profit target price (entry + target R)
     pub profit_target_percent: f64, // Profit target as % gain
-    pub scale_out_shares: u64,      // Shares to sell at profit target
-    pub remaining_shares: u64,      // Shares to trail after scale out
+    pub scale_out_shares: f64,      // Shares to sell at profit target
+    pub remaining_shares: f64,      // Shares to trail after scale out
     pub scale_out_value: f64,       // Value of shares to scale out
 
     // Stop loss levels
-    pub initial_stop: f64,      // Initial stop loss price
+    pub initial_stop: f64,      // Initial stop loss price (the book's entry stop if held)
+    pub initial_r: f64,         // Initial risk per unit (entry - initial stop)
     pub stop_loss_percent: f64, // Stop loss as % loss
-    pub trailing_stop: f64,     // Current trailing stop price
+    pub trailing_stop: f64,     // Current trailing stop price (ratcheted by the book if held)
     pub stop_distance_atr: f64, // Stop distance in ATR units
 
     // Risk management
     pub portfolio_risk: f64,    // Total risk as % of portfolio
     pub risk_reward_ratio: f64, // Risk/reward ratio
     pub max_loss: f64,          // Maximum possible loss
-    pub max_gain: f64,          // Maximum possible gain (2R)
+    pub max_gain: f64,          // Maximum possible gain at the profit target
 
     // Execution parameters
-    pub is_extended: bool,        // Price > 10% above MA30
+    pub is_extended: bool,        // Price above MA30 by more than the extended threshold
     pub ma30_pullback_price: f64, // MA30 price for pullback entry
     pub extended_percent: f64,    // How much above MA30 (if extended)
     pub signal_strength: f64,     // Signal strength score (0-1)
+
+    // Liquidity
+    pub avg_daily_dollar_volume: Option<f64>, // Trailing average daily dollar volume
+    pub max_shares_by_liquidity: Option<f64>, // Max shares at the ADV participation cap (None: uncapped)
+    pub is_illiquid: bool,                    // Liquidity cap binds the position size
+
+    // Account
+    pub currency: String,         // Currency of every value above
+    pub equity: f64,              // Account equity the plan is sized against
+    pub cash: f64,                // Free cash before this order
+    pub held_shares: f64,         // Units already held
+    pub max_shares_by_cash: f64,  // Held units plus what free cash buys
+    pub order_shares: f64,        // Units to buy (negative: sell) to reach the recommendation
+
+    // Exchange constraints
+    pub instrument: Instrument, // Lot, tick and minimums sizes and prices are rounded to
+    pub below_minimum: bool,    // Size rounds below the minimum quantity or notional
 }
 
 impl TradePlan {
-    /// Create a `TradePlan` from analysis with AI-powered insights
+    /// Create a `TradePlan` from analysis with AI-powered insights, sized against `account`
+    /// (marked to the latest closes) in lots and ticks of `instrument`. A `position` already in
+    /// the daemon's book supplies the stops, R and target of the open trade.
     ///
     /// # Errors
     /// Returns an error if AI insights cannot be generated or if data processing fails.
-    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    pub async fn from_analysis(analysis: &StrategyAnalysis, rank: usize) -> Result<Self> {
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::too_many_arguments)]
+    pub async fn from_analysis(
+        analysis: &StrategyAnalysis,
+        rank: usize,
+        args: &TradeArgs,
+        account: &Account,
+        instrument: &Instrument,
+        position: Option<&Position>,
+        ai: &AiSettings,
+        regime: Option<&RegimeState>,
+    ) -> Result<Self> {
         let asset = analysis.asset().clone();
         let stats = analysis;
+        let profit_target_r = args.profit_target_r.required("profit_target_r")?;
 
         // Determine conviction based on performance metrics
         let (high_conviction, medium_conviction, conviction_rationale) =
             determine_conviction(stats);
 
         // Determine execution mode based on asset characteristics
-        let execution_mode = determine_execution_mode(&asset, stats);
+        let execution_mode =
+            determine_execution_mode(&asset, stats, args.extended_threshold.required("extended_threshold")?);
 
         // Generate initial computed values for risk assessment
         let initial_computed_values =
-            generate_computed_values(&asset, stats, &execution_mode, 0.01); // Use default 1% for initial calculation
+            generate_computed_values(&asset, stats, &execution_mode, 0.01, args, account, instrument, position)?; // Use default 1% for initial calculation
 
         // Determine risk cap based on quantitative analysis
         let risk_cap = determine_risk_cap(&asset, stats, &initial_computed_values);
 
         // Generate final computed values with proper risk cap
-        let computed_values = generate_computed_values(&asset, stats, &execution_mode, risk_cap);
+        let computed_values = generate_computed_values(
+            &asset,
+            stats,
+            &execution_mode,
+            risk_cap,
+            args,
+            account,
+            instrument,
+            position,
+        )?;
 
         // Generate AI-powered asset-specific notes
-        let notes = match generate_asset_notes_ai(&asset, stats, &computed_values).await {
+        let notes = match generate_asset_notes_ai(&asset, stats, &computed_values, regime, ai).await {
             Ok(ai_notes) => ai_notes,
             Err(e) => {
-                println!(
+                eprintln!(
                     "⚠️  AI insights failed for {asset}: {e}. Using fallback analysis."
                 );
                 generate_asset_notes(&asset, stats, rank)
@@ -192,9 +248,17 @@ impl TradePlan {
                 },
             },
             exit_rules: ExitRules {
-                profit_taking: "Scale 50% at +2R (R = initial risk from entry to stop), then trail the rest".to_string(),
-                stop_loss: "Initial stop: close – 3.0 × ATR14 (fallback: close × (1 − 2.5 × rolling_std14))".to_string(),
-                trailing_stop: "Ratchet stop to max(prior stop, close – 3.0 × ATR14) each day".to_string(),
+                profit_taking: format!(
+                    "Scale 50% at +{profit_target_r}R (R = initial risk from entry to stop), then trail the rest"
+                ),
+                stop_loss: format!(
+                    "Initial stop: close – {:.1} × ATR14 (fallback: close × (1 − 2.5 × rolling_std14))",
+                    computed_values.stop_distance_atr
+                ),
+                trailing_stop: format!(
+                    "Ratchet stop to max(prior stop, close – {:.1} × ATR14) each day",
+                    computed_values.stop_distance_atr
+                ),
                 hard_exit_conditions: "Hard exit if close < MA30 or RS flips bearish (RS_MA7 < RS_MA30)".to_string(),
             },
             position_sizing: PositionSizing {
@@ -202,13 +266,13 @@ impl TradePlan {
                 half_weight: 0.5,
                 risk_cap_percent: risk_cap * 100.0,
                 risk_calculation: format!(
-                    "R = entry_price − stop_price; units = min(raw_weight_normalized × portfolio_value / entry_price, ({}% × portfolio_value) / R) \
-                    Risk per share: ${:.2}, Max shares by risk: {:.0}, Max shares by position: {:.0}, Recommended: {:.0}",
+                    "R = entry_price − stop_price; units = min(raw_weight_normalized × equity / entry_price, ({}% × equity) / R, held + cash / entry_price), rounded down to the exchange lot \
+                    Risk per share: ${:.2}, Max shares by risk: {:.2}, Max shares by position: {:.2}, Recommended: {}",
                     risk_cap * 100.0,
                     computed_values.risk_per_share,
                     computed_values.max_shares_by_risk,
                     computed_values.max_shares_by_position,
-                    computed_values.recommended_shares
+                    computed_values.instrument.format_quantity(computed_values.recommended_shares)
                 ),
             },
             conviction: Conviction {
@@ -219,19 +283,24 @@ impl TradePlan {
             backtest_stats: BacktestStats {
                 total_return_percent: stats.total_return() * 100.0,
                 sharpe_ratio: stats.sharpe_ratio(),
+                probabilistic_sharpe_percent: stats.psr() * 100.0,
+                deflated_sharpe_percent: stats.dsr() * 100.0,
                 win_rate_percent: stats.win_rate() * 100.0,
                 max_drawdown_percent: stats.max_drawdown() * 100.0,
                 trading_days: stats.trading_days(),
                 expected_return: format!(
-                    "+{:.2}%, Sharpe {:.2}, Win {:.1}%, MaxDD {:.2}%, {} days",
+                    "+{:.2}%, Sharpe {:.2} (PSR {:.0}%, DSR {:.0}%), Win {:.1}%, MaxDD {:.2}%, {} days",
                     stats.total_return() * 100.0,
                     stats.sharpe_ratio(),
+                    stats.psr() * 100.0,
+                    stats.dsr() * 100.0,
                     stats.win_rate() * 100.0,
                     stats.max_drawdown() * 100.0,
                     stats.trading_days()
                 ),
             },
             computed_values,
+            regime: regime.map(RegimeState::describe),
             notes,
         })
     }
@@ -252,6 +321,35 @@ impl TradePlan {
         playbook.push(format!("Size: Full (3/3) or Half (2/3+RS). Cap single-name risk at {:.1}% of equity (position = {:.1}% / R).", self.position_sizing.risk_cap_percent * 100.0, self.position_sizing.risk_cap_percent * 100.0));
         playbook.push(format!("Conviction: High ({:.0}%) on 3/3; Medium ({:.0}%) on 2/3+RS.", self.conviction.high_conviction * 100.0, self.conviction.medium_conviction * 100.0));
         playbook.push(format!("Expected: {}", self.backtest_stats.expected_return));
+        let cv = &self.computed_values;
+        playbook.push(format!(
+            "Order: {} {} units to reach {} (holding {}; {:.1}% of {:.0} {} equity)",
+            if cv.order_shares < 0.0 { "sell" } else { "buy" },
+            cv.instrument.format_quantity(cv.order_shares.abs()),
+            cv.instrument.format_quantity(cv.recommended_shares),
+            cv.instrument.format_quantity(cv.held_shares),
+            cv.position_percent * 100.0,
+            cv.equity,
+            cv.currency
+        ));
+        if cv.below_minimum {
+            playbook.push(
+                "Minimum: size rounds below the exchange minimum quantity or notional; no order."
+                    .to_string(),
+            );
+        }
+        if let Some(regime) = &self.regime {
+            playbook.push(format!("Regime: {regime}"));
+        }
+        if self.computed_values.is_illiquid {
+            playbook.push(format!(
+                "Liquidity: ILLIQUID, size capped at {} units by average daily dollar volume (${:.0}).",
+                self.computed_values
+                    .instrument
+                    .format_quantity(self.computed_values.max_shares_by_liquidity.unwrap_or(0.0)),
+                self.computed_values.avg_daily_dollar_volume.unwrap_or(0.0)
+            ));
+        }
         playbook.push(format!("Notes: {}", self.notes));
         
         println!("{}) {}", rank, self.asset);
@@ -305,7 +403,10 @@ impl TradePlan {
             "     - Max Shares by Position: {:.0}",
             cv.max_shares_by_position
         );
-        println!("     - Recommended Shares: {}", cv.recommended_shares);
+        println!(
+            "     - Recommended Shares: {}",
+            cv.instrument.format_quantity(cv.recommended_shares)
+        );
         println!("     - Position Value: ${:.2}", cv.position_value);
         println!(
             "     - Position % of Portfolio: {:.1}%",
@@ -317,9 +418,15 @@ impl TradePlan {
             "     - Profit Target: ${:.2} (+{:.1}%)",
             cv.profit_target, cv.profit_target_percent
         );
-        println!("     - Scale Out Shares: {}", cv.scale_out_shares);
+        println!(
+            "     - Scale Out Shares: {}",
+            cv.instrument.format_quantity(cv.scale_out_shares)
+        );
         println!("     - Scale Out Value: ${:.2}", cv.scale_out_value);
-        println!("     - Remaining Shares: {}", cv.remaining_shares);
+        println!(
+            "     - Remaining Shares: {}",
+            cv.instrument.format_quantity(cv.remaining_shares)
+        );
 
         println!("   • Stop Loss:");
         println!(
@@ -342,6 +449,17 @@ impl TradePlan {
         );
         println!("     - MA30 Pullback Price: ${:.2}", cv.ma30_pullback_price);
 
+        println!("   • Liquidity:");
+        match cv.avg_daily_dollar_volume {
+            Some(adv) => println!("     - Avg Daily Dollar Volume: ${adv:.0}"),
+            None => println!("     - Avg Daily Dollar Volume: n/a (no volume data)"),
+        }
+        match cv.max_shares_by_liquidity {
+            Some(cap) => println!("     - Max Shares by Liquidity: {cap:.2}"),
+            None => println!("     - Max Shares by Liquidity: uncapped"),
+        }
+        println!("     - Illiquid: {}", cv.is_illiquid);
+
         println!();
     }
 }
@@ -519,7 +637,12 @@ fn determine_risk_cap(
     (risk_cap * 1000.0).round() / 1000.0
 }
 
-fn determine_execution_mode(_asset: &str, stats: &StrategyAnalysis) -> ExecutionMode {
+/// `extended_threshold` applies to good assets; very stable ones get 1.5x, volatile ones 0.5x.
+fn determine_execution_mode(
+    _asset: &str,
+    stats: &StrategyAnalysis,
+    extended_threshold: f64,
+) -> ExecutionMode {
     // Determine execution mode based on quantitative metrics rather than asset names
 
     // Factor 1: Sharpe Ratio - higher Sharpe indicates more reliable signals
@@ -574,11 +697,11 @@ fn determine_execution_mode(_asset: &str, stats: &StrategyAnalysis) -> Execution
 
     // Adjust extended threshold based on volatility and performance
     let extended_threshold = if stats.max_drawdown() <= 0.05 && stats.sharpe_ratio() >= 1.5 {
-        0.15 // 15% for very stable, high-performing assets
+        extended_threshold * 1.5 // Very stable, high-performing assets
     } else if stats.max_drawdown() <= 0.10 && stats.sharpe_ratio() >= 1.0 {
-        0.10 // 10% for good performing assets
+        extended_threshold // Good performing assets
     } else {
-        0.05 // 5% for volatile or lower-performing assets
+        extended_threshold * 0.5 // Volatile or lower-performing assets
     };
 
     // Adjust limit order duration based on confidence
@@ -598,17 +721,21 @@ fn determine_execution_mode(_asset: &str, stats: &StrategyAnalysis) -> Execution
     }
 }
 
-#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::too_many_arguments)]
 fn generate_computed_values(
-    _asset: &str,
+    asset: &str,
     stats: &StrategyAnalysis,
     execution_mode: &ExecutionMode,
     risk_cap: f64,
-) -> ComputedValues {
+    args: &TradeArgs,
+    account: &Account,
+    instrument: &Instrument,
+    position: Option<&Position>,
+) -> Result<ComputedValues> {
     // Get the latest signal data for actual market values
     let signals = stats.signals();
     if signals.is_empty() {
-        return ComputedValues::default();
+        return Ok(ComputedValues::default());
     }
 
     let latest = &signals[signals.len() - 1];
@@ -629,39 +756,69 @@ fn generate_computed_values(
     let all_signals = trend_signal && momentum_signal && rs_signal;
     let partial_signals = rs_signal && (trend_signal || momentum_signal);
 
-    // Position sizing calculations (assuming $100k portfolio for now)
-    let portfolio_value = 100_000.0;
-    let stop_price = 3.0f64.mul_add(-atr_14, current_price);
+    // Position sizing calculations, against the account's marked equity
+    let equity = account.equity();
+    let held_shares = account.quantity(asset);
+    let stop_distance_atr = args.stop_atr_mult.required("stop_atr_mult")?;
+    let profit_target_r = args.profit_target_r.required("profit_target_r")?;
+    let max_adv_fraction = args.max_adv_fraction.required("max_adv_fraction")?;
+    let stop_price = instrument.price_down(stop_distance_atr.mul_add(-atr_14, current_price));
     let risk_per_share = current_price - stop_price;
-    let max_shares_by_risk = (portfolio_value * risk_cap) / risk_per_share;
+    let max_shares_by_risk = (equity * risk_cap) / risk_per_share;
     let max_position_percent = risk_cap / (risk_per_share / current_price).max(0.01);
-    let max_shares_by_position = (portfolio_value * max_position_percent.min(1.0)) / current_price;
-    let recommended_shares = max_shares_by_risk.min(max_shares_by_position).floor() as u64;
-    let position_value = recommended_shares as f64 * current_price;
-    let position_percent = position_value / portfolio_value;
-
-    // Profit taking calculations
-    let profit_target = 2.0f64.mul_add(risk_per_share, current_price);
+    let max_shares_by_position = (equity * max_position_percent.min(1.0)) / current_price;
+    // Buying beyond free cash is not possible; what is already held needs no cash
+    let max_shares_by_cash = held_shares + account.cash.max(0.0) / current_price;
+
+    // Liquidity cap: never plan more than a fraction of trailing average daily dollar volume
+    let avg_daily_dollar_volume = latest.adv();
+    let max_shares_by_liquidity = avg_daily_dollar_volume
+        .filter(|_| max_adv_fraction > 0.0)
+        .map(|adv| max_adv_fraction * adv / current_price);
+    let unconstrained_shares = max_shares_by_risk.min(max_shares_by_position);
+    let is_illiquid = max_shares_by_liquidity.is_some_and(|cap| cap < unconstrained_shares);
+    let sized_shares = unconstrained_shares
+        .min(max_shares_by_liquidity.unwrap_or(f64::INFINITY))
+        .min(max_shares_by_cash);
+    // Whole lots of the exchange step; nothing if that is below its minimum order
+    let recommended_shares = instrument.quantity(sized_shares, current_price);
+    let below_minimum = recommended_shares == 0.0 && sized_shares > 0.0;
+    let order_shares = instrument.round_quantity(recommended_shares - held_shares);
+    let position_value = recommended_shares * current_price;
+    let position_percent = position_value / equity;
+
+    // Profit taking calculations; a position in the book keeps the target of its entry and
+    // sells nothing more once it has scaled out
+    let profit_target = position.map_or_else(
+        || instrument.price_up(profit_target_r.mul_add(risk_per_share, current_price)),
+        |p| p.target(&BookRules { profit_target_r }),
+    );
     let profit_target_percent = (profit_target / current_price - 1.0) * 100.0;
-    let scale_out_shares = (recommended_shares as f64 * 0.5) as u64;
-    let remaining_shares = recommended_shares - scale_out_shares;
-    let scale_out_value = scale_out_shares as f64 * profit_target;
+    let scale_out_shares = if position.is_some_and(|p| p.scaled_out) {
+        0.0
+    } else {
+        instrument.quantity(recommended_shares * 0.5, profit_target)
+    };
+    let remaining_shares = instrument.round_quantity(recommended_shares - scale_out_shares);
+    let scale_out_value = scale_out_shares * profit_target;
 
-    // Stop loss levels
-    let initial_stop = stop_price;
-    let stop_loss_percent = (1.0 - stop_price / current_price) * 100.0;
-    let trailing_stop = stop_price; // Will be updated daily
-    let stop_distance_atr = 3.0;
+    // Stop loss levels: the book's entry stop and the stop it has ratcheted to since
+    let (initial_stop, initial_r, trailing_stop) = position.map_or(
+        (stop_price, risk_per_share, stop_price),
+        |p| (p.initial_stop, p.initial_r, p.trailing_stop),
+    );
+    let entry_price = position.map_or(current_price, |p| p.entry_price);
+    let stop_loss_percent = initial_r / entry_price * 100.0;
 
     // Risk management
-    let portfolio_risk = (recommended_shares as f64 * risk_per_share) / portfolio_value;
+    let portfolio_risk = (recommended_shares * risk_per_share) / equity;
     let risk_reward_ratio = (profit_target - current_price) / risk_per_share;
-    let max_loss = recommended_shares as f64 * risk_per_share;
-    let max_gain = recommended_shares as f64 * (profit_target - current_price);
+    let max_loss = recommended_shares * risk_per_share;
+    let max_gain = recommended_shares * (profit_target - current_price);
 
     // Execution parameters
     let is_extended = current_price > ma30 * (1.0 + execution_mode.extended_threshold);
-    let ma30_pullback_price = ma30;
+    let ma30_pullback_price = instrument.price_down(ma30);
     let extended_percent = if is_extended {
         (current_price / ma30 - 1.0) * 100.0
     } else {
@@ -675,7 +832,7 @@ fn generate_computed_values(
         0.0
     };
 
-    ComputedValues {
+    Ok(ComputedValues {
         // Current market data
         current_price,
         ma30,
@@ -710,6 +867,7 @@ fn generate_computed_values(
 
         // Stop loss levels
         initial_stop,
+        initial_r,
         stop_loss_percent,
         trailing_stop,
         stop_distance_atr,
@@ -725,7 +883,24 @@ fn generate_computed_values(
         ma30_pullback_price,
         extended_percent,
         signal_strength,
-    }
+
+        // Liquidity
+        avg_daily_dollar_volume,
+        max_shares_by_liquidity,
+        is_illiquid,
+
+        // Account
+        currency: account.currency.clone(),
+        equity,
+        cash: account.cash,
+        held_shares,
+        max_shares_by_cash,
+        order_shares,
+
+        // Exchange constraints
+        instrument: *instrument,
+        below_minimum,
+    })
 }
 
 impl Default for ComputedValues {
@@ -747,15 +922,16 @@ impl Default for ComputedValues {
             risk_per_share: 0.0,
             max_shares_by_risk: 0.0,
             max_shares_by_position: 0.0,
-            recommended_shares: 0,
+            recommended_shares: 0.0,
             position_value: 0.0,
             position_percent: 0.0,
             profit_target: 0.0,
             profit_target_percent: 0.0,
-            scale_out_shares: 0,
-            remaining_shares: 0,
+            scale_out_shares: 0.0,
+            remaining_shares: 0.0,
             scale_out_value: 0.0,
             initial_stop: 0.0,
+            initial_r: 0.0,
             stop_loss_percent: 0.0,
             trailing_stop: 0.0,
             stop_distance_atr: 0.0,
@@ -767,6 +943,17 @@ impl Default for ComputedValues {
             ma30_pullback_price: 0.0,
             extended_percent: 0.0,
             signal_strength: 0.0,
+            avg_daily_dollar_volume: None,
+            max_shares_by_liquidity: None,
+            is_illiquid: false,
+            currency: String::new(),
+            equity: 0.0,
+            cash: 0.0,
+            held_shares: 0.0,
+            max_shares_by_cash: 0.0,
+            order_shares: 0.0,
+            instrument: Instrument::default(),
+            below_minimum: false,
         }
     }
 }
@@ -809,7 +996,7 @@ fn calculate_volatility(signals: &[crate::analyzer::SignalRow], period: usize) -
     for i in 1..signals.len() {
         let current = &signals[i];
         let previous = &signals[i - 1];
-        let ret = (current.close() / previous.close() - 1.0).ln();
+        let ret = (current.close() / previous.close()).ln();
         returns.push(ret);
     }
 
@@ -832,6 +1019,8 @@ async fn generate_asset_notes_ai(
     asset: &str,
     stats: &StrategyAnalysis,
     computed_values: &ComputedValues,
+    regime: Option<&RegimeState>,
+    ai: &AiSettings,
 ) -> Result<String> {
     // Try to generate AI insights, fall back to basic metrics if API fails
     let metrics = AssetMetrics {
@@ -849,9 +1038,10 @@ async fn generate_asset_notes_ai(
         rs_ma30: computed_values.rs_ma30,
         atr_14: computed_values.atr_14,
         volatility: computed_values.volatility,
+        market_regime: regime.map(RegimeState::describe),
     };
     
-    match generate_asset_insights(&metrics).await {
+    match generate_asset_insights(&metrics, ai).await {
         Ok(insights) => {
             let mut notes = Vec::new();
             notes.extend(insights.trading_notes);
@@ -861,7 +1051,7 @@ async fn generate_asset_notes_ai(
             Ok(notes.join("; "))
         }
         Err(e) => {
-            println!(
+            eprintln!(
                 "⚠️  AI insights unavailable for {asset}: {e}. Using fallback analysis."
             );
             let fallback = generate_fallback_insights(
@@ -905,33 +1095,98 @@ fn generate_asset_notes(_asset: &str, stats: &StrategyAnalysis, _rank: usize) ->
     }
 }
 
-/// Generate the top 10 trading playbooks from signal files.
+/// Latest close of each analyzed asset, for marking an [`Account`].
+#[must_use]
+pub fn latest_prices(analyses: &[StrategyAnalysis]) -> BTreeMap<String, f64> {
+    analyses
+        .iter()
+        .filter_map(|a| a.signals().last().map(|s| (a.asset().clone(), s.close())))
+        .collect()
+}
+
+/// Build the top 10 trading playbooks from in-memory analyses, e.g. those of
+/// [`crate::analyzer::analyze_backtest`], marking `account` to their latest closes and
+/// sizing every plan against it in the lots and ticks of `instruments`. Assets open in
+/// `book` carry its stops.
 ///
 /// # Errors
-/// Returns an error if signal files cannot be read or processed.
-///
-/// # Panics
-/// Panics if `partial_cmp` returns `None` when sorting by total return.
-#[allow(clippy::cast_possible_truncation)]
-pub async fn generate_top_10_playbooks(signals_dir: &str) -> Result<Vec<TradePlan>> {
-    let analyses = analyze_signals_directory(signals_dir)?;
-
-    // Filter profitable strategies and sort by total return
-    let mut profitable: Vec<_> = analyses.iter().filter(|a| a.is_profitable()).collect();
+/// Returns an error if a playbook cannot be built.
+pub async fn top_playbooks(
+    analyses: &[StrategyAnalysis],
+    regime: Option<&RegimeState>,
+    args: &TradeArgs,
+    account: &mut Account,
+    instruments: &Instruments,
+    book: &PositionBook,
+    ai: &AiSettings,
+) -> Result<Vec<TradePlan>> {
+    account.mark(&latest_prices(analyses));
+
+    // Filter profitable strategies whose Sharpe survives deflation by the number of assets
+    // screened, then rank by deflated Sharpe so lucky noise is not promoted
+    let mut profitable: Vec<_> = analyses
+        .iter()
+        .filter(|a| a.is_profitable() && a.dsr() >= MIN_DEFLATED_SHARPE)
+        .collect();
 
-    profitable.sort_by(|a, b| b.total_return().partial_cmp(&a.total_return()).unwrap());
+    profitable.sort_by(|a, b| {
+        b.dsr()
+            .total_cmp(&a.dsr())
+            .then(b.total_return().total_cmp(&a.total_return()))
+    });
 
     // Take top 10
     let mut top_10 = Vec::new();
     for (i, analysis) in profitable.iter().take(10).enumerate() {
-        let playbook = TradePlan::from_analysis(analysis, i + 1).await.unwrap();
-        top_10.push(playbook);
+        let instrument = instruments.get(analysis.asset());
+        top_10.push(
+            TradePlan::from_analysis(
+                analysis,
+                i + 1,
+                args,
+                account,
+                &instrument,
+                book.position(analysis.asset()),
+                ai,
+                regime,
+            )
+            .await?,
+        );
     }
 
     Ok(top_10)
 }
 
-pub fn print_top_10_playbooks(playbooks: &[TradePlan]) {
+/// Generate the top 10 trading playbooks from signal files, sized against `account`,
+/// rounded to the `--instruments` file and carrying the stops of positions open in `book`.
+///
+/// # Errors
+/// Returns an error if signal files or the instruments file cannot be read or processed.
+pub async fn generate_top_10_playbooks(
+    signals_dir: &str,
+    args: &TradeArgs,
+    account: &mut Account,
+    book: &PositionBook,
+    ai: &AiSettings,
+) -> Result<Vec<TradePlan>> {
+    let analyses = analyze_signals_directory(signals_dir)?;
+    let regime = read_latest_regime(&Path::new(signals_dir).join("regime.csv"))?;
+    let instruments = Instruments::from_path(args.instruments.as_deref())?;
+    top_playbooks(&analyses, regime.as_ref(), args, account, &instruments, book, ai).await
+}
+
+/// Print the playbooks with the shared ruleset and execution detail.
+///
+/// # Errors
+/// Returns a configuration error if a stop, target or pullback parameter is not set.
+pub fn print_top_10_playbooks(
+    playbooks: &[TradePlan],
+    args: &TradeArgs,
+    account: &Account,
+) -> Result<()> {
+    let stop_atr_mult = args.stop_atr_mult.required("stop_atr_mult")?;
+    let target_r = args.profit_target_r.required("profit_target_r")?;
+    let extended_threshold = args.extended_threshold.required("extended_threshold")?;
     println!("⸻");
     println!("Top-10 Playbooks");
     println!();
@@ -950,14 +1205,22 @@ pub fn print_top_10_playbooks(playbooks: &[TradePlan]) {
     );
     println!("   • Stops / targets");
     println!(
-        "     • Initial stop: close – 3.0 × ATR14 (fallback: close × (1 − 2.5 × rolling_std14))"
+        "     • Initial stop: close – {stop_atr_mult:.1} × ATR14 (fallback: close × (1 − 2.5 × rolling_std14))"
     );
-    println!("     • Trailing: ratchet stop to max(prior stop, close – 3.0 × ATR14) each day");
     println!(
-        "     • Profit-taking: scale 50% at +2R (R = initial risk from entry to stop), then trail the rest until:"
+        "     • Trailing: ratchet stop to max(prior stop, close – {stop_atr_mult:.1} × ATR14) each day"
+    );
+    println!(
+        "     • Profit-taking: scale 50% at +{target_r}R (R = initial risk from entry to stop), then trail the rest until:"
     );
     println!("     • Hard exit if close < MA30 or RS flips bearish (RS_MA7 < RS_MA30)");
     println!();
+    if let Some(regime) = playbooks.first().and_then(|p| p.regime.as_ref()) {
+        println!("Current market regime: {regime}");
+        println!();
+    }
+    println!("Account: {}", account.describe());
+    println!();
     println!("\"Expected return\" below. Treat as historical sample, not forward projection.");
     println!();
     println!("⸻");
@@ -972,17 +1235,18 @@ pub fn print_top_10_playbooks(playbooks: &[TradePlan]) {
     println!("     • if (trend && momentum && rs) -> full_weight");
     println!("     • else if (rs && (trend || momentum)) -> half_weight");
     println!(
-        "   • For extended names (close / MA30 > 1.10): place GTC limit at MA30 for 24–48h; if unfilled but signals persist, promote to market-on-close next day."
+        "   • For extended names (close / MA30 > {:.2}): place GTC limit at MA30 for 24–48h; if unfilled but signals persist, promote to market-on-close next day.",
+        1.0 + extended_threshold
     );
     println!("   • Position sizing (risk-based):");
     println!("     • R = entry_price − stop_price");
     println!(
-        "     • units = min(raw_weight_normalized × portfolio_value / entry_price, (risk_cap × portfolio_value) / R)"
+        "     • units = min(raw_weight_normalized × equity / entry_price, (risk_cap × equity) / R, held + cash / entry_price)"
     );
     println!("     • Suggested risk caps above per asset (0.75–1.25%).");
     println!("   • Exit mechanics:");
     println!(
-        "     • Scale: when close >= entry + 2R, sell 50%; raise stop to entry or entry + 0.5R."
+        "     • Scale: when close >= entry + {target_r}R, sell 50%; raise stop to entry or entry + 0.5R."
     );
     println!(
         "     • Trend/RS fail (daily close): exit remainder at next bar open (or EOD close, to match backtest granularity)."
@@ -990,6 +1254,7 @@ pub fn print_top_10_playbooks(playbooks: &[TradePlan]) {
     println!(
         "   • Conflict resolver (SOL vs wrappers): on any day where multiple SOL-linked tokens qualify, select the single highest RS_MA7/RS_MA30 spread; keep only that exposure."
     );
+    Ok(())
 }
 
 /// Save playbooks to a JSON file.
@@ -1005,7 +1270,6 @@ pub fn save_playbooks_to_json(playbooks: &[TradePlan], output_path: &str) -> Res
             .collect::<Vec<_>>(),
     )?;
     fs::write(output_path, json)?;
-    println!("Playbooks saved to: {output_path}");
     Ok(())
 }
 
@@ -1013,22 +1277,39 @@ pub fn save_playbooks_to_json(playbooks: &[TradePlan], output_path: &str) -> Res
 ///
 /// # Errors
 /// Returns an error if signal files cannot be processed or if output files cannot be written.
-pub async fn execute(signals_dir: &str, output_json: Option<&str>) -> Result<()> {
+pub async fn execute(args: &TradeArgs, ai: &AiSettings) -> Result<()> {
+    let mut account = Account::from_args(args)?;
+    // Without the daemon there is no position book: stops are those of a fresh entry
+    execute_with(args, &mut account, &PositionBook::default(), ai).await
+}
+
+/// [`execute`] against a given account and position book, e.g. the daemon's.
+///
+/// # Errors
+/// Returns an error if signal files cannot be processed or if output files cannot be written.
+pub async fn execute_with(
+    args: &TradeArgs,
+    account: &mut Account,
+    book: &PositionBook,
+    ai: &AiSettings,
+) -> Result<()> {
+    let signals_dir = args.signals_dir.as_deref().required("signals_dir")?;
     println!("🎯 Generating Top-10 Trading Playbooks");
     println!("Analyzing signals from: {signals_dir}");
     println!();
 
-    let playbooks = generate_top_10_playbooks(signals_dir).await?;
+    let playbooks = generate_top_10_playbooks(signals_dir, args, account, book, ai).await?;
 
     if playbooks.is_empty() {
         println!("❌ No profitable strategies found to generate playbooks!");
         return Ok(());
     }
 
-    print_top_10_playbooks(&playbooks);
+    print_top_10_playbooks(&playbooks, args, account)?;
 
-    if let Some(json_path) = output_json {
+    if let Some(json_path) = args.output_json.as_deref() {
         save_playbooks_to_json(&playbooks, json_path)?;
+        println!("Playbooks saved to: {json_path}");
     }
 
     Ok(())
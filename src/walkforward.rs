@@ -0,0 +1,394 @@
+//! Walk-forward optimization: pick parameters on rolling or anchored training windows and
+//! stitch each pick's following test window into one out-of-sample equity curve.
+
+use crate::error::{Context, Error, Required, Result};
+use chrono::NaiveDate;
+use csv::WriterBuilder;
+use rayon::prelude::*;
+use std::fs;
+
+use crate::metrics::{self, Metrics};
+use crate::strategy;
+use crate::sweep::{self, ParamGrid};
+use crate::{Objective, WalkForwardArgs};
+
+/// Daily series kept from one full-history backtest.
+#[derive(Debug, Clone)]
+pub struct CandidateRun {
+    pub params: Vec<String>,
+    pub dates: Vec<NaiveDate>,
+    pub daily_ret: Vec<f64>,
+    pub turnover: Vec<f64>,
+    pub exposure: Vec<f64>,
+}
+
+impl CandidateRun {
+    /// Index range of `[start, end]` (inclusive dates) in this run.
+    fn range(&self, start: NaiveDate, end: NaiveDate) -> std::ops::Range<usize> {
+        self.dates.partition_point(|d| *d < start)..self.dates.partition_point(|d| *d <= end)
+    }
+
+    /// Metrics over `[start, end]` with equity rebased to 1.0 at the window start.
+    fn window_metrics(&self, start: NaiveDate, end: NaiveDate) -> Metrics {
+        let r = self.range(start, end);
+        let dates = &self.dates[r.clone()];
+        metrics::compute(
+            &self.daily_ret[r.clone()],
+            dates,
+            metrics::periods_per_year(dates),
+            &self.turnover[r.clone()],
+            &self.exposure[r],
+        )
+    }
+}
+
+/// One train/test split and its outcome.
+#[derive(Debug, Clone)]
+pub struct Fold {
+    pub train_start: NaiveDate,
+    pub train_end: NaiveDate,
+    pub test_start: NaiveDate,
+    pub test_end: NaiveDate,
+    pub params: Vec<String>,
+    pub in_sample: Metrics,
+    pub out_of_sample: Metrics,
+}
+
+/// Folds of a walk-forward run and the test slices of their picks, stitched in date order.
+#[derive(Debug, Clone, Default)]
+pub struct WalkForward {
+    pub folds: Vec<Fold>,
+    pub oos_dates: Vec<NaiveDate>,
+    pub oos_ret: Vec<f64>,
+    pub oos_turnover: Vec<f64>,
+    pub oos_exposure: Vec<f64>,
+    /// 1-based fold each out-of-sample day belongs to
+    pub oos_fold: Vec<usize>,
+}
+
+/// Score `m` under `objective` (higher is better).
+#[must_use]
+pub fn objective_value(objective: Objective, m: &Metrics) -> f64 {
+    match objective {
+        Objective::Sharpe => m.sharpe,
+        Objective::Cagr => m.cagr,
+        Objective::Calmar => {
+            if m.max_drawdown > 0.0 {
+                m.cagr / m.max_drawdown
+            } else {
+                m.cagr
+            }
+        }
+        Objective::TotalReturn => m.total_return,
+    }
+}
+
+/// Compound daily returns into an equity curve whose first point is `1 + r[0]`.
+#[must_use]
+pub fn equity_from_returns(rets: &[f64]) -> Vec<f64> {
+    rets.iter()
+        .scan(1.0, |eq, r| {
+            *eq *= 1.0 + r;
+            Some(*eq)
+        })
+        .collect()
+}
+
+/// Split `n` dates into (train, test) index ranges. Rolling windows keep `train` days;
+/// anchored windows always start at 0. The last test window may be shorter.
+#[must_use]
+pub fn fold_ranges(
+    n: usize,
+    train: usize,
+    test: usize,
+    anchored: bool,
+) -> Vec<(std::ops::Range<usize>, std::ops::Range<usize>)> {
+    let mut folds = Vec::new();
+    if train == 0 || test == 0 {
+        return folds;
+    }
+    let mut test_start = train;
+    while test_start < n {
+        let train_start = if anchored { 0 } else { test_start - train };
+        let test_end = (test_start + test).min(n);
+        folds.push((train_start..test_start, test_start..test_end));
+        test_start += test;
+    }
+    folds
+}
+
+fn params_label(grids: &[ParamGrid], params: &[String]) -> String {
+    grids
+        .iter()
+        .zip(params)
+        .map(|(g, v)| format!("{}={v}", g.name))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pick the best of `runs` under `objective` on each training window of `train_days` (from
+/// the first date if `anchored`) and take its returns over the following `test_days`.
+///
+/// # Errors
+/// Returns an error if there are no runs or too few days for one fold.
+pub fn walk_forward(
+    runs: &[CandidateRun],
+    train_days: usize,
+    test_days: usize,
+    anchored: bool,
+    objective: Objective,
+) -> Result<WalkForward> {
+    // Calendars differ only if a combination drops assets; fold on the longest one
+    let Some(calendar) = runs.iter().map(|r| &r.dates).max_by_key(|d| d.len()).cloned() else {
+        return Err(Error::data("no parameter combination produced a backtest"));
+    };
+
+    let splits = fold_ranges(calendar.len(), train_days, test_days, anchored);
+    if splits.is_empty() {
+        return Err(Error::data(format!(
+            "need more than {} days for one fold, have {}",
+            train_days,
+            calendar.len()
+        )));
+    }
+
+    let mut wf = WalkForward {
+        folds: Vec::with_capacity(splits.len()),
+        ..WalkForward::default()
+    };
+    for (k, (train, test)) in splits.iter().enumerate() {
+        let (train_start, train_end) = (calendar[train.start], calendar[train.end - 1]);
+        let (test_start, test_end) = (calendar[test.start], calendar[test.end - 1]);
+
+        let Some((best, in_sample)) = runs
+            .iter()
+            .map(|r| (r, r.window_metrics(train_start, train_end)))
+            .max_by(|a, b| {
+                objective_value(objective, &a.1).total_cmp(&objective_value(objective, &b.1))
+            })
+        else {
+            continue;
+        };
+
+        let r = best.range(test_start, test_end);
+        wf.oos_dates.extend_from_slice(&best.dates[r.clone()]);
+        wf.oos_ret.extend_from_slice(&best.daily_ret[r.clone()]);
+        wf.oos_turnover.extend_from_slice(&best.turnover[r.clone()]);
+        wf.oos_exposure.extend_from_slice(&best.exposure[r.clone()]);
+        wf.oos_fold.extend(std::iter::repeat_n(k + 1, r.len()));
+
+        wf.folds.push(Fold {
+            train_start,
+            train_end,
+            test_start,
+            test_end,
+            params: best.params.clone(),
+            in_sample,
+            out_of_sample: best.window_metrics(test_start, test_end),
+        });
+    }
+
+    Ok(wf)
+}
+
+/// Execute walk-forward optimization.
+///
+/// Each grid point is backtested once over the full history (signals only use data up to the
+/// decision date), then every fold picks the best point on its training slice and takes that
+/// point's returns on the following test slice.
+///
+/// # Errors
+/// Returns an error if the grid is invalid, data is too short for one fold, or outputs cannot
+/// be written.
+#[allow(clippy::cast_precision_loss, clippy::too_many_lines)]
+pub fn execute(args: &WalkForwardArgs) -> Result<()> {
+    let grids = sweep::parse_grids(&args.grid)?;
+    let train_days = args.train_days.unwrap_or(180);
+    let test_days = args.test_days.unwrap_or(60);
+    let anchored = args.anchored.unwrap_or(false);
+    let objective = args.objective.unwrap_or_default();
+
+    let out_dir = args.base.out.as_ref().required("out")?;
+    fs::create_dir_all(out_dir).context("create out dir")?;
+
+    let data = strategy::load_market_data(&args.base)?;
+    let combos = sweep::combinations(&grids);
+    println!(
+        "🚶 Walk-forward: {} combinations, {} train / {} test days ({}), objective {:?}",
+        combos.len(),
+        train_days,
+        test_days,
+        if anchored { "anchored" } else { "rolling" },
+        objective
+    );
+
+    let results: Vec<(Vec<String>, Result<strategy::Backtest>)> = combos
+        .into_par_iter()
+        .map(|params| {
+            let bt = strategy::run_backtest(&data, &sweep::args_for(&args.base, &grids, &params));
+            (params, bt)
+        })
+        .collect();
+    // Failed combinations are left out of the selection; if none ran, the first error is fatal
+    let mut runs = Vec::with_capacity(results.len());
+    let mut first_error = None;
+    for (params, bt) in results {
+        match bt {
+            Ok(bt) => runs.push(CandidateRun {
+                params,
+                dates: bt.dates,
+                daily_ret: bt.daily_ret,
+                turnover: bt.turnover,
+                exposure: bt.exposure,
+            }),
+            Err(e) => {
+                eprintln!("⚠️  {} failed: {e}", params_label(&grids, &params));
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let (true, Some(e)) = (runs.is_empty(), first_error) {
+        return Err(e);
+    }
+    let WalkForward {
+        folds,
+        oos_dates,
+        oos_ret,
+        oos_turnover,
+        oos_exposure,
+        oos_fold,
+    } = walk_forward(&runs, train_days, test_days, anchored, objective)?;
+
+    // Per-fold parameters and IS/OOS metrics
+    let mut wtr = WriterBuilder::new().from_path(out_dir.join("walkforward_folds.csv"))?;
+    let mut header: Vec<String> = [
+        "fold",
+        "train_start",
+        "train_end",
+        "test_start",
+        "test_end",
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    header.extend(grids.iter().map(|g| g.name.clone()));
+    header.extend(
+        [
+            "is_objective",
+            "is_sharpe",
+            "is_cagr",
+            "is_max_drawdown",
+            "oos_objective",
+            "oos_sharpe",
+            "oos_cagr",
+            "oos_total_return",
+            "oos_max_drawdown",
+        ]
+        .iter()
+        .map(ToString::to_string),
+    );
+    wtr.write_record(&header)?;
+    for (k, f) in folds.iter().enumerate() {
+        let mut rec = vec![
+            (k + 1).to_string(),
+            f.train_start.to_string(),
+            f.train_end.to_string(),
+            f.test_start.to_string(),
+            f.test_end.to_string(),
+        ];
+        rec.extend(f.params.iter().cloned());
+        rec.extend([
+            format!("{:.4}", objective_value(objective, &f.in_sample)),
+            format!("{:.4}", f.in_sample.sharpe),
+            format!("{:.6}", f.in_sample.cagr),
+            format!("{:.6}", f.in_sample.max_drawdown),
+            format!("{:.4}", objective_value(objective, &f.out_of_sample)),
+            format!("{:.4}", f.out_of_sample.sharpe),
+            format!("{:.6}", f.out_of_sample.cagr),
+            format!("{:.6}", f.out_of_sample.total_return),
+            format!("{:.6}", f.out_of_sample.max_drawdown),
+        ]);
+        wtr.write_record(&rec)?;
+    }
+    wtr.flush()?;
+
+    // Stitched out-of-sample equity curve
+    let oos_equity = equity_from_returns(&oos_ret);
+    let mut wtr = WriterBuilder::new().from_path(out_dir.join("oos_equity_curve.csv"))?;
+    wtr.write_record(["date", "equity", "port_ret", "fold"])?;
+    for i in 0..oos_dates.len() {
+        wtr.write_record(&[
+            oos_dates[i].to_string(),
+            format!("{:.8}", oos_equity[i]),
+            format!("{:.8}", oos_ret[i]),
+            oos_fold[i].to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+
+    // In-sample vs out-of-sample degradation
+    let oos = metrics::compute(
+        &oos_ret,
+        &oos_dates,
+        metrics::periods_per_year(&oos_dates),
+        &oos_turnover,
+        &oos_exposure,
+    );
+    let n_folds = folds.len().max(1) as f64;
+    let mean = |f: &dyn Fn(&Fold) -> f64| folds.iter().map(f).sum::<f64>() / n_folds;
+    let is_objective = mean(&|f| objective_value(objective, &f.in_sample));
+    let oos_objective = mean(&|f| objective_value(objective, &f.out_of_sample));
+    let is_sharpe = mean(&|f| f.in_sample.sharpe);
+    let is_cagr = mean(&|f| f.in_sample.cagr);
+    // Walk-forward efficiency: annualized OOS return as a share of annualized IS return
+    let efficiency = if is_cagr.abs() > 0.0 {
+        oos.cagr / is_cagr
+    } else {
+        0.0
+    };
+
+    println!();
+    println!("📋 FOLDS");
+    for (k, f) in folds.iter().enumerate() {
+        println!(
+            "   {:>2}. test {} → {}  IS {:>7.2} / OOS {:>7.2}  {}",
+            k + 1,
+            f.test_start,
+            f.test_end,
+            objective_value(objective, &f.in_sample),
+            objective_value(objective, &f.out_of_sample),
+            params_label(&grids, &f.params)
+        );
+    }
+
+    let summary = format!(
+        "Folds: {}\nMode: {}\nObjective: {:?}\nOOS Days: {}\nOOS Total Return: {:.2}%\nOOS CAGR: {:.2}%\nOOS Sharpe (ann.): {:.2}\nOOS Max Drawdown: {:.2}%\nMean IS Objective: {:.4}\nMean OOS Objective: {:.4}\nObjective Degradation: {:.1}%\nMean IS Sharpe: {:.2}\nMean IS CAGR: {:.2}%\nWalk-Forward Efficiency (OOS CAGR / IS CAGR): {:.2}\n",
+        folds.len(),
+        if anchored { "anchored" } else { "rolling" },
+        objective,
+        oos.days,
+        oos.total_return * 100.0,
+        oos.cagr * 100.0,
+        oos.sharpe,
+        oos.max_drawdown * 100.0,
+        is_objective,
+        oos_objective,
+        if is_objective.abs() > 0.0 {
+            (1.0 - oos_objective / is_objective) * 100.0
+        } else {
+            0.0
+        },
+        is_sharpe,
+        is_cagr * 100.0,
+        efficiency
+    );
+    fs::write(out_dir.join("walkforward_metrics.txt"), &summary)?;
+    println!();
+    println!("{summary}");
+    println!(
+        "💾 Folds, OOS equity curve and summary written to {}",
+        out_dir.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! Calendar alignment: the union calendar, listing dates and the forward-fill limit.
+
+mod common;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use common::date;
+use crypto_momentum_ai::StrategyArgs;
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::strategy::{self, ForwardFill, Series};
+
+/// Closes of `100 + day` on each of `days`, volume 1000.
+fn closes(days: impl Iterator<Item = usize>) -> String {
+    let mut csv = String::from("date,close,volume\n");
+    for d in days {
+        writeln!(csv, "{},{},1000", date(d), 100 + d).unwrap();
+    }
+    csv
+}
+
+/// BTC trades days 2-59 except day 5; LATE lists on day 20; GAP trades from day 0 but
+/// misses days 30-39; OLD has only a day before BTC starts and day 5.
+fn fixture() -> [(&'static str, String); 4] {
+    [
+        ("BTC", closes((2..60).filter(|d| *d != 5))),
+        ("LATE", closes(20..60)),
+        ("GAP", closes((0..60).filter(|d| !(30..40).contains(d)))),
+        ("OLD", closes([1, 5].into_iter())),
+    ]
+}
+
+fn series(csv: &str) -> Series {
+    strategy::parse_series(csv.as_bytes()).unwrap()
+}
+
+#[test]
+fn union_calendar_starts_at_btc_and_takes_every_other_date() {
+    let all: Vec<Series> = fixture().iter().map(|(_, csv)| series(csv)).collect();
+    let calendar = strategy::union_dates(&all);
+    // Days before BTC's first date are dropped; BTC's missing day 5 comes from the others
+    assert_eq!(calendar, (2..60).map(date).collect::<Vec<_>>());
+    assert!(strategy::union_dates(&[]).is_empty());
+}
+
+#[test]
+fn forward_fill_stops_after_max_fill_days() {
+    let gap = series(&fixture()[2].1);
+    let calendar: Vec<_> = (2..60).map(date).collect();
+    let aligned = strategy::align_forward_fill(&gap, &calendar, 3);
+    let at = |day: usize| aligned[day - 2];
+
+    let last = at(29).unwrap();
+    assert!(!last.filled && last.close == 129.0);
+    // Three missing days repeat day 29's close and volume without an intraday range
+    for day in 30..33 {
+        let bar = at(day).unwrap();
+        assert!(bar.filled);
+        assert_eq!(
+            (bar.close, bar.volume, bar.open),
+            (129.0, Some(1000.0), None)
+        );
+    }
+    // Beyond the limit the series is unavailable until it trades again
+    assert!((33..40).all(|day| at(day).is_none()));
+    let back = at(40).unwrap();
+    assert!(!back.filled && back.close == 140.0);
+
+    // Before the first observation there is nothing to fill
+    let mut fill = ForwardFill::default();
+    assert!(fill.step(None, 3).is_none());
+    let late = series(&fixture()[1].1);
+    let aligned = strategy::align_forward_fill(&late, &calendar, 3);
+    assert!(aligned[..18].iter().all(Option::is_none));
+    assert_eq!(aligned[18].map(|b| b.close), Some(120.0));
+}
+
+#[test]
+fn availability_mask_marks_listing_and_long_gaps() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = |name: &str| tmp.path().join(format!("{name}.csv"));
+    for (name, csv) in &fixture()[..3] {
+        fs::write(path(name), csv).unwrap();
+    }
+    let cli = StrategyArgs {
+        btc: Some(path("BTC")),
+        assets: Some(vec![path("LATE"), path("GAP")]),
+        max_fill_days: Some(3),
+        record: Some(false),
+        ..StrategyArgs::default()
+    };
+    let args: StrategyArgs = Resolved::from_sources(None, None, &[])
+        .unwrap()
+        .with_cli("strategy", &cli)
+        .unwrap();
+    let bt = strategy::backtest(&args).unwrap().backtest;
+    let out = tmp.path().join("availability.csv");
+    strategy::write_availability(&out, &bt.dates, &bt.signals).unwrap();
+
+    let mask = mask_of(&out);
+    assert_eq!(mask[0], ["date", "GAP", "LATE"]);
+    assert_eq!(mask.len() - 1, bt.dates.len());
+    // Row 1 is day 2
+    let row = |day: usize| &mask[day - 1];
+    assert_eq!(row(2)[0], date(2).to_string());
+    let expected = |day: usize| {
+        let gap = !(33..40).contains(&day);
+        let late = day >= 20;
+        [u8::from(gap).to_string(), u8::from(late).to_string()]
+    };
+    for day in 2..60 {
+        assert_eq!(row(day)[1..], expected(day), "day {day}");
+    }
+}
+
+fn mask_of(path: &Path) -> Vec<Vec<String>> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|l| l.split(',').map(str::to_string).collect())
+        .collect()
+}
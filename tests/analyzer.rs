@@ -0,0 +1,121 @@
+//! Regression tests for `analyzer::StrategyAnalysis` return accounting.
+//!
+//! Every expected value is computed by hand from the fixtures in `tests/fixtures/analyzer`;
+//! the weight decided at one close earns the next day's close-to-close return.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::{EPS, assert_close};
+use crypto_momentum_ai::analyzer::{self, StrategyAnalysis};
+use crypto_momentum_ai::overfit;
+
+fn fixture(name: &str) -> StrategyAnalysis {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/analyzer")
+        .join(format!("signals_{name}.csv"));
+    let signals = analyzer::read_signals_file(&path).expect("read fixture");
+    StrategyAnalysis::new(name.to_string(), signals)
+}
+
+#[test]
+fn long_only_uses_previous_day_weight() {
+    // closes 100, 110, 99, 99, 100, 102 / weights 1, 1, 0, 0, 0.5, 0
+    let a = fixture("LONG");
+    assert_eq!(a.total_days(), 6);
+    assert_eq!(a.trading_days(), 3);
+    let expected = [0.1, -0.1, 0.01];
+    assert_eq!(a.returns().len(), expected.len());
+    for (r, e) in a.returns().iter().zip(expected) {
+        assert_close(*r, e);
+    }
+    // 1.1 * 0.9 * 1.01 - 1
+    assert_close(a.total_return(), -0.0001);
+    assert_close(a.win_rate(), 2.0 / 3.0);
+    assert_close(a.profit_factor(), 1.1);
+    assert_close(a.max_drawdown(), 0.1);
+    assert_close(a.avg_win(), 0.055);
+    assert_close(a.avg_loss(), -0.1);
+    assert!(!a.is_profitable());
+}
+
+#[test]
+fn long_only_trade_stats() {
+    let t = fixture("LONG").trade_stats().clone();
+    assert_eq!(t.trades, 2);
+    assert_close(t.best, 0.01);
+    assert_close(t.worst, -0.01);
+    assert_close(t.win_rate, 0.5);
+    assert_close(t.avg_return, 0.0);
+    assert_close(t.avg_win, 0.01);
+    assert_close(t.avg_loss, -0.01);
+    assert_close(t.avg_holding_days, 1.5);
+}
+
+#[test]
+fn short_side_and_flip_close_the_trade() {
+    // closes 100, 105, 84, 88.2 / weights 1, -1, -1, 0
+    let a = fixture("FLIP");
+    assert_eq!(a.trading_days(), 3);
+    for (r, e) in a.returns().iter().zip([0.05, 0.2, -0.05]) {
+        assert_close(*r, e);
+    }
+    // 1.05 * 1.2 * 0.95 - 1
+    assert_close(a.total_return(), 0.197);
+    assert_close(a.profit_factor(), 5.0);
+    assert_close(a.max_drawdown(), 0.05);
+
+    let t = a.trade_stats();
+    assert_eq!(t.trades, 2);
+    assert_close(t.worst, 0.05);
+    assert_close(t.best, 0.14);
+    assert_close(t.win_rate, 1.0);
+    assert_close(t.avg_holding_days, 1.5);
+}
+
+#[test]
+fn entry_day_move_is_not_credited() {
+    // The jump into the entry close happened before the position existed.
+    let a = fixture("LOOKAHEAD");
+    assert_eq!(a.trading_days(), 1);
+    assert_close(a.total_return(), 0.0);
+    assert_close(a.max_drawdown(), 0.0);
+    assert_eq!(a.trade_stats().trades, 1);
+    assert_close(a.trade_stats().avg_return, 0.0);
+}
+
+#[test]
+fn directory_analysis_reads_every_signals_file() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/analyzer");
+    let analyses = analyzer::analyze_signals_directory(dir.to_str().unwrap()).unwrap();
+    let mut assets: Vec<&str> = analyses.iter().map(|a| a.asset().as_str()).collect();
+    assets.sort_unstable();
+    assert_eq!(assets, ["FLIP", "LONG", "LOOKAHEAD"]);
+
+    let flip = analyses.iter().find(|a| a.asset() == "FLIP").unwrap();
+    assert_close(flip.total_return(), 0.197);
+    // Deflation can only lower the probability of skill
+    assert!(flip.dsr() <= flip.psr() + EPS);
+
+    // PSR and DSR use the same per-bar series as the Sharpe ratio, flat days included
+    let trials: Vec<&[f64]> = analyses.iter().map(|a| a.bar_returns()).collect();
+    let diagnostics = overfit::sharpe_diagnostics(&trials);
+    for (a, d) in analyses.iter().zip(&diagnostics) {
+        assert_eq!(a.bar_returns().len(), a.total_days() - 1);
+        assert_close(a.psr(), d.psr);
+        assert_close(a.dsr(), d.dsr);
+    }
+    assert!(analyses.iter().any(|a| a.returns().len() < a.bar_returns().len()));
+}
+
+#[test]
+fn benchmarks_need_rs_for_btc_but_always_get_the_universe() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/analyzer");
+    let analyses = analyzer::analyze_signals_directory(dir.to_str().unwrap()).unwrap();
+    for a in &analyses {
+        // Fixtures carry no rs column, so BTC cannot be recovered
+        assert!(a.vs_btc().is_none());
+        assert!(a.vs_universe().is_some());
+    }
+}
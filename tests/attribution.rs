@@ -0,0 +1,63 @@
+//! Hand-computed checks for portfolio attribution.
+
+mod common;
+
+use chrono::NaiveDate;
+
+use common::assert_close;
+use crypto_momentum_ai::attribution::{self, Contribution, SignalState};
+
+fn c(asset: &str, state: SignalState, weight: f64, asset_return: f64) -> Contribution {
+    Contribution {
+        asset: asset.to_string(),
+        state,
+        weight,
+        asset_return,
+    }
+}
+
+#[test]
+fn summarizes_by_asset_state_and_drawdown() {
+    let dates: Vec<NaiveDate> = (1..=4)
+        .map(|d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap())
+        .collect();
+    let contributions = vec![
+        vec![],
+        vec![
+            c("ETH", SignalState::Full, 0.5, 0.1),
+            c("SOL", SignalState::Half, 0.5, 0.02),
+        ],
+        vec![
+            c("ETH", SignalState::Full, 0.5, -0.2),
+            c(attribution::HEDGE, SignalState::Hedge, -0.3, 0.1),
+        ],
+        vec![c("SOL", SignalState::Half, 1.0, 0.05)],
+    ];
+    // Daily totals 0.06, -0.13, 0.05
+    let equity = [1.0, 1.06, 1.06 * 0.87, 1.06 * 0.87 * 1.05];
+    let a = attribution::summarize(&dates, &equity, &contributions);
+
+    let eth = a.by_asset.iter().find(|g| g.group == "ETH").unwrap();
+    assert_eq!(eth.days, 2);
+    assert_close(eth.contribution, -0.05);
+    assert_close(eth.hit_rate, 0.5);
+    // Only day 2 falls inside the drawdown (peak day 1, trough day 2)
+    assert_close(eth.drawdown_contribution, -0.1);
+    assert_eq!(a.drawdown, Some((dates[1], dates[2])));
+
+    let half = a.by_state.iter().find(|g| g.group == "half_2of3_rs").unwrap();
+    assert_eq!(half.days, 2);
+    assert_close(half.contribution, 0.06);
+    assert_close(half.avg_asset_return, 0.035);
+    assert_close(half.avg_weight, 0.75);
+
+    // Hedge is short: its signed asset return is -10%
+    let hedge = a.by_state.iter().find(|g| g.group == "btc_hedge").unwrap();
+    assert_close(hedge.contribution, -0.03);
+    assert_close(hedge.avg_asset_return, -0.1);
+
+    let total: f64 = a.by_asset.iter().map(|g| g.contribution).sum();
+    assert_close(total, 0.06 - 0.13 + 0.05);
+    assert_eq!(a.by_month.len(), 1);
+    assert_close(a.by_month[0].1.values().sum::<f64>(), total);
+}
@@ -0,0 +1,55 @@
+//! Hand-computed checks for benchmark-relative statistics.
+
+mod common;
+
+use common::assert_close;
+use crypto_momentum_ai::benchmark;
+
+#[test]
+fn leveraged_copy_has_beta_two_and_no_alpha() {
+    let bench = [0.01, -0.02, 0.03, -0.01];
+    let rets: Vec<f64> = bench.iter().map(|b| 2.0 * b).collect();
+    let r = benchmark::relative(&rets, &bench, 365.25);
+    assert_close(r.beta, 2.0);
+    assert_close(r.alpha, 0.0);
+    assert_close(r.correlation, 1.0);
+    assert_close(r.up_capture, 2.0);
+    assert_close(r.down_capture, 2.0);
+    // Active return equals the benchmark return
+    assert_close(r.information_ratio, 0.0025 * 365.25 / r.tracking_error);
+}
+
+#[test]
+fn constant_excess_return_is_pure_alpha() {
+    let bench = [0.01, -0.02, 0.03, -0.01];
+    let rets: Vec<f64> = bench.iter().map(|b| b + 0.001).collect();
+    let r = benchmark::relative(&rets, &bench, 100.0);
+    assert_close(r.beta, 1.0);
+    assert_close(r.alpha, 0.1);
+    assert_close(r.tracking_error, 0.0);
+    assert_close(r.information_ratio, 0.0);
+}
+
+#[test]
+fn buy_and_hold_and_equal_weight_skip_missing_closes() {
+    let a = vec![Some(100.0), Some(110.0), None, Some(121.0)];
+    let b = vec![Some(50.0), Some(45.0), Some(45.0), Some(54.0)];
+    let bh = benchmark::buy_and_hold(&a);
+    assert_close(bh[1], 0.1);
+    assert_close(bh[2], 0.0);
+    assert_close(bh[3], 0.0);
+    let ew = benchmark::equal_weight(&[a, b]);
+    assert_close(ew[0], 0.0);
+    assert_close(ew[1], (0.1 - 0.1) / 2.0);
+    // Only the asset with closes on both days counts
+    assert_close(ew[2], 0.0);
+    assert_close(ew[3], 0.2);
+
+    // A shorter series stops contributing at its end instead of panicking
+    let short = vec![Some(10.0), Some(11.0)];
+    let long = vec![Some(20.0), Some(22.0), Some(24.2)];
+    let ew = benchmark::equal_weight(&[short, long]);
+    assert_eq!(ew.len(), 3);
+    assert_close(ew[1], 0.1);
+    assert_close(ew[2], 0.1);
+}
@@ -0,0 +1,53 @@
+//! Fixture factories and assertions shared by the integration tests.
+#![allow(dead_code)]
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+pub const EPS: f64 = 1e-9;
+
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < EPS,
+        "expected {expected}, got {actual}"
+    );
+}
+
+/// `n` dates from 2024-01-01 on.
+pub fn date(n: usize) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(n as i64)
+}
+
+/// 200 daily closes compounding at `drift` with a small alternating wiggle.
+pub fn write_closes(path: &Path, start: f64, drift: f64) {
+    let mut csv = String::from("date,close\n");
+    let mut px = start;
+    for d in 0..200 {
+        px *= 1.0 + drift + if d % 2 == 0 { 0.004 } else { -0.004 };
+        writeln!(csv, "{},{px:.6}", date(d)).unwrap();
+    }
+    fs::write(path, csv).unwrap();
+}
+
+/// `days` of uptrending OHLCV gaining `up` every third day; on `crash` the asset opens 30%
+/// below the prior close.
+pub fn write_ohlc(path: &Path, days: usize, start: f64, up: f64, crash: Option<usize>) {
+    let mut px = start;
+    let mut csv = String::from("date,open,high,low,close,volume\n");
+    for d in 0..days {
+        let open = if crash == Some(d) { px * 0.7 } else { px };
+        px = open * (1.0 + if d % 3 == 0 { up } else { -0.004 });
+        writeln!(
+            csv,
+            "{},{open:.6},{:.6},{:.6},{px:.6},1000000",
+            date(d),
+            open.max(px) * 1.005,
+            open.min(px) * 0.995
+        )
+        .unwrap();
+    }
+    fs::write(path, csv).unwrap();
+}
@@ -0,0 +1,92 @@
+//! Layered configuration: defaults < file < profile < env < CLI.
+
+use std::path::PathBuf;
+
+use crypto_momentum_ai::config::{Resolved, Source};
+use crypto_momentum_ai::{DaemonArgs, SignalMode, StrategyArgs, TradeArgs};
+
+const FILE: &str = r#"
+[strategy]
+ma_long = 10
+btc_hedge = 0.0
+
+[trade]
+portfolio_value = 250000.0
+
+[profiles.fast.strategy]
+ma_short = 2
+mode = "cross-sectional"
+"#;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect()
+}
+
+#[test]
+fn layers_override_in_order() {
+    let env = env(&[
+        ("CMAI_STRATEGY_MA_LONG", "12"),
+        ("CMAI_STRATEGY_ASSETS", r#"["a.csv","b.csv"]"#),
+        ("CMAI_AI_MODEL", "gpt-test"),
+        ("HOME", "/root"),
+    ]);
+    let resolved = Resolved::from_sources(Some(FILE), Some("fast"), &env).unwrap();
+    let strategy = &resolved.config.strategy;
+
+    assert_eq!(strategy.ma_short, Some(2));
+    assert_eq!(resolved.source("strategy", "ma_short"), Some(Source::Profile));
+    assert_eq!(strategy.mode, Some(SignalMode::CrossSectional));
+    assert_eq!(strategy.btc_hedge, Some(0.0));
+    assert_eq!(resolved.source("strategy", "btc_hedge"), Some(Source::File));
+    assert_eq!(strategy.ma_long, Some(12));
+    assert_eq!(resolved.source("strategy", "ma_long"), Some(Source::Env));
+    assert_eq!(
+        strategy.assets,
+        Some(vec![PathBuf::from("a.csv"), PathBuf::from("b.csv")])
+    );
+    assert_eq!(strategy.atr_mult, Some(3.0));
+    assert_eq!(resolved.source("strategy", "atr_mult"), Some(Source::Default));
+    assert_eq!(resolved.config.trade.portfolio_value, Some(250_000.0));
+    assert_eq!(resolved.config.ai.model.as_deref(), Some("gpt-test"));
+
+    // CLI flags win; unset flags keep the layered value
+    let cli = StrategyArgs {
+        ma_long: Some(20),
+        ..StrategyArgs::default()
+    };
+    let merged: StrategyArgs = resolved.with_cli("strategy", &cli).unwrap();
+    assert_eq!(merged.ma_long, Some(20));
+    assert_eq!(merged.ma_short, Some(2));
+
+    let trade: TradeArgs = resolved
+        .with_cli("trade", &TradeArgs::default())
+        .unwrap();
+    assert_eq!(trade.stop_atr_mult, Some(3.0));
+    let daemon: DaemonArgs = resolved
+        .with_cli("daemon", &DaemonArgs::default())
+        .unwrap();
+    assert_eq!(daemon.check_interval, Some(60));
+}
+
+#[test]
+fn rejects_unknown_keys_profiles_and_bad_values() {
+    assert!(Resolved::from_sources(Some("[strategy]\nma_lng = 3\n"), None, &[]).is_err());
+    assert!(Resolved::from_sources(Some("[stratgy]\nma_long = 3\n"), None, &[]).is_err());
+    assert!(Resolved::from_sources(Some(FILE), Some("slow"), &[]).is_err());
+    assert!(Resolved::from_sources(None, None, &env(&[("CMAI_TRADE_PORTFOLIO", "1")])).is_err());
+    assert!(
+        Resolved::from_sources(None, None, &env(&[("CMAI_STRATEGY_MA_LONG", "abc")])).is_err()
+    );
+}
+
+#[test]
+fn show_masks_secrets() {
+    let env = env(&[("CMAI_OHLC_API_KEY", "secret-key")]);
+    let resolved = Resolved::from_sources(None, None, &env).unwrap();
+    assert_eq!(resolved.config.ohlc.api_key.as_deref(), Some("secret-key"));
+    let shown = resolved.render();
+    assert!(!shown.contains("secret-key"));
+    assert!(shown.contains("[daemon]"));
+}
@@ -0,0 +1,108 @@
+//! Order simulation: fills at the prices each execution style promises, and stops that gap.
+
+mod common;
+
+use std::path::Path;
+
+use common::write_ohlc;
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::engine::{self, Event, OrderType, Purpose, Rules};
+use crypto_momentum_ai::strategy::Backtest;
+use crypto_momentum_ai::{ExecutionStyle, StrategyArgs, strategy};
+
+fn backtest(dir: &Path, crash: Option<usize>) -> Backtest {
+    let (btc, eth) = (dir.join("BTC.csv"), dir.join("ETH_ethereum.csv"));
+    write_ohlc(&btc, 200, 100.0, 0.03, None);
+    write_ohlc(&eth, 200, 10.0, 0.03, crash);
+    let cli = StrategyArgs {
+        btc: Some(btc),
+        assets: Some(vec![eth]),
+        out: Some(dir.join("out")),
+        ma_short: Some(5),
+        ma_long: Some(20),
+        record: Some(false),
+        ..StrategyArgs::default()
+    };
+    let args: StrategyArgs = Resolved::from_sources(None, None, &[])
+        .unwrap()
+        .with_cli("strategy", &cli)
+        .unwrap();
+    strategy::backtest(&args).unwrap().backtest
+}
+
+const fn rules(style: ExecutionStyle) -> Rules {
+    Rules {
+        style,
+        extended_threshold: 0.02,
+        limit_hours: 48,
+        profit_target_r: 0.0,
+    }
+}
+
+#[test]
+fn execution_styles_fill_where_they_promise() {
+    let tmp = tempfile::tempdir().unwrap();
+    let bt = backtest(tmp.path(), None);
+
+    let moc = engine::simulate(&bt, rules(ExecutionStyle::MarketOnClose));
+    assert!(!moc.trades.is_empty());
+    assert!(moc.trades.iter().all(|t| t.entry_price == t.signal_close));
+
+    let next = engine::simulate(&bt, rules(ExecutionStyle::NextOpen));
+    for t in &next.trades {
+        let i = bt.dates.iter().position(|d| *d == t.entry_date).unwrap();
+        assert_eq!(Some(t.entry_price), bt.bars["ETH_ethereum"][i].unwrap().open);
+    }
+
+    // Pullback limits rest at MA30 (here the long MA) and fill at or below it, or expire
+    let pullback = engine::simulate(&bt, rules(ExecutionStyle::Pullback));
+    let mut limits = std::collections::BTreeMap::new();
+    for event in &pullback.events {
+        match event {
+            Event::Submitted(o) if o.purpose == Purpose::Entry => {
+                if let OrderType::Limit(p) = o.kind {
+                    assert!(o.expires.is_some_and(|e| e > o.submitted));
+                    limits.insert(o.id, p);
+                }
+            }
+            Event::Filled(f) if f.purpose == Purpose::Entry => {
+                if let Some(limit) = limits.get(&f.order_id) {
+                    assert!(f.price <= *limit);
+                }
+            }
+            _ => {}
+        }
+    }
+    assert!(!limits.is_empty());
+    let s = &pullback.summary;
+    assert!(s.entries_expired > 0 || s.entries_filled > 0);
+    assert!(s.avg_entry_slippage <= 0.0);
+}
+
+#[test]
+fn gap_down_stop_fills_at_the_open() {
+    let tmp = tempfile::tempdir().unwrap();
+    // Crash on the second day of a position held for several days in the smooth series
+    let smooth = backtest(tmp.path(), None);
+    let held = engine::simulate(&smooth, rules(ExecutionStyle::MarketOnClose))
+        .trades
+        .into_iter()
+        .find(|t| (t.exit_date - t.entry_date).num_days() >= 3 && t.initial_stop.is_some())
+        .expect("a multi-day trade");
+    let day = smooth.dates.iter().position(|d| *d == held.entry_date).unwrap() + 1;
+
+    let bt = backtest(tmp.path(), Some(day));
+    let sim = engine::simulate(&bt, rules(ExecutionStyle::MarketOnClose));
+    let stopped = sim
+        .trades
+        .iter()
+        .find(|t| t.entry_date == held.entry_date)
+        .unwrap();
+    assert_eq!((stopped.exit_date, stopped.exit_reason), (bt.dates[day], "stop"));
+    assert_eq!(
+        Some(stopped.exit_price),
+        bt.bars["ETH_ethereum"][day].unwrap().open
+    );
+    assert!(stopped.exit_price < stopped.initial_stop.unwrap());
+    assert_eq!(sim.summary.stop_gaps, 1);
+}
@@ -0,0 +1,81 @@
+//! Library failures carry a class the daemon can act on instead of panicking.
+
+use std::fs;
+use std::time::Duration;
+
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::error::{Context, Error, Recovery};
+use crypto_momentum_ai::{StrategyArgs, strategy, sweep};
+
+fn backtest_error(args: &StrategyArgs) -> Error {
+    match strategy::backtest(args) {
+        Ok(_) => panic!("backtest should fail"),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn failures_are_classified() {
+    let tmp = tempfile::tempdir().unwrap();
+    let btc = tmp.path().join("BTC.csv");
+    fs::write(&btc, "date,close\n2024-01-01,100\n2024-01-02,101\n").unwrap();
+    let resolved = Resolved::from_sources(None, None, &[]).unwrap();
+    let args = |assets| StrategyArgs {
+        btc: Some(btc.clone()),
+        assets: Some(assets),
+        ..StrategyArgs::default()
+    };
+
+    // Settings that were never defaulted are configuration errors, not panics
+    let err = backtest_error(&args(vec![]));
+    assert!(matches!(err, Error::Config(ref m) if m.contains("ma_long")), "{err}");
+    assert_eq!(err.recovery(), Recovery::Abort);
+
+    // Too little history is bad data for this cycle only
+    let full: StrategyArgs = resolved.with_cli("strategy", &args(vec![])).unwrap();
+    let err = backtest_error(&full);
+    assert!(matches!(err, Error::Data(_)), "{err}");
+    assert_eq!(err.recovery(), Recovery::Skip);
+
+    // A missing input file is an I/O error naming the file
+    let missing = tmp.path().join("MISSING.csv");
+    let err = backtest_error(&StrategyArgs {
+        assets: Some(vec![missing]),
+        ..full.clone()
+    });
+    assert!(matches!(err, Error::Io { .. }), "{err}");
+    assert!(err.to_string().contains("MISSING.csv"));
+
+    // A malformed CSV row is a data error
+    let bad = tmp.path().join("BAD.csv");
+    fs::write(&bad, "date,close\n2024-01-01,abc\n").unwrap();
+    let err = backtest_error(&StrategyArgs {
+        assets: Some(vec![bad]),
+        ..full
+    });
+    assert!(matches!(err, Error::Data(_)), "{err}");
+
+    assert!(matches!(sweep::parse_grid("ma_short=5:1:1"), Err(Error::Config(_))));
+    assert!(matches!(
+        Resolved::from_sources(Some("[strategy]\nma_lng = 3\n"), None, &[]),
+        Err(Error::Config(_))
+    ));
+}
+
+#[test]
+fn recovery_and_context_keep_the_class() {
+    let limited = Error::RateLimited {
+        message: "HTTP 429".to_string(),
+        retry_after: Some(Duration::from_secs(30)),
+    };
+    assert_eq!(limited.recovery(), Recovery::Retry(Some(Duration::from_secs(30))));
+    assert_eq!(Error::network("timeout").recovery(), Recovery::Retry(None));
+    assert_eq!(Error::ai("bad JSON").recovery(), Recovery::Skip);
+
+    let io: Result<(), std::io::Error> = Err(std::io::ErrorKind::NotFound.into());
+    let err = io.context("read BTC").unwrap_err();
+    assert_eq!(err.class(), "io");
+    assert!(err.to_string().starts_with("read BTC"));
+    let err = Error::network("HTTP 503").context("fetch bitcoin");
+    assert_eq!(err.to_string(), "network error: fetch bitcoin: HTTP 503");
+}
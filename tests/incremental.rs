@@ -0,0 +1,160 @@
+//! The daemon's incremental signal engine must match a full recompute exactly.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::regime::RegimeState;
+use crypto_momentum_ai::{SignalMode, StrategyArgs, incremental, strategy};
+
+/// OHLCV rows for `days` days compounding at `drift`, omitting the days in `gap`.
+fn rows(days: i64, start: f64, drift: f64, gap: std::ops::Range<i64>) -> Vec<String> {
+    let mut px = start;
+    let mut out = vec!["date,open,high,low,close,volume".to_string()];
+    for d in 0..days {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(d);
+        let open = px;
+        px *= 1.0 + drift + if d % 3 == 0 { 0.01 } else { -0.004 };
+        if gap.contains(&d) {
+            continue;
+        }
+        let mut row = String::new();
+        write!(
+            row,
+            "{date},{open:.6},{:.6},{:.6},{px:.6},{:.2}",
+            open.max(px) * 1.01,
+            open.min(px) * 0.99,
+            1000.0 + (d % 7) as f64 * 100.0
+        )
+        .unwrap();
+        out.push(row);
+    }
+    out
+}
+
+/// Write the first `n` data rows (all if `None`).
+fn write(path: &Path, rows: &[String], n: Option<usize>) {
+    let n = n.map_or(rows.len(), |n| n + 1);
+    fs::write(path, rows[..n].join("\n") + "\n").unwrap();
+}
+
+fn regimes(path: &Path) -> Vec<RegimeState> {
+    csv::Reader::from_path(path)
+        .unwrap()
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn assert_matches_full(args: &StrategyArgs, out: &Path) {
+    let full = tempfile::tempdir().unwrap();
+    let result = strategy::backtest(args).unwrap();
+    strategy::write_outputs(&result, full.path()).unwrap();
+    for name in result.backtest.signals.keys() {
+        let file = format!("signals_{name}.csv");
+        assert_eq!(
+            fs::read_to_string(out.join(&file)).unwrap(),
+            fs::read_to_string(full.path().join(&file)).unwrap(),
+            "{file}"
+        );
+    }
+    // Regimes are reclassified over a trailing window, so rolling sums may differ in the last bit
+    let (inc, full) = (
+        regimes(&out.join("regime.csv")),
+        regimes(&full.path().join("regime.csv")),
+    );
+    assert_eq!(inc.len(), full.len());
+    for (a, b) in inc.iter().zip(&full) {
+        assert_eq!((a.date, a.regime, a.btc_trend), (b.date, b.regime, b.btc_trend));
+        let close = |x: Option<f64>, y: Option<f64>| match (x, y) {
+            (Some(x), Some(y)) => (x - y).abs() < 1e-12,
+            (x, y) => x == y,
+        };
+        assert!(close(a.breadth, b.breadth));
+        assert!(close(a.realized_vol, b.realized_vol) && close(a.dispersion, b.dispersion));
+    }
+}
+
+#[test]
+fn appended_bars_match_a_full_recompute() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data = tmp.path().join("data");
+    fs::create_dir(&data).unwrap();
+    let btc = rows(240, 100.0, 0.001, 0..0);
+    let eth = rows(240, 10.0, 0.003, 0..0);
+    // Listed late and gaps past the fill limit in the appended part
+    let sol = rows(240, 5.0, -0.001, 0..20);
+    let sol_gapped: Vec<String> = sol
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !(190..196).contains(i))
+        .map(|(_, r)| r.clone())
+        .collect();
+    let paths = ["BTC.csv", "ETH_ethereum.csv", "SOL_solana.csv"].map(|f| data.join(f));
+
+    let resolved = Resolved::from_sources(None, None, &[]).unwrap();
+    for mode in [SignalMode::Threshold, SignalMode::CrossSectional] {
+        let out = tmp.path().join(format!("out_{mode:?}"));
+        let cli = StrategyArgs {
+            btc: Some(paths[0].clone()),
+            assets: Some(paths[1..].to_vec()),
+            out: Some(out.clone()),
+            mode: Some(mode),
+            rank_lookback: Some(20),
+            top_k: Some(1),
+            record: Some(false),
+            ..StrategyArgs::default()
+        };
+        let args: StrategyArgs = resolved.with_cli("strategy", &cli).unwrap();
+
+        write(&paths[0], &btc, Some(150));
+        write(&paths[1], &eth, Some(150));
+        write(&paths[2], &sol_gapped, Some(130));
+        let first = incremental::update(&args).unwrap();
+        assert_eq!(first.recomputed.as_deref(), Some("no saved state"));
+        assert_eq!(first.dates.len(), 150);
+        assert_matches_full(&args, &out);
+
+        // A few cycles of newly appended bars
+        for (n_btc, n_sol) in [(Some(151), Some(131)), (Some(180), Some(160)), (None, None)] {
+            write(&paths[0], &btc, n_btc);
+            write(&paths[1], &eth, n_btc);
+            write(&paths[2], &sol_gapped, n_sol);
+            let update = incremental::update(&args).unwrap();
+            assert_eq!(update.recomputed, None, "{mode:?}");
+            assert!(!update.dates.is_empty());
+            assert_matches_full(&args, &out);
+        }
+        let idle = incremental::update(&args).unwrap();
+        assert_eq!((idle.recomputed, idle.dates.len()), (None, 0));
+
+        // Editing a processed bar forces a full recompute
+        let mut edited = eth.clone();
+        let mut cols: Vec<String> = edited[100].split(',').map(str::to_string).collect();
+        cols[4] = format!("{:.6}", cols[4].parse::<f64>().unwrap() * 1.05);
+        edited[100] = cols.join(",");
+        write(&paths[1], &edited, None);
+        let update = incremental::update(&args).unwrap();
+        assert!(
+            update
+                .recomputed
+                .as_deref()
+                .is_some_and(|r| r.contains("history changed")),
+            "{update:?}"
+        );
+        assert_matches_full(&args, &out);
+        write(&paths[1], &eth, None);
+
+        // So do different settings
+        let other = StrategyArgs {
+            ma_short: Some(5),
+            ..args.clone()
+        };
+        let update = incremental::update(&other).unwrap();
+        assert!(update.recomputed.is_some());
+        assert_matches_full(&other, &out);
+    }
+}
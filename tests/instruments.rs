@@ -0,0 +1,122 @@
+//! Fractional sizes and prices rounded to each asset's lot, tick and minimum order.
+
+mod common;
+
+use std::fs;
+
+use common::write_closes;
+use crypto_momentum_ai::account::Account;
+use crypto_momentum_ai::ai_insights::AiSettings;
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::instruments::{Instrument, Instruments};
+use crypto_momentum_ai::positions::PositionBook;
+use crypto_momentum_ai::{StrategyArgs, TradeArgs, analyzer, strategy, trade};
+
+#[test]
+fn rounding_to_lots_and_ticks() {
+    let lot = Instrument {
+        step_size: Some(0.001),
+        tick_size: Some(0.05),
+        min_qty: Some(0.01),
+        min_notional: Some(5.0),
+    };
+    assert_eq!(lot.quantity(1.23456, 100.0), 1.234);
+    // Exact multiples stay put despite float error
+    assert_eq!(lot.quantity(0.3, 100.0), 0.3);
+    assert_eq!(lot.quantity(0.009, 1000.0), 0.0);
+    assert_eq!(lot.quantity(0.04, 100.0), 0.0);
+    assert_eq!(lot.round_quantity(1.234 - 0.5), 0.734);
+    assert_eq!(lot.price_down(100.37), 100.35);
+    assert_eq!(lot.price_up(100.37), 100.4);
+    assert_eq!(lot.price_up(100.35), 100.35);
+    assert_eq!(lot.format_quantity(2.0), "2.000");
+
+    let free = Instrument::default();
+    assert_eq!(free.quantity(0.123_456_789, 60_000.0), 0.123_456_789);
+    assert_eq!(free.price_down(101.234), 101.234);
+    assert_eq!(free.format_quantity(0.5), "0.5");
+}
+
+#[test]
+fn instruments_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("instruments.json");
+    fs::write(
+        &path,
+        r#"{"BTC": {"step_size": 0.00001, "tick_size": 0.01, "min_notional": 5}}"#,
+    )
+    .unwrap();
+    let instruments = Instruments::load(&path).unwrap();
+    assert_eq!(instruments.get("BTC").step_size, Some(0.00001));
+    assert_eq!(instruments.get("BTC").min_qty, None);
+    assert_eq!(instruments.get("DOGE"), Instrument::default());
+
+    fs::write(&path, r#"{"BTC": {"tick_size": -0.01}}"#).unwrap();
+    assert!(Instruments::load(&path).is_err());
+}
+
+#[tokio::test]
+async fn high_priced_assets_size_in_fractional_lots() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (btc, eth) = (
+        tmp.path().join("BTC.csv"),
+        tmp.path().join("ETH_ethereum.csv"),
+    );
+    write_closes(&btc, 100.0, 0.001);
+    // Priced above the whole account: whole units would round to nothing
+    write_closes(&eth, 150_000.0, 0.004);
+    let resolved = Resolved::from_sources(None, None, &[]).unwrap();
+    let cli = StrategyArgs {
+        btc: Some(btc),
+        assets: Some(vec![eth]),
+        record: Some(false),
+        ..StrategyArgs::default()
+    };
+    let args: StrategyArgs = resolved.with_cli("strategy", &cli).unwrap();
+    let analyses = analyzer::analyze_backtest(&strategy::backtest(&args).unwrap().backtest);
+    let trade_args: TradeArgs = resolved.with_cli("trade", &TradeArgs::default()).unwrap();
+    let ai = AiSettings {
+        enabled: Some(false),
+        ..AiSettings::default()
+    };
+
+    let eth = Instrument {
+        step_size: Some(0.0001),
+        tick_size: Some(0.5),
+        min_qty: Some(0.0001),
+        min_notional: Some(10.0),
+    };
+    let plan = |instrument: Instrument| {
+        let instruments = Instruments([("ETH_ethereum".to_string(), instrument)].into());
+        let (analyses, trade_args, ai) = (&analyses, &trade_args, &ai);
+        async move {
+            let mut account = Account::from_args(trade_args).unwrap();
+            let book = PositionBook::default();
+            trade::top_playbooks(analyses, None, trade_args, &mut account, &instruments, &book, ai)
+                .await
+                .unwrap()
+                .remove(0)
+                .computed_values
+        }
+    };
+
+    let cv = plan(eth).await;
+    assert!(cv.current_price > 100_000.0);
+    assert!(cv.recommended_shares > 0.0 && cv.recommended_shares < 1.0);
+    let on_grid = |x: f64, step: f64| ((x / step).round() * step - x).abs() < 1e-9;
+    assert!(on_grid(cv.recommended_shares, 0.0001));
+    assert!(on_grid(cv.scale_out_shares, 0.0001));
+    assert!((cv.scale_out_shares + cv.remaining_shares - cv.recommended_shares).abs() < 1e-9);
+    assert!(on_grid(cv.stop_price, 0.5) && cv.stop_price < cv.current_price);
+    assert!(on_grid(cv.profit_target, 0.5) && on_grid(cv.ma30_pullback_price, 0.5));
+    assert!(!cv.below_minimum);
+
+    // An order below the exchange minimum notional is not placed at all
+    let cv = plan(Instrument {
+        min_notional: Some(1e9),
+        ..eth
+    })
+    .await;
+    assert_eq!(cv.recommended_shares, 0.0);
+    assert!(cv.below_minimum);
+}
@@ -0,0 +1,154 @@
+//! Library entry points return typed results and leave the filesystem alone.
+
+mod common;
+
+use std::fs;
+
+use common::write_closes;
+use crypto_momentum_ai::account::{Account, Holding};
+use crypto_momentum_ai::ai_insights::AiSettings;
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::instruments::{Instrument, Instruments};
+use crypto_momentum_ai::positions::{Position, PositionBook};
+use crypto_momentum_ai::{StrategyArgs, TradeArgs, analyzer, strategy, trade};
+
+#[tokio::test]
+async fn backtest_analysis_and_playbooks_without_side_effects() {
+    let tmp = tempfile::tempdir().unwrap();
+    let btc = tmp.path().join("BTC.csv");
+    let eth = tmp.path().join("ETH_ethereum.csv");
+    let sol = tmp.path().join("SOL_solana.csv");
+    write_closes(&btc, 100.0, 0.001);
+    write_closes(&eth, 10.0, 0.004);
+    write_closes(&sol, 5.0, -0.002);
+
+    let resolved = Resolved::from_sources(None, None, &[]).unwrap();
+    let cli = StrategyArgs {
+        btc: Some(btc),
+        assets: Some(vec![eth, sol]),
+        out: None,
+        record: Some(false),
+        ..StrategyArgs::default()
+    };
+    let args: StrategyArgs = resolved.with_cli("strategy", &cli).unwrap();
+    let result = strategy::backtest(&args).unwrap();
+    let bt = &result.backtest;
+
+    // Nothing besides the three inputs was written
+    assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 3);
+    assert_eq!(bt.equity.len(), bt.dates.len());
+    assert_eq!(bt.signals.len(), 2);
+    assert!(result.summary.contains("Sharpe"));
+    assert!(result.latest_regime().is_some());
+
+    // Weights agree with the reported gross exposure and include the trending asset
+    let weights = bt.weights();
+    assert_eq!(weights.len(), bt.dates.len());
+    for (day, exposure) in weights.iter().zip(&bt.exposure) {
+        let gross: f64 = day.values().map(|w| w.abs()).sum();
+        assert!((gross - exposure).abs() < 1e-9);
+    }
+    assert!(weights.iter().any(|day| day.contains_key("ETH_ethereum")));
+
+    // In-memory analysis matches the analysis of the written signal files
+    let analyses = analyzer::analyze_backtest(bt);
+    let out = tmp.path().join("out");
+    let written = strategy::write_outputs(&result, &out).unwrap();
+    assert!(written.iter().all(|p| p.is_file()));
+    let from_files = analyzer::analyze_signals_directory(out.to_str().unwrap()).unwrap();
+    assert_eq!(analyses.len(), from_files.len());
+    for a in &analyses {
+        let f = from_files.iter().find(|f| f.asset() == a.asset()).unwrap();
+        assert!((a.total_return() - f.total_return()).abs() < 1e-3);
+    }
+
+    let trade_args: TradeArgs = resolved.with_cli("trade", &TradeArgs::default()).unwrap();
+    let ai = AiSettings {
+        enabled: Some(false),
+        ..AiSettings::default()
+    };
+    let mut account = Account::from_args(&trade_args).unwrap();
+    let plans = trade::top_playbooks(
+        &analyses,
+        result.latest_regime(),
+        &trade_args,
+        &mut account,
+        &Instruments::default(),
+        &PositionBook::default(),
+        &ai,
+    )
+    .await
+    .unwrap();
+    // Only the trending asset survives the profitability and deflated-Sharpe screen
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].asset, "ETH_ethereum");
+    assert!(plans.iter().all(|p| p.regime.is_some()));
+    // Playbooks JSON reads back: no infinite values, e.g. uncapped liquidity without volume
+    let json = serde_json::to_string(&plans).unwrap();
+    let back: Vec<trade::TradePlan> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back[0].computed_values.max_shares_by_liquidity, None);
+    let cv = &plans[0].computed_values;
+    // Log returns of the closes, not of the simple returns (NaN on every down day)
+    assert!(cv.volatility.is_finite() && cv.volatility > 0.0);
+    assert_eq!((cv.equity, cv.held_shares, cv.currency.as_str()), (100_000.0, 0.0, "USD"));
+    assert_eq!(cv.order_shares, cv.recommended_shares);
+
+    assert_eq!((cv.initial_stop, cv.trailing_stop), (cv.stop_price, cv.stop_price));
+
+    // An account holding ETH with little cash: equity marks the holding to the latest close,
+    // and the buy is capped by what the cash can pay for. The book's open position supplies
+    // the entry stop, R and the stop it has ratcheted to since
+    let price = cv.current_price;
+    let book = PositionBook {
+        positions: vec![Position {
+            asset: "ETH_ethereum".to_string(),
+            entry_date: bt.dates[0],
+            entry_price: price / 2.0,
+            initial_quantity: 40.0,
+            quantity: 40.0,
+            initial_stop: price * 0.4,
+            initial_r: price * 0.1,
+            trailing_stop: price * 0.8,
+            scaled_out: true,
+            realized: 0.0,
+            updated: bt.dates[bt.dates.len() - 1],
+            last_close: price,
+            instrument: Instrument::default(),
+        }],
+        ..PositionBook::default()
+    };
+    let mut account = Account {
+        currency: "EUR".to_string(),
+        cash: 10.0 * price,
+        positions: vec![Holding {
+            asset: "ETH_ethereum".to_string(),
+            quantity: 40.0,
+            avg_price: price / 2.0,
+            mark: None,
+        }],
+    };
+    let plans = trade::top_playbooks(
+        &analyses,
+        None,
+        &trade_args,
+        &mut account,
+        &Instruments::default(),
+        &book,
+        &ai,
+    )
+    .await
+    .unwrap();
+    let held = &plans[0].computed_values;
+    assert!((held.equity - 50.0 * price).abs() < 1e-6);
+    assert!((account.equity() - held.equity).abs() < 1e-9);
+    assert_eq!((held.held_shares, held.currency.as_str()), (40.0, "EUR"));
+    assert!(held.recommended_shares <= 50.0 + 1e-9);
+    assert_eq!(held.order_shares, held.recommended_shares - 40.0);
+    assert!((held.position_percent - held.position_value / held.equity).abs() < 1e-12);
+    assert_eq!(
+        (held.initial_stop, held.initial_r, held.trailing_stop),
+        (price * 0.4, price * 0.1, price * 0.8)
+    );
+    assert!((held.stop_loss_percent - 20.0).abs() < 1e-9);
+    assert_eq!(held.scale_out_shares, 0.0);
+}
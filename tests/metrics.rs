@@ -0,0 +1,72 @@
+//! Hand-computed checks for the shared `metrics` suite.
+
+mod common;
+
+use chrono::NaiveDate;
+
+use common::assert_close;
+use crypto_momentum_ai::metrics::{self, DAILY_PERIODS_PER_YEAR};
+
+fn dates(start: &str, step_days: i64, n: usize) -> Vec<NaiveDate> {
+    let start = NaiveDate::parse_from_str(start, "%Y-%m-%d").unwrap();
+    (0..n)
+        .map(|i| start + chrono::Duration::days(step_days * i as i64))
+        .collect()
+}
+
+#[test]
+fn periods_per_year_follows_bar_interval() {
+    assert_close(metrics::periods_per_year(&dates("2024-01-01", 1, 10)), 365.25);
+    assert_close(
+        metrics::periods_per_year(&dates("2024-01-01", 7, 10)),
+        365.25 / 7.0,
+    );
+    assert_close(metrics::periods_per_year(&[]), DAILY_PERIODS_PER_YEAR);
+}
+
+#[test]
+fn drawdown_duration_and_recovery() {
+    // Equity 1.1, 0.88, 0.924, 1.1088, 0.99792
+    let rets = [0.1, -0.2, 0.05, 0.2, -0.1];
+    let m = metrics::compute(&rets, &[], 365.25, &[], &[]);
+    assert_eq!(m.days, 5);
+    assert_close(m.total_return, 1.1 * 0.8 * 1.05 * 1.2 * 0.9 - 1.0);
+    assert_close(m.max_drawdown, 0.2);
+    // Peak after bar 1, new high after bar 4; trough after bar 2
+    assert_eq!(m.max_drawdown_duration, 3);
+    assert_eq!(m.recovery_time, Some(2));
+    assert_close(m.omega, 0.35 / 0.3);
+    assert_close(m.win_rate, 0.6);
+    assert_close(m.time_in_market, 1.0);
+    // Without dates there is no monthly breakdown
+    assert_close(m.best_month, 0.0);
+}
+
+#[test]
+fn unrecovered_drawdown_and_tail_risk() {
+    let rets = [0.0, -0.1, 0.0, 0.0];
+    let m = metrics::compute(&rets, &[], 365.25, &[], &[0.0, 1.0, 1.0, 0.0]);
+    assert_eq!(m.recovery_time, None);
+    // Flat first bar is the last high; the drawdown stays open for 3 bars
+    assert_eq!(m.max_drawdown_duration, 3);
+    assert_close(m.time_in_market, 0.5);
+    // Worst bar dominates both tails
+    assert!(m.var_99 > 0.09 && m.var_99 <= 0.1);
+    assert_close(m.cvar_99, 0.1);
+}
+
+#[test]
+fn monthly_returns_compound_within_calendar_months() {
+    let d = dates("2024-01-30", 1, 4); // Jan 30, Jan 31, Feb 1, Feb 2
+    let m = metrics::compute(&[0.1, 0.1, -0.05, -0.05], &d, 365.25, &[], &[]);
+    assert_close(m.best_month, 0.21);
+    assert_close(m.worst_month, 0.95 * 0.95 - 1.0);
+
+    // One-sided months: the best of all losing months is still a loss, and vice versa
+    let m = metrics::compute(&[-0.1, -0.1, -0.05, -0.05], &d, 365.25, &[], &[]);
+    assert_close(m.best_month, 0.95 * 0.95 - 1.0);
+    assert_close(m.worst_month, 0.9 * 0.9 - 1.0);
+    let m = metrics::compute(&[0.1, 0.1, 0.05, 0.05], &d, 365.25, &[], &[]);
+    assert_close(m.best_month, 0.21);
+    assert_close(m.worst_month, 1.05 * 1.05 - 1.0);
+}
@@ -0,0 +1,107 @@
+//! Closed-form checks for the Sharpe deflation and backtest-overfitting diagnostics.
+
+mod common;
+
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use common::assert_close;
+use crypto_momentum_ai::overfit;
+
+fn normal() -> Normal {
+    Normal::new(0.0, 1.0).unwrap()
+}
+
+/// `n` alternating returns of `mean ± spread`: skew 0 and (non-excess) kurtosis 1.
+fn two_point(n: usize, mean: f64, spread: f64) -> Vec<f64> {
+    (0..n)
+        .map(|t| {
+            if t % 2 == 0 {
+                mean + spread
+            } else {
+                mean - spread
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn probabilistic_sharpe_matches_the_closed_form() {
+    let rets = two_point(200, 0.01, 0.02);
+    let m = overfit::moments(&rets);
+    assert_close(m.skew, 0.0);
+    assert_close(m.kurtosis, 1.0);
+    assert_close(m.std, (0.0004_f64 * 200.0 / 199.0).sqrt());
+
+    // With skew 0 and kurtosis 1 the denominator is exactly 1: PSR = Φ(SR · √(n − 1))
+    let sr = overfit::sharpe(&rets);
+    assert_close(sr, 0.01 / m.std);
+    assert_close(
+        overfit::probabilistic_sharpe(&rets, 0.0),
+        normal().cdf(sr * 199.0_f64.sqrt()),
+    );
+    // Benchmarked against its own Sharpe the estimate is a coin flip
+    assert_close(overfit::probabilistic_sharpe(&rets, sr), 0.5);
+    // Too short or flat to say anything
+    assert_close(overfit::probabilistic_sharpe(&[0.01, 0.02], 0.0), 0.0);
+    assert_close(overfit::probabilistic_sharpe(&[0.25; 10], 0.0), 0.0);
+}
+
+#[test]
+fn expected_max_sharpe_of_unskilled_trials() {
+    let gamma = 0.577_215_664_901_532_9;
+    let n = 10.0;
+    let expected = (1.0 - gamma) * normal().inverse_cdf(1.0 - 1.0 / n)
+        + gamma * normal().inverse_cdf(1.0 - 1.0 / (n * std::f64::consts::E));
+    assert_close(overfit::expected_max_sharpe(10, 1.0), expected);
+    // Scales with the standard deviation of the trial Sharpes
+    assert_close(overfit::expected_max_sharpe(10, 4.0), 2.0 * expected);
+    assert_close(overfit::expected_max_sharpe(1, 1.0), 0.0);
+    assert_close(overfit::expected_max_sharpe(10, 0.0), 0.0);
+}
+
+#[test]
+fn deflated_sharpe_falls_as_trials_grow() {
+    let rets = two_point(200, 0.005, 0.02);
+    let psr = overfit::probabilistic_sharpe(&rets, 0.0);
+    let mut prev = psr;
+    for trials in [2, 10, 100, 1000] {
+        let dsr = overfit::deflated_sharpe(&rets, trials, 0.01);
+        assert!(dsr <= prev, "{trials} trials: {dsr} > {prev}");
+        prev = dsr;
+    }
+    assert!(prev < psr);
+
+    let flat = two_point(200, 0.0, 0.02);
+    let diagnostics = overfit::sharpe_diagnostics(&[&rets, &flat]);
+    assert_eq!(diagnostics[0].trials, 2);
+    assert!(diagnostics.iter().all(|d| d.dsr <= d.psr));
+    assert_close(diagnostics[1].psr, 0.5);
+}
+
+/// `size` returns around each block mean, wiggling so every block has variance.
+fn blocks(means: &[f64], size: usize) -> Vec<f64> {
+    means
+        .iter()
+        .flat_map(|m| two_point(size, *m, 0.001))
+        .collect()
+}
+
+#[test]
+fn pbo_of_reversing_and_dominant_trials() {
+    // Block means sum to zero, so every out-of-sample half mirrors the in-sample half and
+    // the in-sample winner is always the out-of-sample loser
+    let a = blocks(&[0.01, 0.02, -0.04, 0.01], 10);
+    let b: Vec<f64> = a.iter().map(|r| -r).collect();
+    let pbo = overfit::pbo_cscv(&[a.clone(), b.clone()], 4).unwrap();
+    assert_eq!(pbo.splits, 6);
+    assert_close(pbo.pbo, 1.0);
+    assert!(pbo.logits.iter().all(|l| *l < 0.0));
+
+    // A trial that beats the others in every block wins out of sample too
+    let dominant = blocks(&[0.012; 4], 10);
+    let pbo = overfit::pbo_cscv(&[a.clone(), b, dominant], 4).unwrap();
+    assert_close(pbo.pbo, 0.0);
+
+    assert!(overfit::pbo_cscv(std::slice::from_ref(&a), 4).is_none());
+    assert!(overfit::pbo_cscv(&[a[..6].to_vec(), a[..6].to_vec()], 4).is_none());
+}
@@ -0,0 +1,125 @@
+//! The position book ratchets trailing stops, scales out at the target and stops out on breaches.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crypto_momentum_ai::account::Account;
+use crypto_momentum_ai::instruments::Instrument;
+use crypto_momentum_ai::positions::{ActionKind, BookRules, Entry, Mark, PositionBook};
+
+/// Bars whose strategy stop sits 3 × ATR 2 below the close.
+fn marks(day: u32, close: f64) -> BTreeMap<String, Mark> {
+    let mark = Mark {
+        date: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+        close,
+        stop: Some(close - 6.0),
+    };
+    [("ETH_ethereum".to_string(), mark)].into()
+}
+
+fn kinds(book: &mut PositionBook, day: u32, close: f64, entries: &[Entry]) -> Vec<ActionKind> {
+    let rules = BookRules {
+        profit_target_r: 2.0,
+    };
+    book.update(&marks(day, close), entries, &rules)
+        .iter()
+        .map(|a| a.kind)
+        .collect()
+}
+
+#[test]
+fn position_lifecycle() {
+    let entry = [Entry {
+        asset: "ETH_ethereum".to_string(),
+        quantity: 10.0,
+        stop: 90.0,
+        instrument: Instrument {
+            step_size: Some(1.0),
+            ..Instrument::default()
+        },
+    }];
+    let mut book = PositionBook::default();
+
+    // Enter at the close with R = 10; the same bar again changes nothing
+    assert_eq!(kinds(&mut book, 1, 100.0, &entry), [ActionKind::Enter]);
+    assert!(kinds(&mut book, 1, 100.0, &entry).is_empty());
+    let p = book.position("ETH_ethereum").unwrap();
+    assert_eq!((p.initial_r, p.trailing_stop), (10.0, 90.0));
+
+    // Stops only ratchet up: max(prior, strategy stop)
+    assert_eq!(kinds(&mut book, 2, 105.0, &entry), [ActionKind::RaiseStop]);
+    assert_eq!(book.position("ETH_ethereum").unwrap().trailing_stop, 99.0);
+    assert!(kinds(&mut book, 3, 101.0, &entry).is_empty());
+    assert_eq!(book.position("ETH_ethereum").unwrap().trailing_stop, 99.0);
+
+    // The 2R target (120) scales out half and the stop trails from there
+    assert_eq!(
+        kinds(&mut book, 4, 121.0, &entry),
+        [ActionKind::ScaleOut, ActionKind::RaiseStop]
+    );
+    let p = book.position("ETH_ethereum").unwrap();
+    assert!(p.scaled_out);
+    assert_eq!((p.quantity, p.trailing_stop), (5.0, 115.0));
+
+    // A close through the stop exits the rest; no re-entry on the same bar
+    assert_eq!(kinds(&mut book, 5, 114.0, &entry), [ActionKind::StopOut]);
+    assert!(book.positions.is_empty());
+    let closed = &book.closed[0];
+    assert_eq!(closed.pnl, 5.0 * 21.0 + 5.0 * 14.0);
+    assert_eq!(closed.r_multiple, closed.pnl / 100.0);
+    assert_eq!(book.actions.len(), 5);
+
+    // Persisted between cycles
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("positions.json");
+    assert_eq!(PositionBook::load(&path).unwrap(), PositionBook::default());
+    book.save(&path).unwrap();
+    assert_eq!(PositionBook::load(&path).unwrap(), book);
+}
+
+#[test]
+fn single_lot_is_not_scaled_out() {
+    let entry = [Entry {
+        asset: "ETH_ethereum".to_string(),
+        quantity: 1.0,
+        stop: 90.0,
+        instrument: Instrument {
+            step_size: Some(1.0),
+            ..Instrument::default()
+        },
+    }];
+    let mut book = PositionBook::default();
+    kinds(&mut book, 1, 100.0, &entry);
+    assert_eq!(
+        book.actions[0].describe(),
+        "ENTER ETH_ethereum 1 @ 100.00 (stop 90.00, R 10.00)"
+    );
+
+    // At the target half a lot rounds to nothing: the whole lot keeps trailing
+    assert_eq!(kinds(&mut book, 2, 120.0, &entry), [ActionKind::RaiseStop]);
+    let p = book.position("ETH_ethereum").unwrap();
+    assert!(!p.scaled_out);
+    assert_eq!((p.quantity, p.trailing_stop), (1.0, 114.0));
+}
+
+#[test]
+fn fills_move_the_account() {
+    let entry = [Entry {
+        asset: "ETH_ethereum".to_string(),
+        quantity: 10.0,
+        stop: 90.0,
+        instrument: Instrument::default(),
+    }];
+    let mut book = PositionBook::default();
+    kinds(&mut book, 1, 100.0, &entry);
+    kinds(&mut book, 2, 121.0, &entry);
+    assert_eq!(book.cash_flow(), -1000.0 + 5.0 * 121.0);
+
+    // Half sold at 121, half held and marked there
+    let mut account = Account::cash_only(10_000.0, "USD");
+    book.apply_to(&mut account);
+    assert_eq!(account.cash, 9605.0);
+    assert_eq!(account.quantity("ETH_ethereum"), 5.0);
+    assert_eq!(account.equity(), 10_000.0 + 10.0 * 21.0);
+}
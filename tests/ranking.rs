@@ -0,0 +1,122 @@
+//! Cross-sectional ranking: rebalance cadence, skipped days and long/short selection.
+
+mod common;
+
+use common::date;
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::strategy::{self, AlignedBar, DailySignal, RankRules, RankState};
+use crypto_momentum_ai::{RankBy, StrategyArgs};
+
+const ASSETS: [&str; 4] = ["A", "B", "C", "D"];
+
+/// Daily returns of A, B, C and D. Days 1-2 favour A over D, days 4-5 B over A and days 7-8
+/// C over B; on day 3 D jumps 50%, which only a ranking without the skipped day would see.
+fn daily_return(day: usize, asset: usize) -> f64 {
+    let table: [f64; 4] = match day {
+        1 | 2 => [0.05, 0.01, 0.0, -0.05],
+        3 => [0.0, 0.0, 0.0, 0.5],
+        4 | 5 => [-0.05, 0.05, 0.01, 0.0],
+        7 | 8 => [0.0, -0.05, 0.05, 0.01],
+        _ => [0.0; 4],
+    };
+    table[asset]
+}
+
+/// Twelve days of signals per asset.
+fn universe() -> Vec<(String, Vec<DailySignal>)> {
+    let args: StrategyArgs = Resolved::from_sources(None, None, &[])
+        .unwrap()
+        .with_cli("strategy", &StrategyArgs::default())
+        .unwrap();
+    let dates: Vec<_> = (0..12).map(date).collect();
+    ASSETS
+        .iter()
+        .enumerate()
+        .map(|(a, name)| {
+            let mut px = 100.0;
+            let bars: Vec<AlignedBar> = (0..12)
+                .map(|d| {
+                    px *= 1.0 + daily_return(d, a);
+                    AlignedBar {
+                        open: None,
+                        close: px,
+                        high: None,
+                        low: None,
+                        volume: None,
+                        filled: false,
+                    }
+                })
+                .collect();
+            let signals = strategy::compute_signals(&dates, &bars, &[100.0; 12], &args).unwrap();
+            ((*name).to_string(), signals)
+        })
+        .collect()
+}
+
+/// Step a fresh ranking through every day and return each day's weights of A-D.
+fn weights(rules: &RankRules) -> Vec<[f64; 4]> {
+    let mut signals = universe();
+    let mut state = RankState::default();
+    (0..12)
+        .map(|t| {
+            let mut today: Vec<(&str, &mut [DailySignal])> = signals
+                .iter_mut()
+                .map(|(name, sigs)| (name.as_str(), &mut sigs[..=t]))
+                .collect();
+            state.step(&mut today, rules);
+            std::array::from_fn(|a| signals[a].1[t].raw_weight())
+        })
+        .collect()
+}
+
+fn rules(top_k: usize, short_bottom: bool) -> RankRules {
+    RankRules {
+        rank_by: RankBy::Return,
+        lookback: 2,
+        skip: 1,
+        top_k,
+        short_bottom,
+        rebalance: 3,
+    }
+}
+
+#[test]
+fn rebalances_every_third_day_and_holds_in_between() {
+    let w = weights(&rules(1, true));
+    // Ranking needs lookback + skip days first; rebalances fall on days 3, 6 and 9
+    for day in &w[..3] {
+        assert_eq!(*day, [0.0; 4]);
+    }
+    // Day 3 ranks days 0-2: D's jump that day is skipped, so D is still the short
+    for day in &w[3..6] {
+        assert_eq!(*day, [1.0, 0.0, 0.0, -1.0]);
+    }
+    for day in &w[6..9] {
+        assert_eq!(*day, [-1.0, 1.0, 0.0, 0.0]);
+    }
+    for day in &w[9..] {
+        assert_eq!(*day, [0.0, -1.0, 1.0, 0.0]);
+    }
+}
+
+#[test]
+fn top_k_longs_without_shorts() {
+    let w = weights(&rules(2, false));
+    assert_eq!(w[3], [1.0, 1.0, 0.0, 0.0]);
+    assert_eq!(w[6], [0.0, 1.0, 1.0, 0.0]);
+    assert_eq!(w[9], [0.0, 0.0, 1.0, 1.0]);
+}
+
+#[test]
+fn the_skipped_day_is_left_out_of_the_ranking() {
+    let daily = |skip| {
+        weights(&RankRules {
+            skip,
+            rebalance: 1,
+            ..rules(1, true)
+        })
+    };
+    // Ranked on days 1-3, D's jump on day 3 puts it on top; skipping day 3 leaves it last
+    assert_eq!(daily(0)[3], [0.0, 0.0, -1.0, 1.0]);
+    assert_eq!(daily(1)[3], [1.0, 0.0, 0.0, -1.0]);
+}
@@ -0,0 +1,93 @@
+//! The regime classifier on hand-built BTC and asset paths.
+
+use chrono::NaiveDate;
+
+use crypto_momentum_ai::regime::{self, Regime, RegimeConfig};
+
+fn dates(n: usize) -> Vec<NaiveDate> {
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    (0..n)
+        .map(|i| start + chrono::Duration::days(i as i64))
+        .collect()
+}
+
+/// `n` closes compounding at `daily` from 100.
+fn path(n: usize, daily: f64) -> Vec<f64> {
+    (0..n)
+        .map(|i| 100.0 * (1.0 + daily).powi(i as i32))
+        .collect()
+}
+
+fn config() -> RegimeConfig {
+    RegimeConfig {
+        ma_short: 3,
+        ma_long: 10,
+        vol_lookback: 5,
+        high_vol: 0.90,
+        breadth_bull: 0.60,
+        breadth_bear: 0.40,
+        neutral_exposure: 0.75,
+        risk_off_exposure: 0.50,
+        crisis_exposure: 0.25,
+    }
+}
+
+/// Two assets compounding at `daily` and `daily` + 0.1%, always tradeable.
+fn universe(n: usize, daily: f64) -> Vec<Vec<Option<f64>>> {
+    [daily, daily + 0.001]
+        .iter()
+        .map(|d| path(n, *d).into_iter().map(Some).collect())
+        .collect()
+}
+
+#[test]
+fn missing_breadth_casts_no_vote() {
+    // No asset has a long MA yet: breadth is unknown, not bearish
+    let btc = path(8, 0.01);
+    let assets = vec![btc.iter().map(|c| Some(*c)).collect()];
+    let states = regime::classify(&dates(8), &btc, &assets, &config());
+    for s in &states {
+        assert_eq!(s.breadth, None);
+        assert_eq!(s.regime, Regime::Neutral);
+    }
+}
+
+#[test]
+fn smooth_rally_is_risk_on() {
+    // BTC uptrend and full breadth vote +2; constant returns carry no volatility
+    let btc = path(40, 0.01);
+    let states = regime::classify(&dates(40), &btc, &universe(40, 0.01), &config());
+    let last = states.last().unwrap();
+    assert_eq!((last.btc_trend, last.breadth), (1, Some(1.0)));
+    assert_eq!(last.regime, Regime::RiskOn);
+    assert_eq!(last.exposure, 1.0);
+}
+
+#[test]
+fn calm_decline_is_risk_off() {
+    let btc = path(40, -0.01);
+    let states = regime::classify(&dates(40), &btc, &universe(40, -0.01), &config());
+    let last = states.last().unwrap();
+    assert_eq!((last.btc_trend, last.breadth), (-1, Some(0.0)));
+    assert!(last.realized_vol.unwrap() < 0.90);
+    assert_eq!(last.regime, Regime::RiskOff);
+    assert_eq!(last.exposure, 0.50);
+}
+
+#[test]
+fn volatile_decline_is_crisis() {
+    // Alternating -8% / +4% days: a downtrend with annualized volatility above 90%
+    let btc: Vec<f64> = (0..40)
+        .scan(100.0, |px, i| {
+            let close = *px;
+            *px *= if i % 2 == 0 { 0.92 } else { 1.04 };
+            Some(close)
+        })
+        .collect();
+    let states = regime::classify(&dates(40), &btc, &universe(40, -0.01), &config());
+    let last = states.last().unwrap();
+    assert_eq!(last.btc_trend, -1);
+    assert!(last.realized_vol.unwrap() > 0.90);
+    assert_eq!(last.regime, Regime::Crisis);
+    assert_eq!(last.exposure, 0.25);
+}
@@ -0,0 +1,42 @@
+//! The HTML report must be self-contained and escape user-controlled text.
+
+use chrono::NaiveDate;
+
+use crypto_momentum_ai::report::{self, ReportData};
+
+#[test]
+fn report_is_offline_and_escaped() {
+    let dates: Vec<NaiveDate> = (1..=120)
+        .map(|d| NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(d))
+        .collect();
+    let daily_ret: Vec<f64> = (0..120).map(|i| if i % 3 == 0 { -0.01 } else { 0.01 }).collect();
+    let equity = daily_ret
+        .iter()
+        .scan(1.0, |e, r| {
+            *e *= 1.0 + r;
+            Some(*e)
+        })
+        .collect();
+    let data = ReportData {
+        title: "<run & co>".to_string(),
+        dates,
+        equity,
+        daily_ret,
+        exposure: vec![1.0; 120],
+        num_positions: vec![2.0; 120],
+        benchmarks: vec![("btc_buy_and_hold".to_string(), vec![1.0; 120])],
+        attribution: vec![("ETH".to_string(), 0.05), ("SOL".to_string(), -0.02)],
+        params: vec![("ma_short".to_string(), "7".to_string())],
+        summary: "Sharpe (ann.): 1.00".to_string(),
+    };
+    let html = report::render(&data);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("&lt;run &amp; co&gt;"));
+    assert!(!html.contains("<run & co>"));
+    // Charts are inline SVG; nothing is fetched from the network
+    assert_eq!(html.matches("<svg").count(), 6);
+    assert!(!html.contains("No data"));
+    assert!(!html.contains("http://") && !html.contains("https://"));
+    assert!(!html.contains("<script src"));
+    assert!(html.contains("2024-01"));
+}
@@ -0,0 +1,80 @@
+//! Hand-computed checks for the covariance-based portfolio risk of two assets.
+
+mod common;
+
+use common::assert_close;
+use crypto_momentum_ai::risk;
+
+const A: [f64; 4] = [0.01, -0.01, 0.01, -0.01];
+/// Uncorrelated with `A`: the centred cross products cancel
+const B: [f64; 4] = [0.01, 0.01, -0.01, -0.01];
+/// Sample variance of `A` and `B`: 4 × 0.0001 / (4 − 1)
+const VAR: f64 = 0.0004 / 3.0;
+
+#[test]
+fn covariance_and_correlation() {
+    let cov = risk::covariance_matrix(&[A.to_vec(), B.to_vec()]);
+    assert_close(cov[0][0], VAR);
+    assert_close(cov[1][1], VAR);
+    assert_close(cov[0][1], 0.0);
+    assert_eq!(cov[0][1], cov[1][0]);
+
+    let corr = risk::correlation_matrix(&risk::covariance_matrix(&[A.to_vec(), A.to_vec()]));
+    assert_close(corr[0][1], 1.0);
+    // Too short to estimate anything
+    assert_eq!(risk::covariance_matrix(&[vec![0.01]]), vec![vec![0.0]]);
+}
+
+#[test]
+fn two_positions_at_zero_and_full_correlation() {
+    let sd = VAR.sqrt();
+    let positions = [1000.0, 1000.0];
+
+    // rho = 0: volatilities add in quadrature and each position carries half the variance
+    let cov = risk::covariance_matrix(&[A.to_vec(), B.to_vec()]);
+    let r = risk::portfolio_risk(&positions, &cov, 10_000.0, 3.0);
+    assert_close(r.daily_volatility, 2.0_f64.sqrt() * 1000.0 * sd);
+    assert_close(r.daily_volatility_percent, r.daily_volatility / 10_000.0);
+    assert_close(r.risk_contribution[0], 0.5);
+    assert_close(r.risk_contribution[1], 0.5);
+    assert_close(r.marginal_risk[0], 1000.0 * VAR / r.daily_volatility);
+    assert_close(r.diversified_stress_loss, 3.0 * r.daily_volatility);
+    assert_close(r.perfect_correlation_stress_loss, 3.0 * 2000.0 * sd);
+    assert!(r.diversified_stress_loss < r.perfect_correlation_stress_loss);
+
+    // rho = 1: no diversification, so both stress losses coincide
+    let cov = risk::covariance_matrix(&[A.to_vec(), A.to_vec()]);
+    let r = risk::portfolio_risk(&positions, &cov, 10_000.0, 3.0);
+    assert_close(r.daily_volatility, 2000.0 * sd);
+    assert_close(r.diversified_stress_loss, r.perfect_correlation_stress_loss);
+}
+
+#[test]
+fn heat_scale_only_shrinks() {
+    assert_close(risk::heat_scale(12.0, 6.0), 0.5);
+    assert_close(risk::heat_scale(5.0, 6.0), 1.0);
+    assert_close(risk::heat_scale(12.0, 0.0), 1.0);
+}
+
+#[test]
+fn heat_cap_follows_correlation() {
+    // Two $1000 positions, each losing $400 if stopped out, against a 6% cap on $10,000
+    let positions = [1000.0, 1000.0];
+    let stop_risks = [400.0, 400.0];
+    let heat_of = |b: [f64; 4]| {
+        let cov = risk::covariance_matrix(&[A.to_vec(), b.to_vec()]);
+        let corr = risk::correlation_matrix(&cov);
+        let r = risk::portfolio_risk(&positions, &cov, 10_000.0, 3.0);
+        risk::portfolio_heat(&stop_risks, &corr, &r, 10_000.0)
+    };
+
+    // rho = 1: the stop risks add up to 8% and the cap scales positions down
+    let correlated = heat_of(A);
+    assert_close(correlated, 8.0);
+    assert_close(risk::heat_scale(correlated, 6.0), 0.75);
+
+    // rho = 0: they add in quadrature, √2 × 4% ≈ 5.7%, under the cap
+    let uncorrelated = heat_of(B);
+    assert_close(uncorrelated, 2.0_f64.sqrt() * 4.0);
+    assert_close(risk::heat_scale(uncorrelated, 6.0), 1.0);
+}
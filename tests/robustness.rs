@@ -0,0 +1,103 @@
+//! Seeded regression checks for the resampling schemes behind `robustness`.
+
+use chrono::NaiveDate;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crypto_momentum_ai::RobustnessArgs;
+use crypto_momentum_ai::metrics;
+use crypto_momentum_ai::robustness::{self, Method};
+use crypto_momentum_ai::strategy::Trade;
+
+/// A drifting, oscillating daily return series.
+fn returns(n: usize) -> Vec<f64> {
+    (0..n)
+        .map(|t| 0.01f64.mul_add((t as f64 * 1.3).sin(), 0.002))
+        .collect()
+}
+
+fn trade(contribution: f64) -> Trade {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    Trade {
+        asset: "ETH_ethereum".to_string(),
+        side: 1,
+        entry_date: date,
+        exit_date: date,
+        entry_price: 100.0,
+        exit_price: 100.0 * (1.0 + contribution),
+        days: 1,
+        trade_return: contribution,
+        contribution,
+        stopped: false,
+    }
+}
+
+fn compounded(rets: &[f64]) -> f64 {
+    rets.iter().map(|r| 1.0 + r).product()
+}
+
+#[test]
+fn resamples_are_seeded_and_drawn_from_the_input() {
+    let rets = returns(250);
+    let draw =
+        |seed| robustness::stationary_bootstrap(&rets, 10.0, &mut StdRng::seed_from_u64(seed));
+    let sample = draw(7);
+    assert_eq!(sample, draw(7));
+    assert_ne!(sample, draw(8));
+    assert_eq!(sample.len(), rets.len());
+    assert!(sample.iter().all(|r| rets.contains(r)));
+
+    let trades: Vec<Trade> = [0.05, -0.02, 0.03, -0.04, 0.01].map(trade).to_vec();
+    let path = robustness::shuffled_trade_path(&trades, 20, &mut StdRng::seed_from_u64(7));
+    assert_eq!(path.len(), 20);
+    let contributions: Vec<f64> = trades.iter().map(|t| t.contribution).collect();
+    assert!((compounded(&path) - compounded(&contributions)).abs() < 1e-12);
+}
+
+#[test]
+fn intervals_bracket_the_point_estimate() {
+    let rets = returns(400);
+    let trades: Vec<Trade> = [0.05, -0.02, 0.03, -0.04, 0.01, 0.02, -0.01]
+        .map(trade)
+        .to_vec();
+    let args = RobustnessArgs {
+        simulations: Some(200),
+        block_length: Some(10.0),
+        min_days: Some(90),
+        confidence: Some(0.90),
+        loss_threshold: Some(0.20),
+        seed: Some(42),
+        ..RobustnessArgs::default()
+    };
+    let summaries = robustness::analyze(&rets, &trades, 365.0, &args);
+    let again = robustness::analyze(&rets, &trades, 365.0, &args);
+    let point = metrics::compute(&rets, &[], 365.0, &[], &[]);
+
+    for (s, a) in summaries.iter().zip(&again) {
+        assert_eq!(s.samples.len(), 200);
+        assert_eq!(
+            (s.cagr.lower, s.cagr.median, s.cagr.upper),
+            (a.cagr.lower, a.cagr.median, a.cagr.upper)
+        );
+        assert!(s.cagr.lower <= s.cagr.median && s.cagr.median <= s.cagr.upper);
+        assert!(s.sharpe.lower <= s.sharpe.upper);
+    }
+
+    let bootstrap = &summaries[0];
+    assert_eq!(bootstrap.method, Method::BlockBootstrap);
+    assert!(bootstrap.cagr.lower <= point.cagr && point.cagr <= bootstrap.cagr.upper);
+    assert!(bootstrap.sharpe.lower <= point.sharpe && point.sharpe <= bootstrap.sharpe.upper);
+
+    // Reordering trades changes the path, never the compounded result
+    let shuffle = &summaries[1];
+    assert!(shuffle.method.path_only());
+    let total = compounded(&trades.iter().map(|t| t.contribution).collect::<Vec<_>>()) - 1.0;
+    assert!(
+        shuffle
+            .samples
+            .iter()
+            .all(|m| (m.total_return - total).abs() < 1e-9)
+    );
+    assert!(shuffle.max_drawdown.lower <= shuffle.max_drawdown.upper);
+    assert_eq!(shuffle.prob_loss, 0.0);
+}
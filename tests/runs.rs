@@ -0,0 +1,90 @@
+//! Run registry: recording, lookup and reproduction of archived runs.
+
+use std::fs;
+
+use chrono::NaiveDate;
+use clap::Parser;
+
+use crypto_momentum_ai::StrategyArgs;
+use crypto_momentum_ai::metrics::Metrics;
+use crypto_momentum_ai::runs;
+
+#[test]
+fn records_hashes_and_reproduces_runs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let btc = tmp.path().join("BTC.csv");
+    let eth = tmp.path().join("ETH_ethereum.csv");
+    fs::write(&btc, "date,close\n2024-01-01,100\n").unwrap();
+    fs::write(&eth, "date,close\n2024-01-01,10\n").unwrap();
+    let out = tmp.path().join("signals");
+    fs::create_dir_all(&out).unwrap();
+    fs::write(out.join("metrics.txt"), "Total Return: 1.00%\n").unwrap();
+
+    let args = StrategyArgs {
+        btc: Some(btc.clone()),
+        assets: Some(vec![eth.clone()]),
+        out: Some(out.clone()),
+        ma_long: Some(7),
+        ..StrategyArgs::default()
+    };
+    let runs_dir = tmp.path().join("runs");
+    let dates = [
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+    ];
+    let metrics = Metrics {
+        total_return: 0.01,
+        omega: f64::INFINITY,
+        ..Metrics::default()
+    };
+    let outputs = [out.join("metrics.txt"), out.join("missing.csv")];
+    let first = runs::record(&runs_dir, &args, &dates, &metrics, &outputs).unwrap();
+    let second = runs::record(&runs_dir, &args, &dates, &metrics, &outputs).unwrap();
+
+    // Same args and data give the same config hash but distinct run directories
+    assert_ne!(first.id, second.id);
+    assert_eq!(first.config_hash, second.config_hash);
+    assert_eq!(first.outputs, vec!["metrics.txt".to_string()]);
+    assert!(runs_dir.join(&first.id).join("metrics.txt").is_file());
+    assert_eq!(first.inputs.len(), 2);
+    assert_eq!(first.inputs[0].sha256, runs::hash_file(&btc).unwrap().0);
+    assert_eq!(first.start_date, Some(dates[0]));
+
+    // Manifests load back, including non-finite metrics
+    let all = runs::load_all(&runs_dir).unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(runs::find(&runs_dir, &second.id).unwrap().id, second.id);
+    assert!(runs::find(&runs_dir, &first.id[..8]).is_err());
+
+    // Output and registry settings do not; strategy parameters do
+    let elsewhere = StrategyArgs {
+        out: Some(tmp.path().join("elsewhere")),
+        runs_dir: Some(tmp.path().join("other_runs")),
+        record: Some(true),
+        ..args.clone()
+    };
+    let moved = runs::record(&runs_dir, &elsewhere, &dates, &metrics, &outputs).unwrap();
+    assert_eq!(moved.config_hash, first.config_hash);
+    let longer = StrategyArgs {
+        ma_long: Some(8),
+        ..args.clone()
+    };
+    let retuned = runs::record(&runs_dir, &longer, &dates, &metrics, &outputs).unwrap();
+    assert_ne!(retuned.config_hash, first.config_hash);
+
+    // Changing the data changes the config hash
+    fs::write(&eth, "date,close\n2024-01-01,11\n").unwrap();
+    let third = runs::record(&runs_dir, &args, &dates, &metrics, &outputs).unwrap();
+    assert_ne!(third.config_hash, first.config_hash);
+
+    // The printed command parses back to the recorded arguments
+    let cmd = runs::command_line(&first.args);
+    // Drop the subcommand word; StrategyArgs parses the flags after it
+    let words = cmd.split_whitespace().enumerate().filter(|(i, _)| *i != 1);
+    let parsed = StrategyArgs::try_parse_from(words.map(|(_, w)| w)).unwrap();
+    assert_eq!(parsed.btc, Some(btc));
+    assert_eq!(parsed.assets, Some(vec![eth]));
+    assert_eq!(parsed.out, Some(out));
+    assert_eq!(parsed.ma_long, Some(7));
+    assert_eq!(parsed.ma_short, None);
+}
@@ -0,0 +1,102 @@
+//! Stops trigger on the intraday range and fill at the open when the bar gaps through them.
+
+mod common;
+
+use common::write_ohlc;
+use crypto_momentum_ai::StrategyArgs;
+use crypto_momentum_ai::config::Resolved;
+use crypto_momentum_ai::strategy::{self, AlignedBar, StopTrigger, stop_exit};
+
+fn bar(open: Option<f64>, high: Option<f64>, low: Option<f64>, close: f64) -> AlignedBar {
+    AlignedBar {
+        open,
+        close,
+        high,
+        low,
+        volume: None,
+        filled: false,
+    }
+}
+
+#[test]
+fn stop_exit_prices() {
+    // Long stop at 90
+    let wick = bar(Some(100.0), Some(101.0), Some(89.0), 99.0);
+    assert_eq!(
+        stop_exit(1, 90.0, &wick),
+        Some((90.0, StopTrigger::Intrabar))
+    );
+    let gap = bar(Some(80.0), Some(85.0), Some(78.0), 84.0);
+    assert_eq!(stop_exit(1, 90.0, &gap), Some((80.0, StopTrigger::Gap)));
+    let clear = bar(Some(100.0), Some(101.0), Some(91.0), 95.0);
+    assert_eq!(stop_exit(1, 90.0, &clear), None);
+    // Close-only data falls back to the close
+    assert_eq!(
+        stop_exit(1, 90.0, &bar(None, None, None, 88.0)),
+        Some((88.0, StopTrigger::Close))
+    );
+    assert_eq!(stop_exit(1, 90.0, &bar(None, None, None, 91.0)), None);
+    // Short stop at 110 mirrors on the high
+    let spike = bar(Some(120.0), Some(125.0), Some(115.0), 118.0);
+    assert_eq!(
+        stop_exit(-1, 110.0, &spike),
+        Some((120.0, StopTrigger::Gap))
+    );
+    let wick = bar(Some(100.0), Some(111.0), Some(99.0), 105.0);
+    assert_eq!(
+        stop_exit(-1, 110.0, &wick),
+        Some((110.0, StopTrigger::Intrabar))
+    );
+}
+
+#[test]
+fn gap_through_the_stop_exits_at_the_open() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (btc, eth) = (
+        tmp.path().join("BTC.csv"),
+        tmp.path().join("ETH_ethereum.csv"),
+    );
+    // ETH outpaces BTC so relative strength is bullish too
+    write_ohlc(&btc, 160, 100.0, 0.01, None);
+    write_ohlc(&eth, 160, 100.0, 0.03, Some(120));
+    let cli = StrategyArgs {
+        btc: Some(btc),
+        assets: Some(vec![eth]),
+        ma_short: Some(5),
+        ma_long: Some(20),
+        record: Some(false),
+        ..StrategyArgs::default()
+    };
+    let args: StrategyArgs = Resolved::from_sources(None, None, &[])
+        .unwrap()
+        .with_cli("strategy", &cli)
+        .unwrap();
+    let result = strategy::backtest(&args).unwrap();
+    let bt = &result.backtest;
+
+    let open = bt.bars["ETH_ethereum"][120].unwrap().open.unwrap();
+    let fill = bt
+        .stop_fills
+        .iter()
+        .find(|f| f.date == bt.dates[120])
+        .expect("stopped on the gap");
+    assert_eq!((fill.trigger, fill.price), (StopTrigger::Gap, open));
+    assert!(fill.slippage() < 0.0 && fill.slippage_r() < -1.0);
+
+    // The trade closes at the gap fill, and the day's loss is taken rather than skipped
+    let trade = bt
+        .trades
+        .iter()
+        .find(|t| t.exit_date == bt.dates[120])
+        .unwrap();
+    assert!(trade.stopped);
+    assert_eq!(trade.exit_price, open);
+    let prev = bt.signals["ETH_ethereum"][119].price();
+    assert!(bt.daily_ret[120] < 0.0);
+    assert!(
+        bt.contributions[120]
+            .iter()
+            .any(|c| (c.asset_return - (open / prev - 1.0)).abs() < 1e-12)
+    );
+    assert!(result.summary.contains("Stops Hit (gap/intrabar/close): "));
+}
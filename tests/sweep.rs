@@ -0,0 +1,124 @@
+//! Grid parsing and the heatmap pivot of parameter sweeps.
+
+use std::fs;
+
+use crypto_momentum_ai::error::Error;
+use crypto_momentum_ai::metrics::Metrics;
+use crypto_momentum_ai::sweep::{self, SweepResult};
+
+fn values(spec: &str) -> Vec<String> {
+    sweep::parse_grid(spec).unwrap().values
+}
+
+#[test]
+fn integer_ranges_are_inclusive() {
+    let grid = sweep::parse_grid("ma-short=2:10:4").unwrap();
+    assert_eq!(grid.name, "ma_short");
+    assert_eq!(grid.values, ["2", "6", "10"]);
+    // A step past the end stops short of it
+    assert_eq!(values("ma_long=20:30:4"), ["20", "24", "28"]);
+    assert_eq!(values("top_k=3:3:1"), ["3"]);
+}
+
+#[test]
+fn float_ranges_do_not_accumulate_rounding() {
+    assert_eq!(values("atr_mult=2:3:0.5"), ["2", "2.5", "3"]);
+    assert_eq!(
+        values("vol_mult=0.1:0.5:0.1"),
+        ["0.1", "0.2", "0.3", "0.4", "0.5"]
+    );
+}
+
+#[test]
+fn lists_keep_their_order_and_validate_every_value() {
+    assert_eq!(values("atr_mult=3, 2 ,2.5"), ["3", "2", "2.5"]);
+    assert_eq!(values("rank_by=rs-slope,return"), ["rs-slope", "return"]);
+    assert_eq!(values("short_alts=true,false"), ["true", "false"]);
+    for bad in [
+        "ma_short=2,x",
+        "ma_short=10:2:1",
+        "ma_short=2:10:0",
+        "ma_short=2:10",
+        "ma_short",
+        "ma_short=",
+        "no_such_field=1,2",
+    ] {
+        assert!(
+            matches!(sweep::parse_grid(bad), Err(Error::Config(_))),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn data_and_output_paths_cannot_be_swept() {
+    for field in ["btc", "assets", "out", "benchmark", "runs_dir", "record"] {
+        let err = sweep::parse_grid(&format!("{field}=a,b")).unwrap_err();
+        assert!(
+            err.to_string().contains("cannot be swept"),
+            "{field}: {err}"
+        );
+    }
+}
+
+fn result(params: [&str; 3], sharpe: Option<f64>) -> SweepResult {
+    SweepResult {
+        params: params.map(str::to_string).to_vec(),
+        metrics: sharpe.map(|sharpe| Metrics {
+            sharpe,
+            cagr: sharpe / 10.0,
+            max_drawdown: sharpe.abs() / 10.0,
+            ..Metrics::default()
+        }),
+        error: sharpe.is_none().then(|| "failed".to_string()),
+        daily_ret: vec![],
+        psr: 0.0,
+        dsr: 0.0,
+    }
+}
+
+#[test]
+fn heatmaps_pivot_two_parameters_on_the_best_of_the_rest() {
+    let grids = sweep::parse_grids(&[
+        "ma_short=2,4".to_string(),
+        "atr_mult=2,3,4".to_string(),
+        "top_k=1".to_string(),
+    ])
+    .unwrap();
+    let results = [
+        result(["2", "2", "1"], Some(0.5)),
+        result(["2", "3", "1"], Some(1.5)),
+        result(["2", "4", "1"], None),
+        result(["4", "2", "1"], Some(1.0)),
+        result(["4", "3", "1"], Some(2.0)),
+        result(["4", "4", "1"], Some(-0.5)),
+    ];
+    let tmp = tempfile::tempdir().unwrap();
+    sweep::write_heatmaps(tmp.path(), &grids, &results).unwrap();
+
+    // Only the pair with two or more values each gets maps, one per metric
+    let mut files: Vec<String> = fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "heatmap_cagr_ma_short_x_atr_mult.csv",
+            "heatmap_max_drawdown_ma_short_x_atr_mult.csv",
+            "heatmap_sharpe_ma_short_x_atr_mult.csv",
+        ]
+    );
+    // Rows are the first parameter, columns the second; the failed point is an empty cell
+    let sharpe = fs::read_to_string(tmp.path().join(&files[2])).unwrap();
+    assert_eq!(
+        sharpe,
+        "ma_short\\atr_mult,2,3,4\n2,0.5000,1.5000,\n4,1.0000,2.0000,-0.5000\n"
+    );
+    let drawdown = fs::read_to_string(tmp.path().join(&files[1])).unwrap();
+    assert_eq!(
+        drawdown,
+        "ma_short\\atr_mult,2,3,4\n2,0.0500,0.1500,\n4,0.1000,0.2000,0.0500\n"
+    );
+}
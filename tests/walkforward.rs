@@ -0,0 +1,83 @@
+//! Fold boundaries and per-fold parameter selection of the walk-forward optimizer.
+
+mod common;
+
+use common::date;
+use crypto_momentum_ai::Objective;
+use crypto_momentum_ai::walkforward::{self, CandidateRun};
+
+#[test]
+fn rolling_folds_slide_the_training_window() {
+    assert_eq!(
+        walkforward::fold_ranges(11, 4, 3, false),
+        vec![(0..4, 4..7), (3..7, 7..10), (6..10, 10..11)]
+    );
+    // Exactly filled: no empty trailing window
+    assert_eq!(
+        walkforward::fold_ranges(10, 4, 3, false),
+        vec![(0..4, 4..7), (3..7, 7..10)]
+    );
+}
+
+#[test]
+fn anchored_folds_grow_from_the_first_date() {
+    assert_eq!(
+        walkforward::fold_ranges(11, 4, 3, true),
+        vec![(0..4, 4..7), (0..7, 7..10), (0..10, 10..11)]
+    );
+}
+
+#[test]
+fn too_little_history_has_no_folds() {
+    assert!(walkforward::fold_ranges(4, 4, 3, false).is_empty());
+    assert!(walkforward::fold_ranges(10, 0, 3, false).is_empty());
+    assert!(walkforward::fold_ranges(10, 4, 0, true).is_empty());
+}
+
+/// 40 days returning `first` a day for 20 days, then `second`.
+fn candidate(name: &str, first: f64, second: f64) -> CandidateRun {
+    CandidateRun {
+        params: vec![name.to_string()],
+        dates: (0..40).map(date).collect(),
+        daily_ret: (0..40)
+            .map(|d| if d < 20 { first } else { second })
+            .collect(),
+        turnover: vec![0.0; 40],
+        exposure: vec![1.0; 40],
+    }
+}
+
+#[test]
+fn each_fold_takes_the_best_training_candidate() {
+    let runs = [
+        candidate("early", 0.01, -0.01),
+        candidate("late", -0.01, 0.01),
+    ];
+    let wf = walkforward::walk_forward(&runs, 10, 10, false, Objective::TotalReturn).unwrap();
+
+    // Training on days 0-9 and 10-19 favours the early leader, 20-29 the late one
+    let picks: Vec<&str> = wf.folds.iter().map(|f| f.params[0].as_str()).collect();
+    assert_eq!(picks, ["early", "early", "late"]);
+    assert_eq!(
+        wf.folds.iter().map(|f| f.test_start).collect::<Vec<_>>(),
+        [date(10), date(20), date(30)]
+    );
+    assert!(wf.folds.iter().all(|f| f.in_sample.total_return > 0.0));
+
+    // The stitched curve carries each pick's test-window returns: the switch comes one fold
+    // late, so days 20-29 lose
+    assert_eq!(wf.oos_dates, (10..40).map(date).collect::<Vec<_>>());
+    let expected: Vec<f64> = (10..40)
+        .map(|d| if (20..30).contains(&d) { -0.01 } else { 0.01 })
+        .collect();
+    assert_eq!(wf.oos_ret, expected);
+    assert_eq!(wf.oos_fold, [[1; 10], [2; 10], [3; 10]].concat());
+    assert!(wf.folds[1].out_of_sample.total_return < 0.0);
+}
+
+#[test]
+fn walk_forward_rejects_short_histories() {
+    let runs = [candidate("only", 0.01, 0.01)];
+    assert!(walkforward::walk_forward(&runs, 40, 10, false, Objective::Sharpe).is_err());
+    assert!(walkforward::walk_forward(&[], 10, 10, false, Objective::Sharpe).is_err());
+}